@@ -0,0 +1,69 @@
+//! Benchmarks for the whole-image operations [`Image::map_pixels`] speeds
+//! up under the `parallel` feature: a convolution kernel, a full-image
+//! recolor, and a palette quantization pass. Run `cargo bench` for the
+//! serial baseline and `cargo bench --features parallel` for the
+//! rayon-parallel version, and compare the two reports.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tie::filter::{self, BLUR};
+use tie::image::Image;
+use tie::palette::{DistanceMetric, PaletteName, PaletteSlots};
+
+const SIZE: u32 = 256;
+
+fn sample_image() -> Image {
+    let mut image = Image::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            image
+                .set_pixel(x, y, [(x % 256) as u8, (y % 256) as u8, 128, 255])
+                .unwrap();
+        }
+    }
+    image
+}
+
+fn bench_convolution(c: &mut Criterion) {
+    let source = sample_image();
+    c.bench_function("blur_256x256", |b| {
+        b.iter(|| {
+            let mut image = source.clone();
+            image.map_pixels(|x, y, pixel| {
+                let _ = pixel;
+                filter::convolve(&source, x, y, BLUR)
+            });
+            image
+        })
+    });
+}
+
+fn bench_recolor(c: &mut Criterion) {
+    let source = sample_image();
+    c.bench_function("recolor_256x256", |b| {
+        b.iter(|| {
+            let mut image = source.clone();
+            image.map_pixels(|_, _, [r, g, b, a]| [255 - r, 255 - g, 255 - b, a]);
+            image
+        })
+    });
+}
+
+fn bench_quantization(c: &mut Criterion) {
+    let source = sample_image();
+    let slots = PaletteSlots::from_preset(PaletteName::Pico8);
+    c.bench_function("quantize_256x256", |b| {
+        b.iter(|| {
+            let mut image = source.clone();
+            image.map_pixels(|_, _, pixel| slots.constrain(pixel, DistanceMetric::Rgb));
+            image
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_convolution,
+    bench_recolor,
+    bench_quantization
+);
+criterion_main!(benches);
@@ -0,0 +1,3488 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::color::Color;
+use crate::colorspace::ColorSpace;
+use crate::command::{self, Command, CommandError};
+use crate::config::Config;
+use crate::crash::{self, CrashContext};
+use crate::display::DisplaySettings;
+use crate::filter::Kernel;
+use crate::floating::Floating;
+use crate::generate;
+use crate::history::History;
+use crate::i18n::Locale;
+use crate::image::{Image, ImageError};
+use crate::keyconfig::MappableAction;
+use crate::palette::{DistanceMetric, PaletteError, PaletteName, PaletteSlots, PAGE_SIZE};
+use crate::palette_state::PaletteState;
+use crate::selection::Selection;
+use crate::session::SessionState;
+use crate::theme::Theme;
+use crate::tool::ToolKind;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Command(#[from] CommandError),
+    #[error(transparent)]
+    Image(#[from] ImageError),
+    #[error("no file path to write to; use :w <path>")]
+    NoPath,
+    #[error("failed to create directory {0}: {1}")]
+    CreateDir(PathBuf, std::io::Error),
+    #[error("failed to write {0}: {1}")]
+    WriteFile(PathBuf, std::io::Error),
+    #[error("no active selection to grab; use :select first")]
+    NoSelection,
+    #[error("nothing has been yanked yet; use :yank first")]
+    NothingToPaste,
+    #[error("no mark '{0}'; use :mark {0} to set it first")]
+    NoSuchMark(char),
+    #[error(transparent)]
+    Palette(#[from] PaletteError),
+    #[error("color #{0:02x}{1:02x}{2:02x} is not in the active palette; use :set lockpalette off to paint freely")]
+    LockedPalette(u8, u8, u8),
+    #[error("{0} pixel color(s) are outside the active palette: {1}")]
+    OutOfPalette(usize, String),
+    #[error("git commit failed: {0}")]
+    GitCommit(String),
+    #[cfg(feature = "network")]
+    #[error(transparent)]
+    Lospec(#[from] crate::lospec::LospecError),
+    #[cfg(feature = "clipboard")]
+    #[error(transparent)]
+    Clipboard(#[from] arboard::Error),
+}
+
+impl AppError {
+    /// The message to show the user, translated where a catalog entry exists.
+    pub fn localized(&self, locale: Locale) -> String {
+        match self {
+            AppError::NoPath => crate::i18n::error_no_path(locale).to_string(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// The input mode the editor is currently in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Command,
+}
+
+/// Which widget [`Mode::Normal`]'s movement keys (`hjkl`) are routed to -
+/// the canvas cursor, or the palette bar's selection and paging. Toggled
+/// with `Tab`, bound by [`App::toggle_focus`]. The inspector overlay has no
+/// input of its own, so it isn't a focus target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Focus {
+    #[default]
+    Canvas,
+    Palette,
+}
+
+/// A single-cell cursor move, as driven by the hjkl movement keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        }
+    }
+
+    fn axis(self) -> Axis {
+        match self {
+            Direction::Left | Direction::Right => Axis::Horizontal,
+            Direction::Up | Direction::Down => Axis::Vertical,
+        }
+    }
+}
+
+/// Which axis cursor movement is locked to, for straight strokes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// How many cells a single drag-paint chord (Shift+hjkl) covers.
+pub const DRAG_STEPS: u32 = 4;
+
+/// Default grid size for grid-snapped movement, in pixels.
+pub const DEFAULT_GRID_SIZE: u32 = 8;
+
+/// How many recently executed command lines are kept for crash reports.
+pub const RECENT_COMMANDS: usize = 20;
+
+/// How many entries [`App::message_log`] keeps before dropping the oldest,
+/// for `:messages`.
+pub const MESSAGE_LOG_CAPACITY: usize = 50;
+
+/// One executed command line and its result, kept in [`App::message_log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub command: String,
+    pub error: Option<String>,
+}
+
+/// The result of [`App::execute_outcome`], for callers - `tie apply`,
+/// eventually macro playback - that drive commands without a UI to read
+/// [`App::status_message`]/[`App::last_error`] back off of afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutcome {
+    /// The status-bar message the command left behind on success, mirroring
+    /// [`App::status_message`].
+    pub message: Option<String>,
+    /// The localized error the command failed with, mirroring
+    /// [`App::last_error`].
+    pub error: Option<String>,
+    /// Whether the image differs from what it was immediately before this
+    /// command ran.
+    pub changed: bool,
+}
+
+/// Whether `(x, y)` should be affected by a selection-respecting operation,
+/// given a snapshot of [`App::selection`]. Free function (rather than an
+/// `App` method) so [`App::filter`] and [`App::map_selected`] can capture
+/// just the selection, not all of `self`, in the closure they hand to
+/// [`Image::map_pixels`].
+fn is_selected(selection: &Option<Selection>, x: u32, y: u32) -> bool {
+    match selection {
+        Some(selection) => selection.contains(x, y),
+        None => true,
+    }
+}
+
+/// Pulls `point` back inside a `width`x`height` image, for any cursor-like
+/// position (the live cursor, a jumped-to mark) that might be stale after a
+/// shrinking edit. A zero-sized image has no in-bounds point at all, so it
+/// maps everything to `(0, 0)` rather than underflowing `width - 1`.
+fn clamp_to_image(point: (u32, u32), width: u32, height: u32) -> (u32, u32) {
+    (
+        if width == 0 { 0 } else { point.0.min(width - 1) },
+        if height == 0 { 0 } else { point.1.min(height - 1) },
+    )
+}
+
+/// Wraps `s` in single quotes for safe interpolation into a `sh -c` string,
+/// escaping any single quotes it contains. [`App::run_post_save_cmd`] uses
+/// this on the save path before substituting it into the user's template,
+/// since the path isn't always one the user typed themselves (it can come
+/// from an opened file's directory name) and the template still needs shell
+/// features like `&&` and `|` to work.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Top-level editor state: the image being edited, its undo history, the
+/// cursor, and whatever the command line currently contains.
+pub struct App {
+    pub history: History,
+    pub path: Option<PathBuf>,
+    pub cursor: (u32, u32),
+    /// Additional cursors that every paint operation (`x`, pen-down
+    /// movement, drag-paint) applies at alongside [`App::cursor`], for
+    /// repeating patterns and tile borders. Set via `:cursor add`/`:cursor
+    /// mirror` and cleared with `:cursor clear`.
+    pub extra_cursors: Vec<(u32, u32)>,
+    /// Cursor positions bookmarked with `:mark <letter>`, jumped back to
+    /// with `'<letter>` in normal mode.
+    pub marks: HashMap<char, (u32, u32)>,
+    /// Whether the next key is the letter completing a `'<letter>` jump to
+    /// a mark. Set by the `'` key in normal mode.
+    pub pending_mark_jump: bool,
+    /// Columns with a persistent vertical guide, toggled with `:guide x
+    /// <n>`, for lining up features in a big composition.
+    pub x_guides: Vec<u32>,
+    /// Rows with a persistent horizontal guide, toggled with `:guide y
+    /// <n>`.
+    pub y_guides: Vec<u32>,
+    pub mode: Mode,
+    pub command_line: String,
+    /// The edit position within [`App::command_line`], in chars - lets
+    /// Left/Right/Home/End/Ctrl-w move and delete within the line instead
+    /// of only at its end.
+    pub command_cursor: usize,
+    pub should_quit: bool,
+    /// Color applied by paint actions (`x`, pen-down movement, drag-paint).
+    pub active_color: [u8; 4],
+    /// When set, plain hjkl movement paints the cell it lands on.
+    pub pen_down: bool,
+    /// When set, hjkl movement jumps by [`App::grid_size`] pixels instead of
+    /// one, for navigating between tiles on a sprite sheet. Toggled by `G`.
+    pub grid_snap: bool,
+    /// How many pixels a grid-snapped move covers, set via `:set grid N`.
+    pub grid_size: u32,
+    /// When set, movement is locked to whichever axis the cursor first
+    /// moves along, for easy straight strokes. Toggled by `A`; the axis
+    /// itself is re-picked the next time the cursor moves after toggling on.
+    pub axis_lock: bool,
+    locked_axis: Option<Axis>,
+    /// The pixels changed by the most recent edit, as `(x, y, new_color)`,
+    /// used by `:repeat` to stamp the same change again at an offset.
+    last_change: Vec<(u32, u32, [u8; 4])>,
+    /// The active selection, if any. `None` means editing operations apply
+    /// to the whole image.
+    pub selection: Option<Selection>,
+    /// A region grabbed for moving, previewed live over the canvas until
+    /// dropped (Enter) or cancelled (Esc).
+    pub floating: Option<Floating>,
+    /// The opaque pixels most recently yanked via `:yank`, relative to the
+    /// selection's top-left corner so `:paste` can re-anchor them at the
+    /// cursor - including in the same buffer after further edits. A single
+    /// in-memory clipboard, scoped to this `App`; this is the piece a
+    /// future multi-buffer/tab feature would need to share across buffers.
+    pub clipboard: Option<Vec<(i32, i32, [u8; 4])>>,
+    /// How transparent canvas cells are shown (`:set checker`, `:set bgcolor`).
+    pub display: DisplaySettings,
+    /// Colors for the canvas border, command line, status bar, and selection
+    /// highlight; loaded from the config file and switchable with `:theme`.
+    pub theme: Theme,
+    /// UI language for hints and messages, loaded from the config file.
+    pub locale: Locale,
+    /// Whether `:adjust` works in linear light or on raw sRGB bytes.
+    pub colorspace: ColorSpace,
+    /// A fixed color palette that paint operations snap to, set via
+    /// `:palette use`.
+    pub palette: PaletteName,
+    /// The active palette's cells, as a mutable working copy that supports
+    /// per-session naming (`:palette name`), swapping (`:palette swap`),
+    /// and named overrides (`:set <name> r g b`) - reset to `palette`'s
+    /// fixed colors whenever `:palette use` switches presets.
+    pub palette_slots: PaletteSlots,
+    /// The palette cell cycled to with `[`/`]` and painted with by `P`, as
+    /// an alternative to the one-key-per-cell digit bindings - shown with a
+    /// reversed-video marker in the palette bar. Reset whenever `:palette
+    /// use` switches presets.
+    pub selected_palette_cell: Option<u8>,
+    /// Which block of [`PAGE_SIZE`] palette cells the digit keys `0`-`9`
+    /// currently address, switched with `F1`-`F4` - lets a palette with
+    /// more than ten cells stay fully keyboard-reachable. Reset whenever
+    /// `:palette use` switches presets.
+    pub palette_page: u8,
+    /// Which widget `hjkl` currently drives - toggled with `Tab`. See
+    /// [`Focus`].
+    pub focus: Focus,
+    /// The active editing tool, switched with `:tool` - changes the
+    /// canvas cursor's glyph. See [`ToolKind`].
+    pub active_tool: ToolKind,
+    /// When set, `:set_pixel`/paint operations reject colors that aren't
+    /// already in the active palette instead of snapping to the nearest
+    /// one - set via `:set lockpalette on|off`, for strict retro workflows.
+    pub lock_palette: bool,
+    /// How closeness is measured when snapping paint to the nearest
+    /// palette cell, set via `:set palettemetric`.
+    pub palette_metric: DistanceMetric,
+    /// Per-file remembered palettes, persisted to `palette_state.toml`.
+    pub palette_state: PaletteState,
+    /// Whether setting the palette should be remembered for `path` and
+    /// restored next time it's opened; loaded from the config file.
+    pub remember_palette: bool,
+    /// A shell command run after every successful save, with `{file}`
+    /// replaced by the saved path; loaded from the config file. See
+    /// [`Config::post_save_cmd`].
+    pub post_save_cmd: Option<String>,
+    /// Per-file remembered cursor position and most recently opened file,
+    /// persisted to `session_state.toml` when the editor quits.
+    pub session_state: SessionState,
+    /// Localized message from the last failed command, shown in the status bar.
+    pub last_error: Option<String>,
+    /// A transient, non-error message shown in the status bar in place of
+    /// the usual key-binding hint until the next command replaces or
+    /// clears it - either an explicit message (e.g. after copying the
+    /// color under the cursor) or, echoed back by [`App::execute`] itself,
+    /// the command line that was just run successfully.
+    pub status_message: Option<String>,
+    /// The offending token from the last failed command's
+    /// [`CommandError::InvalidArgument`], if any - highlighted in the
+    /// command line so the user can see exactly what to fix, instead of
+    /// the line being cleared when the command failed.
+    pub invalid_token: Option<String>,
+    /// The last [`RECENT_COMMANDS`] command lines, oldest first - kept for
+    /// crash reports.
+    pub recent_commands: VecDeque<String>,
+    /// The last [`MESSAGE_LOG_CAPACITY`] executed command lines and their
+    /// results, oldest first - shown by the `:messages` overlay.
+    pub message_log: VecDeque<Message>,
+    /// Whether the `:messages` overlay is showing [`App::message_log`]
+    /// below the canvas.
+    pub show_messages: bool,
+    /// Keys rebound to a [`MappableAction`] via `:map`, consulted by
+    /// normal-mode key handling before its built-in bindings - lets a user
+    /// remap the small vocabulary `:map` supports without editing code.
+    pub key_map: HashMap<char, MappableAction>,
+    /// A save path awaiting the user's "overwrite existing file? y/n"
+    /// confirmation, set by `:w <path>` when `path` already exists.
+    /// [`App::confirm_overwrite`] and [`App::cancel_overwrite`] resolve it.
+    pub pending_overwrite: Option<PathBuf>,
+    /// Set by `:revert` when there are unsaved changes, awaiting the user's
+    /// "discard changes and reload? y/n" confirmation. [`App::confirm_revert`]
+    /// and [`App::cancel_revert`] resolve it.
+    pub pending_revert: bool,
+    /// Suppresses the status bar's "begin input command" hint, set from
+    /// `--quiet` for scripted/recorded sessions where it isn't useful.
+    pub quiet: bool,
+    /// Whether the `:histogram` overlay is showing per-channel value
+    /// histograms of the current image below the canvas.
+    pub show_histogram: bool,
+    /// Whether the `:inspect` overlay is showing a magnified neighborhood
+    /// of pixels around the cursor, each labelled with its exact hex color.
+    pub show_inspector: bool,
+    /// Whether the `:toolbar` overlay is listing the available tools and
+    /// their hotkeys below the canvas, highlighting [`App::active_tool`].
+    pub show_toolbar: bool,
+    /// When set, consecutive paints during pen-down movement or a
+    /// drag-paint chord are corrected to erase the corner of an L-shaped
+    /// turn, so diagonal strokes read as a clean staircase - set via `:set
+    /// pixelperfect on|off`, as Aseprite's "pixel perfect" mode does.
+    pub pixel_perfect: bool,
+    /// The last two positions painted by [`App::paint_at_cursors`], oldest
+    /// first, along with the color each held just before that paint -
+    /// tracked so [`App::pixel_perfect`] correction can detect an L-shaped
+    /// corner and revert it. Cleared whenever a stroke boundary is crossed,
+    /// e.g. [`App::toggle_pen`].
+    stroke_trail: Vec<((u32, u32), [u8; 4])>,
+}
+
+impl App {
+    pub fn new(image: Image, path: Option<PathBuf>) -> Self {
+        let config = Config::load();
+        let palette_state = PaletteState::load();
+        let palette = path
+            .as_deref()
+            .filter(|_| config.remember_palette)
+            .and_then(|p| palette_state.get(p))
+            .unwrap_or(config.palette);
+        let session_state = SessionState::load();
+        let cursor = path
+            .as_deref()
+            .and_then(|p| session_state.cursor(p))
+            .filter(|&(x, y)| x < image.width() && y < image.height())
+            .unwrap_or((0, 0));
+        Self {
+            history: History::new(image),
+            path,
+            cursor,
+            extra_cursors: Vec::new(),
+            marks: HashMap::new(),
+            pending_mark_jump: false,
+            x_guides: Vec::new(),
+            y_guides: Vec::new(),
+            mode: Mode::Normal,
+            command_line: String::new(),
+            command_cursor: 0,
+            should_quit: false,
+            active_color: [255, 255, 255, 255],
+            pen_down: false,
+            grid_snap: false,
+            grid_size: DEFAULT_GRID_SIZE,
+            axis_lock: false,
+            locked_axis: None,
+            last_change: Vec::new(),
+            selection: None,
+            floating: None,
+            clipboard: None,
+            display: DisplaySettings::default(),
+            theme: Theme::named(config.theme),
+            locale: config.locale.unwrap_or_else(Locale::from_env),
+            colorspace: config.colorspace,
+            palette,
+            palette_slots: PaletteSlots::from_preset(palette),
+            selected_palette_cell: None,
+            palette_page: 0,
+            focus: Focus::default(),
+            active_tool: ToolKind::default(),
+            lock_palette: false,
+            palette_metric: DistanceMetric::default(),
+            palette_state,
+            remember_palette: config.remember_palette,
+            post_save_cmd: config.post_save_cmd,
+            session_state,
+            last_error: None,
+            status_message: None,
+            invalid_token: None,
+            recent_commands: VecDeque::with_capacity(RECENT_COMMANDS),
+            message_log: VecDeque::with_capacity(MESSAGE_LOG_CAPACITY),
+            show_messages: false,
+            key_map: HashMap::new(),
+            pending_overwrite: None,
+            pending_revert: false,
+            quiet: false,
+            show_histogram: false,
+            show_inspector: false,
+            show_toolbar: false,
+            pixel_perfect: false,
+            stroke_trail: Vec::new(),
+        }
+    }
+
+    /// Whether `(x, y)` should be affected by a selection-respecting operation.
+    fn is_selected(&self, x: u32, y: u32) -> bool {
+        is_selected(&self.selection, x, y)
+    }
+
+    pub fn image(&self) -> &Image {
+        self.history.current()
+    }
+
+    /// Pushes `next` onto the undo history, recording which pixels changed
+    /// so `:repeat` can stamp the same edit again at an offset.
+    fn commit(&mut self, next: Image) {
+        let current = self.image();
+        let mut changed = Vec::new();
+        for y in 0..next.height().min(current.height()) {
+            for x in 0..next.width().min(current.width()) {
+                let before = current.get_pixel(x, y).expect("in bounds");
+                let after = next.get_pixel(x, y).expect("in bounds");
+                if before != after {
+                    changed.push((x, y, after));
+                }
+            }
+        }
+        self.last_change = changed;
+        self.cursor = clamp_to_image(self.cursor, next.width(), next.height());
+        self.history.push(next);
+    }
+
+    /// Deletes the row the cursor is on, for [`Command::DeleteRow`],
+    /// pulling the cursor back onto the last remaining row if it had been
+    /// on the one just deleted.
+    fn delete_row(&mut self) -> Result<(), AppError> {
+        let y = self.cursor.1;
+        let next = self.image().without_row(y)?;
+        self.cursor.1 = self.cursor.1.min(next.height() - 1);
+        self.commit(next);
+        Ok(())
+    }
+
+    /// The column counterpart to [`App::delete_row`], for
+    /// [`Command::DeleteColumn`].
+    fn delete_column(&mut self) -> Result<(), AppError> {
+        let x = self.cursor.0;
+        let next = self.image().without_column(x)?;
+        self.cursor.0 = self.cursor.0.min(next.width() - 1);
+        self.commit(next);
+        Ok(())
+    }
+
+    /// Inserts a blank row before the cursor's row, for
+    /// [`Command::InsertRow`]. The cursor doesn't need to move: it keeps
+    /// pointing at the same row index, which now holds the new blank row.
+    fn insert_row(&mut self) -> Result<(), AppError> {
+        let next = self.image().with_inserted_row(self.cursor.1)?;
+        self.commit(next);
+        Ok(())
+    }
+
+    /// The column counterpart to [`App::insert_row`], for
+    /// [`Command::InsertColumn`].
+    fn insert_column(&mut self) -> Result<(), AppError> {
+        let next = self.image().with_inserted_column(self.cursor.0)?;
+        self.commit(next);
+        Ok(())
+    }
+
+    /// Re-applies the most recent edit's pixel changes `n` more times, each
+    /// offset by `(dx, dy)` from the last - for stamping fences, bricks, and
+    /// dotted lines. A no-op if nothing has been painted yet.
+    pub fn repeat(&mut self, dx: i32, dy: i32, n: u32) {
+        let pattern = self.last_change.clone();
+        if pattern.is_empty() {
+            return;
+        }
+        for step in 1..=i64::from(n) {
+            let mut next = self.image().clone();
+            for &(x, y, color) in &pattern {
+                let nx = x as i64 + i64::from(dx) * step;
+                let ny = y as i64 + i64::from(dy) * step;
+                if nx < 0 || ny < 0 || nx as u32 >= next.width() || ny as u32 >= next.height() {
+                    continue;
+                }
+                let _ = next.set_pixel(nx as u32, ny as u32, color);
+            }
+            self.commit(next);
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) -> Result<(), AppError> {
+        let color = self.constrain_for_paint(color)?;
+        let mut next = self.image().clone();
+        next.set_pixel(x, y, color)?;
+        self.commit(next);
+        Ok(())
+    }
+
+    /// Snaps `color` to the active palette, as every paint operation does -
+    /// unless [`App::lock_palette`] is set, in which case a color that
+    /// isn't already an exact palette entry is rejected instead of snapped.
+    /// A no-op (returns `color` unchanged) when no palette is active.
+    fn constrain_for_paint(&self, color: [u8; 4]) -> Result<[u8; 4], AppError> {
+        if self.lock_palette
+            && !self.palette_slots.colors().is_empty()
+            && !self.palette_slots.contains(color)
+        {
+            let [r, g, b, _a] = color;
+            return Err(AppError::LockedPalette(r, g, b));
+        }
+        Ok(self.palette_slots.constrain(color, self.palette_metric))
+    }
+
+    /// Paints `color` at the primary cursor and every extra cursor in a
+    /// single history step, so one undo reverts the whole stroke. Extra
+    /// cursors that land out of bounds are silently skipped. When
+    /// [`App::pixel_perfect`] is on, also corrects the primary cursor's path
+    /// for an L-shaped corner (see [`App::correct_pixel_perfect_corner`]).
+    /// On failure - e.g. the color isn't in a locked palette - sets
+    /// [`App::last_error`], the same as the other keybound actions that can
+    /// fail, so a rejected paint shows up in the status bar instead of
+    /// silently doing nothing (`main.rs` discards this method's `Result`).
+    pub fn paint_at_cursors(&mut self, color: [u8; 4]) -> Result<(), AppError> {
+        let result = self.paint_at_cursors_impl(color);
+        self.last_error = result.as_ref().err().map(|e| e.localized(self.locale));
+        result
+    }
+
+    fn paint_at_cursors_impl(&mut self, color: [u8; 4]) -> Result<(), AppError> {
+        let color = self.constrain_for_paint(color)?;
+        let mut next = self.image().clone();
+        let (x, y) = self.cursor;
+        let before = next.get_pixel(x, y).expect("in bounds");
+        next.set_pixel(x, y, color)?;
+        for &(x, y) in &self.extra_cursors {
+            let _ = next.set_pixel(x, y, color);
+        }
+        if self.pixel_perfect {
+            self.correct_pixel_perfect_corner(&mut next, (x, y));
+        }
+        self.stroke_trail.push(((x, y), before));
+        if self.stroke_trail.len() > 2 {
+            self.stroke_trail.remove(0);
+        }
+        self.commit(next);
+        Ok(())
+    }
+
+    /// If the primary cursor's last two painted positions plus `point` form
+    /// an L-shaped corner - two diagonally adjacent pixels joined by a
+    /// single orthogonal step - reverts the corner pixel to the color it
+    /// held before that step was painted, the way Aseprite's "pixel
+    /// perfect" stroke correction does.
+    fn correct_pixel_perfect_corner(&self, next: &mut Image, point: (u32, u32)) {
+        let [((x0, y0), _), ((x1, y1), before1)] = self.stroke_trail[..] else {
+            return;
+        };
+        let (x2, y2) = point;
+        let is_corner = x2.abs_diff(x0) == 1
+            && y2.abs_diff(y0) == 1
+            && ((x1, y1) == (x2, y0) || (x1, y1) == (x0, y2));
+        if is_corner {
+            let _ = next.set_pixel(x1, y1, before1);
+        }
+    }
+
+    /// Sets [`App::active_color`] to the palette cell at `digit` on
+    /// [`App::palette_page`], keeping the current alpha - bound to the
+    /// digit keys `0`-`9`, matching the key labels
+    /// [`PaletteBar`](crate::palette_widget::PaletteBar) draws over each
+    /// swatch. A no-op if the resulting cell is out of range (e.g. no
+    /// palette is active, or the page has fewer than `digit + 1` cells).
+    pub fn select_palette_cell(&mut self, digit: u8) {
+        let index = self.palette_page as usize * PAGE_SIZE as usize + digit as usize;
+        if let Some(&[r, g, b]) = self.palette_slots.colors().get(index) {
+            let [.., a] = self.active_color;
+            self.active_color = [r, g, b, a];
+        }
+    }
+
+    /// Switches [`App::palette_page`] to `page`, clamped to the last page
+    /// the active palette actually has - bound to `F1`-`F4` for pages
+    /// `0`-`3`, so a palette with more than ten cells stays fully
+    /// keyboard-reachable.
+    pub fn set_palette_page(&mut self, page: u8) {
+        self.palette_page = page.min(self.palette_slots.page_count() - 1);
+    }
+
+    /// Moves [`App::palette_page`] forward or backward by one, wrapping
+    /// around - the [`Focus::Palette`] counterpart to `j`/`k`'s canvas
+    /// cursor movement.
+    pub fn step_palette_page(&mut self, forward: bool) {
+        let page_count = self.palette_slots.page_count();
+        let next = if forward {
+            (self.palette_page + 1) % page_count
+        } else {
+            (self.palette_page + page_count - 1) % page_count
+        };
+        self.set_palette_page(next);
+    }
+
+    /// Switches [`App::focus`] to the other widget - bound to `Tab`.
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Canvas => Focus::Palette,
+            Focus::Palette => Focus::Canvas,
+        };
+    }
+
+    /// Switches [`App::active_tool`] directly - bound to `F5`-`F7`, one per
+    /// [`ToolKind`] variant, same as `:tool`.
+    pub fn set_tool(&mut self, tool: ToolKind) {
+        self.active_tool = tool;
+    }
+
+    /// Cycles [`App::selected_palette_cell`] forward or backward, wrapping
+    /// around - bound to `]`/`[`. A no-op if no palette is active.
+    pub fn cycle_palette_selection(&mut self, forward: bool) {
+        let len = self.palette_slots.colors().len();
+        if len == 0 {
+            return;
+        }
+        let next = match self.selected_palette_cell {
+            None => 0,
+            Some(index) => {
+                let index = index as usize;
+                if forward {
+                    (index + 1) % len
+                } else {
+                    (index + len - 1) % len
+                }
+            }
+        };
+        self.selected_palette_cell = Some(next as u8);
+    }
+
+    /// Paints at the cursor(s) with [`App::selected_palette_cell`], leaving
+    /// [`App::active_color`] untouched - bound to `P`. A no-op if no cell
+    /// is selected.
+    pub fn paint_with_selected_cell(&mut self) -> Result<(), AppError> {
+        let Some(index) = self.selected_palette_cell else {
+            return Ok(());
+        };
+        let Some(&[r, g, b]) = self.palette_slots.colors().get(index as usize) else {
+            return Ok(());
+        };
+        let [.., a] = self.active_color;
+        self.paint_at_cursors([r, g, b, a])
+    }
+
+    /// Scans the whole image for pixel colors that aren't exactly in the
+    /// active palette, for `:palette check` - useful after `:set
+    /// lockpalette on` was turned on partway through editing, or after
+    /// importing artwork drawn outside the palette. A no-op when no
+    /// palette is active or every pixel already matches a cell.
+    pub fn check_palette(&self) -> Result<(), AppError> {
+        let colors = self.palette_slots.colors();
+        if colors.is_empty() {
+            return Ok(());
+        }
+        let image = self.image();
+        let mut offenders = Vec::new();
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let pixel = image.get_pixel(x, y).expect("in bounds");
+                if !self.palette_slots.contains(pixel) && !offenders.contains(&pixel) {
+                    offenders.push(pixel);
+                }
+            }
+        }
+        if offenders.is_empty() {
+            return Ok(());
+        }
+        const MAX_LISTED: usize = 8;
+        let mut list = offenders
+            .iter()
+            .take(MAX_LISTED)
+            .map(|&color| Color::from(color).hex())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if offenders.len() > MAX_LISTED {
+            list.push_str(", ...");
+        }
+        Err(AppError::OutOfPalette(offenders.len(), list))
+    }
+
+    /// Replaces the working palette with the distinct colors found in
+    /// another image file, so a whole sprite set can share one project's
+    /// colors. No longer tied to a built-in preset, so [`App::palette`] is
+    /// reset to [`PaletteName::None`].
+    fn palette_from_file(&mut self, path: &Path) -> Result<(), AppError> {
+        let colors = Image::load(path)?
+            .palette()
+            .into_iter()
+            .map(|[r, g, b, _a]| [r, g, b])
+            .collect();
+        self.palette = PaletteName::None;
+        self.palette_slots.set_colors(colors);
+        self.selected_palette_cell = None;
+        Ok(())
+    }
+
+    /// Replaces the working palette with a community palette fetched from
+    /// Lospec by `slug`, falling back to a cached copy if offline. Like
+    /// [`App::palette_from_file`], no longer tied to a built-in preset, so
+    /// [`App::palette`] is reset to [`PaletteName::None`].
+    #[cfg(feature = "network")]
+    fn palette_lospec(&mut self, slug: &str) -> Result<(), AppError> {
+        let colors = crate::lospec::fetch(slug)?;
+        self.palette = PaletteName::None;
+        self.palette_slots.set_colors(colors);
+        self.selected_palette_cell = None;
+        Ok(())
+    }
+
+    /// Adds an extra cursor at `(x, y)`, alongside [`App::cursor`].
+    pub fn add_cursor(&mut self, x: u32, y: u32) {
+        self.extra_cursors.push((x, y));
+    }
+
+    /// Drops every extra cursor, leaving only the primary one.
+    pub fn clear_extra_cursors(&mut self) {
+        self.extra_cursors.clear();
+    }
+
+    /// Replaces the extra cursors with a grid spaced `spacing` pixels apart
+    /// in both axes, anchored so it passes through the primary cursor - for
+    /// painting repeating patterns and tile borders in one pass. A `spacing`
+    /// of `0` just clears the extra cursors.
+    pub fn mirror_cursors(&mut self, spacing: u32) {
+        self.extra_cursors.clear();
+        if spacing == 0 {
+            return;
+        }
+        let (cx, cy) = self.cursor;
+        let width = self.image().width();
+        let height = self.image().height();
+        let mut x = cx % spacing;
+        while x < width {
+            let mut y = cy % spacing;
+            while y < height {
+                if (x, y) != (cx, cy) {
+                    self.extra_cursors.push((x, y));
+                }
+                y += spacing;
+            }
+            x += spacing;
+        }
+    }
+
+    /// Bookmarks the cursor's current position under `letter`, overwriting
+    /// any existing mark of the same letter.
+    pub fn mark(&mut self, letter: char) {
+        self.marks.insert(letter, self.cursor);
+    }
+
+    /// Jumps the cursor back to the position bookmarked under `letter`.
+    /// Bound to `'<letter>` in normal mode. Clamped to the current image's
+    /// bounds, since a shrinking edit (`:autocrop`, `:delrow`, `:delcol`)
+    /// made after the mark was set can leave it pointing outside the image.
+    pub fn jump_to_mark(&mut self, letter: char) -> Result<(), AppError> {
+        let result = self
+            .marks
+            .get(&letter)
+            .copied()
+            .ok_or(AppError::NoSuchMark(letter));
+        match result {
+            Ok((x, y)) => {
+                let image = self.image();
+                self.cursor = clamp_to_image((x, y), image.width(), image.height());
+                self.last_error = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.last_error = Some(err.localized(self.locale));
+                Err(err)
+            }
+        }
+    }
+
+    /// Toggles a persistent guide line at `position` on `axis` - adding it
+    /// if absent, removing it if already set. `Axis::Both` toggles the same
+    /// `position` on both the vertical and horizontal guides.
+    pub fn toggle_guide(&mut self, axis: crate::image::Axis, position: u32) {
+        use crate::image::Axis;
+        let toggle = |guides: &mut Vec<u32>| {
+            if let Some(index) = guides.iter().position(|&g| g == position) {
+                guides.remove(index);
+            } else {
+                guides.push(position);
+            }
+        };
+        match axis {
+            Axis::X => toggle(&mut self.x_guides),
+            Axis::Y => toggle(&mut self.y_guides),
+            Axis::Both => {
+                toggle(&mut self.x_guides);
+                toggle(&mut self.y_guides);
+            }
+        }
+    }
+
+    /// Moves the cursor one cell in `dir`, clamped to the image bounds. Paints
+    /// the landing cell (and every extra cursor) if pen-down is toggled on.
+    pub fn move_cursor(&mut self, dir: Direction) {
+        self.move_cursor_by(dir, 1);
+    }
+
+    /// Moves the cursor `steps` cells in `dir` in one go - the ramp for a
+    /// held movement key, via `keyconfig::RepeatAccelerator` in `main.rs`.
+    /// Paints every cell passed through (and every extra cursor) if
+    /// pen-down is toggled on, same as [`App::move_cursor`] but covering
+    /// more ground per call.
+    pub fn move_cursor_by(&mut self, dir: Direction, steps: u32) {
+        for _ in 0..steps.max(1) {
+            let before = self.cursor;
+            self.step_cursor(dir);
+            if self.pen_down {
+                let _ = self.paint_at_cursors(self.active_color);
+            }
+            if self.cursor == before {
+                break;
+            }
+        }
+    }
+
+    /// Toggles pen-down movement painting, also clearing
+    /// [`App::pixel_perfect`] correction's stroke trail so a new stroke
+    /// doesn't get corrected against the tail of the previous one.
+    pub fn toggle_pen(&mut self) {
+        self.pen_down = !self.pen_down;
+        self.stroke_trail.clear();
+    }
+
+    /// Moves the cursor up to `steps` cells in `dir` in one go, painting every
+    /// cell passed through (and every extra cursor). Distinct from the
+    /// pen-down toggle: this is a single chord for a quick straight stroke,
+    /// regardless of pen-down state.
+    pub fn drag(&mut self, dir: Direction, steps: u32) {
+        for _ in 0..steps {
+            let before = self.cursor;
+            self.step_cursor(dir);
+            if self.cursor == before {
+                break;
+            }
+            let _ = self.paint_at_cursors(self.active_color);
+        }
+    }
+
+    /// Toggles whether hjkl movement jumps by [`App::grid_size`] pixels
+    /// instead of one.
+    pub fn toggle_grid_snap(&mut self) {
+        self.grid_snap = !self.grid_snap;
+    }
+
+    /// Toggles whether movement is locked to a single axis. The axis is
+    /// re-picked from the first move made while the lock is on.
+    pub fn toggle_axis_lock(&mut self) {
+        self.axis_lock = !self.axis_lock;
+        self.locked_axis = None;
+    }
+
+    fn step_cursor(&mut self, dir: Direction) {
+        if self.axis_lock {
+            match self.locked_axis {
+                Some(axis) if axis != dir.axis() => return,
+                _ => self.locked_axis = Some(dir.axis()),
+            }
+        }
+        let (dx, dy) = dir.delta();
+        let step = if self.grid_snap {
+            self.grid_size.max(1) as i32
+        } else {
+            1
+        };
+        let (x, y) = self.cursor;
+        let (nx, ny) = (x as i32 + dx * step, y as i32 + dy * step);
+        if nx >= 0
+            && ny >= 0
+            && (nx as u32) < self.image().width()
+            && (ny as u32) < self.image().height()
+        {
+            self.cursor = (nx as u32, ny as u32);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        self.history.undo();
+    }
+
+    pub fn redo(&mut self) {
+        self.history.redo();
+    }
+
+    /// Clears the command line and resets the edit cursor to its start -
+    /// used when entering [`Mode::Command`] and when it's cancelled.
+    pub fn command_clear(&mut self) {
+        self.command_line.clear();
+        self.command_cursor = 0;
+    }
+
+    /// Inserts pasted `text` at [`App::command_cursor`], for bracketed
+    /// paste of file paths and color codes into the `:` prompt. Newlines
+    /// and other control characters are stripped, since a command line is
+    /// always a single line.
+    pub fn command_paste(&mut self, text: &str) {
+        for c in text.chars().filter(|c| !c.is_control()) {
+            self.command_insert(c);
+        }
+    }
+
+    /// Inserts `c` at [`App::command_cursor`] and advances it.
+    pub fn command_insert(&mut self, c: char) {
+        let index = self.command_byte_index();
+        self.command_line.insert(index, c);
+        self.command_cursor += 1;
+    }
+
+    /// Deletes the character before [`App::command_cursor`], if any -
+    /// bound to Backspace.
+    pub fn command_backspace(&mut self) {
+        if self.command_cursor == 0 {
+            return;
+        }
+        self.command_cursor -= 1;
+        let index = self.command_byte_index();
+        self.command_line.remove(index);
+    }
+
+    /// Moves the edit cursor one character left, stopping at the start.
+    pub fn command_move_left(&mut self) {
+        self.command_cursor = self.command_cursor.saturating_sub(1);
+    }
+
+    /// Moves the edit cursor one character right, stopping at the end.
+    pub fn command_move_right(&mut self) {
+        self.command_cursor = (self.command_cursor + 1).min(self.command_line.chars().count());
+    }
+
+    /// Moves the edit cursor to the start of the line.
+    pub fn command_move_home(&mut self) {
+        self.command_cursor = 0;
+    }
+
+    /// Moves the edit cursor to the end of the line.
+    pub fn command_move_end(&mut self) {
+        self.command_cursor = self.command_line.chars().count();
+    }
+
+    /// Deletes the word (and any whitespace) immediately before the edit
+    /// cursor - bound to Ctrl-w, readline-style.
+    pub fn command_delete_word_backward(&mut self) {
+        let chars: Vec<char> = self.command_line.chars().collect();
+        let mut start = self.command_cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let mut kept: String = chars[..start].iter().collect();
+        kept.extend(&chars[self.command_cursor..]);
+        self.command_line = kept;
+        self.command_cursor = start;
+    }
+
+    /// The byte offset in [`App::command_line`] corresponding to
+    /// [`App::command_cursor`] chars in.
+    fn command_byte_index(&self) -> usize {
+        self.command_line
+            .char_indices()
+            .nth(self.command_cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.command_line.len())
+    }
+
+    /// Parses and runs a command line typed by the user (without the leading `:`).
+    pub fn execute(&mut self, line: &str) -> Result<(), AppError> {
+        self.status_message = None;
+        self.recent_commands.push_back(line.to_string());
+        if self.recent_commands.len() > RECENT_COMMANDS {
+            self.recent_commands.pop_front();
+        }
+        let result = command::parse(line)
+            .map_err(AppError::from)
+            .and_then(|cmd| self.run(cmd));
+        self.last_error = result.as_ref().err().map(|e| e.localized(self.locale));
+        self.invalid_token = match &result {
+            Err(AppError::Command(CommandError::InvalidArgument(token))) => Some(token.clone()),
+            _ => None,
+        };
+        self.message_log.push_back(Message {
+            command: line.to_string(),
+            error: self.last_error.clone(),
+        });
+        if self.message_log.len() > MESSAGE_LOG_CAPACITY {
+            self.message_log.pop_front();
+        }
+        if result.is_ok() && self.status_message.is_none() {
+            self.status_message = Some(format!(":{line}"));
+        }
+        crash::update_context(CrashContext::capture(self));
+        result
+    }
+
+    /// Like [`App::execute`], but returns the outcome as a [`CommandOutcome`]
+    /// instead of a bare `Result` - for headless/scripting callers (`tie
+    /// apply`) that want the message and whether the image changed without
+    /// re-deriving them from [`App::status_message`]/[`App::is_dirty`]
+    /// themselves.
+    pub fn execute_outcome(&mut self, line: &str) -> CommandOutcome {
+        let before = self.image().clone();
+        self.execute(line).ok();
+        CommandOutcome {
+            message: self.status_message.clone(),
+            error: self.last_error.clone(),
+            changed: self.image() != &before,
+        }
+    }
+
+    fn run(&mut self, cmd: Command) -> Result<(), AppError> {
+        match cmd {
+            Command::Quit => {
+                if let Some(path) = self.path.clone() {
+                    self.session_state.set_cursor(path, self.cursor);
+                    self.session_state.save();
+                }
+                self.should_quit = true;
+            }
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            Command::ToggleHistogram => self.show_histogram = !self.show_histogram,
+            Command::ToggleInspector => self.show_inspector = !self.show_inspector,
+            Command::ToggleMessages => self.show_messages = !self.show_messages,
+            Command::ToggleToolbar => self.show_toolbar = !self.show_toolbar,
+            Command::Help(topic) => {
+                self.status_message = Some(command::help_text(topic.as_deref()))
+            }
+            Command::Map(None) => {
+                self.status_message = Some(if self.key_map.is_empty() {
+                    "no custom key bindings".to_string()
+                } else {
+                    let mut bindings: Vec<_> = self.key_map.iter().collect();
+                    bindings.sort_by_key(|(key, _)| **key);
+                    bindings
+                        .into_iter()
+                        .map(|(key, action)| format!("{key} {}", action.name()))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                });
+            }
+            Command::Map(Some((key, action))) => {
+                self.status_message = Some(match self.key_map.insert(key, action) {
+                    Some(previous) if previous != action => format!(
+                        "{key} was bound to {}; now bound to {}",
+                        previous.name(),
+                        action.name()
+                    ),
+                    _ => format!("{key} bound to {}", action.name()),
+                });
+            }
+            Command::Unmap(key) => {
+                self.status_message = Some(match self.key_map.remove(&key) {
+                    Some(action) => format!("{key} unbound (was {})", action.name()),
+                    None => format!("{key} was not bound"),
+                });
+            }
+            Command::AutoCrop => self.commit(self.image().autocropped()),
+            Command::Pad {
+                edge,
+                amount,
+                color,
+            } => self.commit(self.image().padded(edge, amount, color)),
+            Command::Center(axis) => self.commit(self.image().centered(axis)),
+            Command::DeleteRow => self.delete_row()?,
+            Command::DeleteColumn => self.delete_column()?,
+            Command::InsertRow => self.insert_row()?,
+            Command::InsertColumn => self.insert_column()?,
+            Command::Write { path, force } => self.write(path.as_deref(), force)?,
+            Command::WriteAndCommit {
+                path,
+                force,
+                message,
+            } => self.write_and_commit(path.as_deref(), force, &message)?,
+            Command::Revert { force } => self.revert(force)?,
+            Command::ExportTimelapse { dir, every } => self.export_timelapse(&dir, every)?,
+            Command::ExportApng {
+                path,
+                every,
+                delay_ms,
+            } => self.export_apng(&path, every, delay_ms)?,
+            Command::ExportIco { path, sizes } => self.image().export_ico(&path, &sizes)?,
+            Command::ExportPico8Gfx(path) => self.export_pico8_gfx(&path)?,
+            Command::ExportGameBoyTiles(path) => self.image().export_gameboy_tiles(&path)?,
+            Command::ExportSelection(path) => self.export_selection(&path)?,
+            Command::CursorAdd(coord) => self.add_cursor(coord.x, coord.y),
+            Command::CursorClear => self.clear_extra_cursors(),
+            Command::CursorMirror(spacing) => self.mirror_cursors(spacing),
+            Command::Mark(letter) => self.mark(letter),
+            Command::Guide { axis, position } => self.toggle_guide(axis, position),
+            Command::SelectAll => {
+                self.selection = Some(Selection::all(self.image().width(), self.image().height()))
+            }
+            Command::SelectNone => self.selection = None,
+            Command::SelectInvert => self.select_invert(),
+            Command::SelectWand { global } => self.select_wand(global)?,
+            Command::SelectRect { x0, y0, x1, y1 } => {
+                self.selection = Some(Selection::rect(
+                    self.image().width(),
+                    self.image().height(),
+                    x0,
+                    y0,
+                    x1,
+                    y1,
+                ))
+            }
+            Command::Yank => self.yank()?,
+            Command::Paste => self.paste()?,
+            Command::Fill(color) => self.fill(color),
+            Command::Replace { from, to } => self.replace_color(from, to),
+            Command::Adjust(delta) => self.adjust(delta),
+            Command::GenerateNoise { a, b } => {
+                self.map_selected(|x, y, _| generate::noise(x, y, a, b))
+            }
+            Command::GenerateChecker { size, a, b } => {
+                self.map_selected(|x, y, _| generate::checker(x, y, size, a, b))
+            }
+            Command::GenerateStripes {
+                size,
+                direction,
+                a,
+                b,
+            } => self.map_selected(|x, y, _| generate::stripes(x, y, size, direction, a, b)),
+            Command::Filter(kernel) => self.filter(kernel),
+            Command::FilterScanlines(darken_percent) => {
+                self.map_selected(|_, y, pixel| crate::filter::scanlines(pixel, y, darken_percent))
+            }
+            Command::FilterCrt => self.map_selected(|x, y, pixel| crate::filter::crt(pixel, x, y)),
+            Command::Shift(dx, dy) => self.shift(dx, dy),
+            Command::ShiftRow(dx) => self.shift_row(dx),
+            Command::ShiftColumn(dy) => self.shift_column(dy),
+            Command::Repeat { dx, dy, n } => self.repeat(dx, dy, n),
+            Command::SetChecker(checker) => self.display.checker = checker,
+            Command::SetBgColor(r, g, b) => self.display.bgcolor = Some((r, g, b)),
+            Command::ClearBgColor => self.display.bgcolor = None,
+            Command::SetColorSpace(colorspace) => self.colorspace = colorspace,
+            Command::SetPreview(preview) => self.display.preview = preview,
+            Command::SetPixelWidth(width) => self.display.pixel_width = width,
+            Command::SetGridSize(size) => self.grid_size = size,
+            Command::SetTheme(name) => self.theme = Theme::named(name),
+            Command::SetTool(tool) => self.active_tool = tool,
+            Command::SetPalette(palette) => {
+                self.palette = palette;
+                self.palette_slots = PaletteSlots::from_preset(palette);
+                self.selected_palette_cell = None;
+                self.palette_page = 0;
+                if self.remember_palette {
+                    if let Some(path) = self.path.clone() {
+                        self.palette_state.set(path, palette);
+                        self.palette_state.save();
+                    }
+                }
+            }
+            Command::PaletteNameCell { index, name } => self.palette_slots.set_name(index, name)?,
+            Command::PaletteSwap { a, b } => self.palette_slots.swap(a, b)?,
+            Command::PaletteFromFile(path) => self.palette_from_file(&path)?,
+            #[cfg(feature = "network")]
+            Command::PaletteLospec(slug) => self.palette_lospec(&slug)?,
+            Command::SetNamedColor { name, page, color } => {
+                self.palette_slots.set_color_by_name(&name, page, color)?
+            }
+            Command::PaletteHarmonize { seed, harmony } => {
+                self.palette_slots.harmonize(seed, harmony)?
+            }
+            Command::PaletteRamp { start, end, steps } => {
+                self.palette_slots
+                    .ramp(start, end, steps, self.colorspace)?
+            }
+            Command::SetLockPalette(on) => self.lock_palette = on,
+            Command::SetPixelPerfect(on) => self.pixel_perfect = on,
+            Command::SetPaletteMetric(metric) => self.palette_metric = metric,
+            Command::PaletteCheck => self.check_palette()?,
+        }
+        Ok(())
+    }
+
+    fn select_invert(&mut self) {
+        let (width, height) = (self.image().width(), self.image().height());
+        let mut selection = self
+            .selection
+            .take()
+            .unwrap_or_else(|| Selection::empty(width, height));
+        selection.invert();
+        self.selection = Some(selection);
+    }
+
+    /// Selects pixels matching the color under the cursor: contiguous with the
+    /// cursor by default, or anywhere in the image when `global` is set.
+    pub fn select_wand(&mut self, global: bool) -> Result<(), AppError> {
+        let image = self.image();
+        let (width, height) = (image.width(), image.height());
+        let (cx, cy) = self.cursor;
+        let target = image.get_pixel(cx, cy)?;
+
+        let mut selection = Selection::empty(width, height);
+        if global {
+            for y in 0..height {
+                for x in 0..width {
+                    if image.get_pixel(x, y).expect("in bounds") == target {
+                        selection.set(x, y, true);
+                    }
+                }
+            }
+        } else {
+            let mut stack = vec![(cx, cy)];
+            let mut visited = vec![false; (width as usize) * (height as usize)];
+            while let Some((x, y)) = stack.pop() {
+                let idx = y as usize * width as usize + x as usize;
+                if visited[idx] {
+                    continue;
+                }
+                visited[idx] = true;
+                if image.get_pixel(x, y).expect("in bounds") != target {
+                    continue;
+                }
+                selection.set(x, y, true);
+                if x > 0 {
+                    stack.push((x - 1, y));
+                }
+                if x + 1 < width {
+                    stack.push((x + 1, y));
+                }
+                if y > 0 {
+                    stack.push((x, y - 1));
+                }
+                if y + 1 < height {
+                    stack.push((x, y + 1));
+                }
+            }
+        }
+        self.selection = Some(selection);
+        Ok(())
+    }
+
+    /// Cuts the active selection's opaque pixels into a floating layer that
+    /// previews over the canvas until it is dropped or cancelled.
+    pub fn grab(&mut self) -> Result<(), AppError> {
+        let selection = self.selection.clone().ok_or(AppError::NoSelection)?;
+        let image = self.image();
+        let mut base = image.clone();
+        let mut pixels = Vec::new();
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                if !selection.contains(x, y) {
+                    continue;
+                }
+                let color = image.get_pixel(x, y).expect("in bounds");
+                if color[3] != 0 {
+                    pixels.push((x as i32, y as i32, color));
+                }
+                base.set_pixel(x, y, [0, 0, 0, 0]).expect("in bounds");
+            }
+        }
+        self.floating = Some(Floating::new(base, pixels));
+        Ok(())
+    }
+
+    /// Moves the floating layer's preview position, if one is active.
+    pub fn nudge_floating(&mut self, dx: i32, dy: i32) {
+        if let Some(floating) = &mut self.floating {
+            floating.nudge(dx, dy);
+        }
+    }
+
+    /// Commits the floating layer at its current preview position.
+    pub fn drop_floating(&mut self) {
+        let Some(floating) = self.floating.take() else {
+            return;
+        };
+        let mut next = floating.base().clone();
+        let mut selection = Selection::empty(next.width(), next.height());
+        for (x, y, color) in floating.preview_pixels() {
+            if x < 0 || y < 0 || x as u32 >= next.width() || y as u32 >= next.height() {
+                continue;
+            }
+            let (x, y) = (x as u32, y as u32);
+            next.set_pixel(x, y, color).expect("in bounds");
+            selection.set(x, y, true);
+        }
+        self.commit(next);
+        self.selection = Some(selection);
+    }
+
+    /// Discards the floating layer, leaving the image as it was before grab.
+    pub fn cancel_floating(&mut self) {
+        self.floating = None;
+    }
+
+    /// Copies the active selection's opaque pixels to [`App::clipboard`],
+    /// relative to the selection's top-left corner, leaving the image
+    /// untouched. Unlike [`App::grab`], this doesn't cut anything.
+    pub fn yank(&mut self) -> Result<(), AppError> {
+        let selection = self.selection.clone().ok_or(AppError::NoSelection)?;
+        let image = self.image();
+        let mut pixels = Vec::new();
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                if !selection.contains(x, y) {
+                    continue;
+                }
+                let color = image.get_pixel(x, y).expect("in bounds");
+                if color[3] != 0 {
+                    pixels.push((x as i32, y as i32, color));
+                }
+            }
+        }
+        let (min_x, min_y) = pixels
+            .iter()
+            .fold((i32::MAX, i32::MAX), |(mx, my), &(x, y, _)| {
+                (mx.min(x), my.min(y))
+            });
+        for (x, y, _) in &mut pixels {
+            *x -= min_x;
+            *y -= min_y;
+        }
+        self.clipboard = Some(pixels);
+        Ok(())
+    }
+
+    /// Drops the last yanked region onto the canvas as a floating layer
+    /// anchored at the cursor, previewed live exactly like a grabbed
+    /// selection until it is dropped (Enter) or cancelled (Esc).
+    pub fn paste(&mut self) -> Result<(), AppError> {
+        let clipboard = self.clipboard.clone().ok_or(AppError::NothingToPaste)?;
+        let base = self.image().clone();
+        let (cx, cy) = (self.cursor.0 as i32, self.cursor.1 as i32);
+        let pixels = clipboard
+            .into_iter()
+            .map(|(x, y, color)| (x + cx, y + cy, color))
+            .collect();
+        self.floating = Some(Floating::new(base, pixels));
+        Ok(())
+    }
+
+    /// Paints every selected pixel (the whole image, if no selection is active).
+    pub fn fill(&mut self, color: [u8; 4]) {
+        self.map_selected(|_, _, _| color);
+    }
+
+    /// Swaps `from` for `to` on every selected pixel currently matching `from`.
+    pub fn replace_color(&mut self, from: [u8; 4], to: [u8; 4]) {
+        self.map_selected(|_, _, pixel| if pixel == from { to } else { pixel });
+    }
+
+    /// Adds `delta` to the RGB channels of every selected pixel, clamped to u8 range.
+    pub fn adjust(&mut self, delta: i16) {
+        let colorspace = self.colorspace;
+        self.map_selected(|_, _, pixel| {
+            let mut out = pixel;
+            for channel in &mut out[..3] {
+                *channel = colorspace.adjust_channel(*channel, delta);
+            }
+            out
+        });
+    }
+
+    /// Wrap-shifts selected pixels by `(dx, dy)`; pixels outside the selection
+    /// are left untouched.
+    pub fn shift(&mut self, dx: i32, dy: i32) {
+        let current = self.image();
+        let (width, height) = (current.width(), current.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+        let mut next = current.clone();
+        for y in 0..height {
+            for x in 0..width {
+                if !self.is_selected(x, y) {
+                    continue;
+                }
+                let sx = (x as i64 - dx as i64).rem_euclid(width as i64) as u32;
+                let sy = (y as i64 - dy as i64).rem_euclid(height as i64) as u32;
+                next.set_pixel(x, y, current.get_pixel(sx, sy).expect("in bounds"))
+                    .expect("in bounds");
+            }
+        }
+        self.commit(next);
+    }
+
+    /// Wrap-shifts the cursor's row left (`dx` negative) or right by `dx`
+    /// pixels. With an active selection, every row containing a selected
+    /// pixel is shifted independently instead of just the cursor's row -
+    /// handy for animating water/flag rows at different speeds in one pass.
+    pub fn shift_row(&mut self, dx: i32) {
+        let current = self.image();
+        let width = current.width();
+        if width == 0 {
+            return;
+        }
+        let mut next = current.clone();
+        for y in self.affected_rows() {
+            for x in 0..width {
+                let sx = (x as i64 - dx as i64).rem_euclid(width as i64) as u32;
+                next.set_pixel(x, y, current.get_pixel(sx, y).expect("in bounds"))
+                    .expect("in bounds");
+            }
+        }
+        self.commit(next);
+    }
+
+    /// Like [`App::shift_row`], but shifts the cursor's column (or every
+    /// selected column) up/down by `dy` pixels.
+    pub fn shift_column(&mut self, dy: i32) {
+        let current = self.image();
+        let height = current.height();
+        if height == 0 {
+            return;
+        }
+        let mut next = current.clone();
+        for x in self.affected_columns() {
+            for y in 0..height {
+                let sy = (y as i64 - dy as i64).rem_euclid(height as i64) as u32;
+                next.set_pixel(x, y, current.get_pixel(x, sy).expect("in bounds"))
+                    .expect("in bounds");
+            }
+        }
+        self.commit(next);
+    }
+
+    /// Rows to operate on for [`App::shift_row`]: every row containing a
+    /// selected pixel, or just the cursor's row if there's no selection.
+    fn affected_rows(&self) -> Vec<u32> {
+        match &self.selection {
+            Some(selection) => {
+                let width = self.image().width();
+                (0..self.image().height())
+                    .filter(|&y| (0..width).any(|x| selection.contains(x, y)))
+                    .collect()
+            }
+            None => vec![self.cursor.1],
+        }
+    }
+
+    /// Columns to operate on for [`App::shift_column`], mirroring
+    /// [`App::affected_rows`].
+    fn affected_columns(&self) -> Vec<u32> {
+        match &self.selection {
+            Some(selection) => {
+                let height = self.image().height();
+                (0..self.image().width())
+                    .filter(|&x| (0..height).any(|y| selection.contains(x, y)))
+                    .collect()
+            }
+            None => vec![self.cursor.0],
+        }
+    }
+
+    /// Runs a 3x3 convolution `kernel` over selected pixels (the whole
+    /// image, if no selection is active), sampling neighbors from the
+    /// image as it was before the filter started so filtered pixels don't
+    /// feed into each other's convolution.
+    pub fn filter(&mut self, kernel: Kernel) {
+        let current = self.image();
+        let (width, height) = (current.width(), current.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+        let palette_slots = self.palette_slots.clone();
+        let metric = self.palette_metric;
+        let selection = self.selection.clone();
+        let mut next = current.clone();
+        next.map_pixels(|x, y, pixel| {
+            if !is_selected(&selection, x, y) {
+                return pixel;
+            }
+            let filtered = crate::filter::convolve(current, x, y, kernel);
+            palette_slots.constrain(filtered, metric)
+        });
+        self.commit(next);
+    }
+
+    /// Replaces every selected pixel with the result of `f(x, y, current_pixel)`.
+    fn map_selected(&mut self, f: impl Fn(u32, u32, [u8; 4]) -> [u8; 4] + Sync) {
+        let palette_slots = self.palette_slots.clone();
+        let metric = self.palette_metric;
+        let selection = self.selection.clone();
+        let mut next = self.image().clone();
+        next.map_pixels(|x, y, pixel| {
+            if !is_selected(&selection, x, y) {
+                return pixel;
+            }
+            palette_slots.constrain(f(x, y, pixel), metric)
+        });
+        self.commit(next);
+    }
+
+    /// Saves to `path`, or to the path of the last `:w`/opened file if
+    /// `path` is `None`. If the target already exists and `force` is false,
+    /// defers to [`App::pending_overwrite`] instead of saving immediately,
+    /// so the input layer can ask "overwrite? y/n" before anything is
+    /// written; `:w!` (`force: true`) skips that and saves right away.
+    fn write(&mut self, path: Option<&Path>, force: bool) -> Result<(), AppError> {
+        let path = match path {
+            Some(p) => {
+                self.path = Some(p.to_path_buf());
+                p.to_path_buf()
+            }
+            None => self.path.clone().ok_or(AppError::NoPath)?,
+        };
+        if !force && path.exists() {
+            self.pending_overwrite = Some(path);
+            return Ok(());
+        }
+        self.save_to(&path)?;
+        if let Some(status) = Self::git_status_word(&path) {
+            self.status_message = Some(format!(":w [git: {status}]"));
+        }
+        Ok(())
+    }
+
+    /// `:w [path] +commit "message"` - like [`App::write`], then `git add`
+    /// and `git commit` the saved file. Only commits if the save actually
+    /// happened; if it deferred to [`App::pending_overwrite`] instead (an
+    /// existing file, without `force`), the commit is skipped and the
+    /// status bar says so, rather than silently dropping the message.
+    fn write_and_commit(
+        &mut self,
+        path: Option<&Path>,
+        force: bool,
+        message: &str,
+    ) -> Result<(), AppError> {
+        self.write(path, force)?;
+        if self.pending_overwrite.is_some() {
+            self.status_message =
+                Some("save needs confirmation; re-run :w +commit after confirming".to_string());
+            return Ok(());
+        }
+        let path = self.path.clone().ok_or(AppError::NoPath)?;
+        self.git_commit(&path, message)?;
+        self.status_message = Some(format!(":w [git: committed \"{message}\"]"));
+        Ok(())
+    }
+
+    /// Creates any missing parent directories rather than failing with a raw
+    /// IO error, the same trade-off `export_timelapse` already makes for its
+    /// output directory, then saves the image to `path`.
+    fn save_to(&mut self, path: &Path) -> Result<(), AppError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::CreateDir(parent.to_path_buf(), e))?;
+        }
+        self.image().save(path)?;
+        self.run_post_save_cmd(path);
+        Ok(())
+    }
+
+    /// Runs [`App::post_save_cmd`] with `{file}` replaced by `path`, if set;
+    /// does nothing when no command is configured. The save already
+    /// succeeded by the time this runs, so a failure here, whether the
+    /// command can't be started or exits unsuccessfully, is only logged,
+    /// never surfaced as a save error.
+    fn run_post_save_cmd(&self, path: &Path) {
+        let Some(template) = &self.post_save_cmd else {
+            return;
+        };
+        let cmd = template.replace("{file}", &shell_quote(&path.to_string_lossy()));
+        match std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&cmd)
+            .output()
+        {
+            Ok(output) if !output.status.success() => {
+                log::warn!(
+                    "post_save_cmd `{cmd}` exited with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("post_save_cmd `{cmd}` failed to run: {e}"),
+        }
+    }
+
+    /// Runs `git status --porcelain` for `path`, shelling out the same way
+    /// [`App::run_post_save_cmd`] does. Returns `"modified"`/`"untracked"`,
+    /// or `None` if `path` is clean, ignored, or not inside a git
+    /// repository at all - `git` missing and a non-zero exit both fold into
+    /// `None`, since "nothing to report" is the right default either way.
+    fn git_status_word(path: &Path) -> Option<&'static str> {
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty())?;
+        let file_name = path.file_name()?;
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .arg("status")
+            .arg("--porcelain")
+            .arg("--")
+            .arg(file_name)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let code = stdout.get(0..2)?;
+        match code {
+            "??" => Some("untracked"),
+            _ if code.trim().is_empty() => None,
+            _ => Some("modified"),
+        }
+    }
+
+    /// `git add`s then `git commit`s `path` with `message`, shelling out the
+    /// same way [`App::run_post_save_cmd`] does. Unlike that fire-and-forget
+    /// hook, a failure here is surfaced as an [`AppError::GitCommit`] rather
+    /// than just logged, since committing is the whole point of `:w +commit`.
+    fn git_commit(&self, path: &Path, message: &str) -> Result<(), AppError> {
+        let dir = path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let file_name = path.file_name().ok_or(AppError::NoPath)?;
+        let run = |args: &[&std::ffi::OsStr]| -> Result<(), AppError> {
+            let output = std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(args)
+                .output()
+                .map_err(|e| AppError::GitCommit(e.to_string()))?;
+            if !output.status.success() {
+                return Err(AppError::GitCommit(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ));
+            }
+            Ok(())
+        };
+        run(&["add".as_ref(), file_name])?;
+        run(&["commit".as_ref(), "-m".as_ref(), message.as_ref()])
+    }
+
+    /// Saves to the path awaiting confirmation in [`App::pending_overwrite`],
+    /// a no-op if nothing is pending. Bound to `y` while the prompt is shown.
+    pub fn confirm_overwrite(&mut self) -> Result<(), AppError> {
+        let Some(path) = self.pending_overwrite.take() else {
+            return Ok(());
+        };
+        let result = self.save_to(&path);
+        self.last_error = result.as_ref().err().map(|e| e.localized(self.locale));
+        result
+    }
+
+    /// Dismisses a pending overwrite confirmation without saving. Bound to
+    /// `n`/Esc while the prompt is shown.
+    pub fn cancel_overwrite(&mut self) {
+        self.pending_overwrite = None;
+    }
+
+    /// Whether the image differs from the state it was opened in this
+    /// session - used to decide whether `:revert` needs to confirm first.
+    pub fn is_dirty(&self) -> bool {
+        self.history.current() != &self.history.snapshots()[0]
+    }
+
+    /// Discards in-memory edits and reloads [`App::path`] from disk. If
+    /// there are unsaved changes and `force` is false, defers to
+    /// [`App::pending_revert`] instead of reloading immediately, so the
+    /// input layer can ask "discard changes? y/n" first; `:revert!`/`:e!`
+    /// (`force: true`) skips that and reloads right away.
+    fn revert(&mut self, force: bool) -> Result<(), AppError> {
+        if !force && self.is_dirty() {
+            self.pending_revert = true;
+            return Ok(());
+        }
+        self.reload()
+    }
+
+    fn reload(&mut self) -> Result<(), AppError> {
+        let path = self.path.clone().ok_or(AppError::NoPath)?;
+        self.history = History::new(Image::load(&path)?);
+        Ok(())
+    }
+
+    /// Reloads the path awaiting confirmation in [`App::pending_revert`], a
+    /// no-op if nothing is pending. Bound to `y` while the prompt is shown.
+    pub fn confirm_revert(&mut self) -> Result<(), AppError> {
+        if !self.pending_revert {
+            return Ok(());
+        }
+        self.pending_revert = false;
+        let result = self.reload();
+        self.last_error = result.as_ref().err().map(|e| e.localized(self.locale));
+        result
+    }
+
+    /// Dismisses a pending revert confirmation, keeping in-memory edits.
+    /// Bound to `n`/Esc while the prompt is shown.
+    pub fn cancel_revert(&mut self) {
+        self.pending_revert = false;
+    }
+
+    /// Reads the color under the cursor, copies its hex code to the system
+    /// clipboard, and shows it in the status bar via [`App::status_message`].
+    /// Bound to `y` in normal mode.
+    #[cfg(feature = "clipboard")]
+    pub fn copy_color_under_cursor(&mut self) -> Result<(), AppError> {
+        let hex = self.color_under_cursor_hex();
+        let result =
+            arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(hex.clone()));
+        match result {
+            Ok(()) => {
+                self.status_message = Some(hex);
+                self.last_error = None;
+                Ok(())
+            }
+            Err(err) => {
+                let err = AppError::Clipboard(err);
+                self.last_error = Some(err.localized(self.locale));
+                Err(err)
+            }
+        }
+    }
+
+    /// Shows the hex code of the color under the cursor in the status bar.
+    /// No system clipboard is available in this build, so the color is only
+    /// displayed, not copied. Bound to `y` in normal mode.
+    #[cfg(not(feature = "clipboard"))]
+    pub fn copy_color_under_cursor(&mut self) -> Result<(), AppError> {
+        self.status_message = Some(self.color_under_cursor_hex());
+        Ok(())
+    }
+
+    fn color_under_cursor_hex(&self) -> String {
+        let pixel = self
+            .image()
+            .get_pixel(self.cursor.0, self.cursor.1)
+            .expect("cursor is always in bounds");
+        Color::from(pixel).hex()
+    }
+
+    /// Writes one PNG per `every`-th history snapshot into `dir`, so the whole
+    /// drawing session can be replayed as a timelapse.
+    pub fn export_timelapse(&self, dir: &Path, every: u32) -> Result<(), AppError> {
+        let every = every.max(1);
+        std::fs::create_dir_all(dir).map_err(|e| AppError::CreateDir(dir.to_path_buf(), e))?;
+        for (frame, snapshot) in self
+            .history
+            .snapshots()
+            .iter()
+            .step_by(every as usize)
+            .enumerate()
+        {
+            let path = dir.join(format!("{frame:05}.png"));
+            snapshot.save(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Writes one animated PNG covering every `every`-th history snapshot,
+    /// each frame shown for `delay_ms` - the same edit-session replay
+    /// [`App::export_timelapse`] offers as loose files, but as a single
+    /// file that round-trips as an animation without converting it first.
+    pub fn export_apng(&self, path: &Path, every: u32, delay_ms: u32) -> Result<(), AppError> {
+        let every = every.max(1);
+        let frames: Vec<Image> = self
+            .history
+            .snapshots()
+            .iter()
+            .step_by(every as usize)
+            .cloned()
+            .collect();
+        Image::save_apng(&frames, path, delay_ms)?;
+        Ok(())
+    }
+
+    /// Writes the image as PICO-8 `__gfx__` hex: one hex digit per pixel,
+    /// the index of its nearest color in the active palette (`0` for every
+    /// pixel if no palette is set), one line per row.
+    pub fn export_pico8_gfx(&self, path: &Path) -> Result<(), AppError> {
+        let image = self.image();
+        let mut out = String::with_capacity((image.width() as usize + 1) * image.height() as usize);
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let pixel = image.get_pixel(x, y).expect("in bounds");
+                let index = self.palette_slots.index_of(pixel, self.palette_metric);
+                out.push(char::from_digit(index as u32, 16).unwrap_or('0'));
+            }
+            out.push('\n');
+        }
+        std::fs::write(path, out).map_err(|e| AppError::WriteFile(path.to_path_buf(), e))?;
+        Ok(())
+    }
+
+    /// Crops the active selection's bounding box, blanking any unselected
+    /// cells within it to transparent, and writes the result as a
+    /// standalone PNG - for sharing part of the artwork without cropping
+    /// externally.
+    pub fn export_selection(&self, path: &Path) -> Result<(), AppError> {
+        let selection = self.selection.as_ref().ok_or(AppError::NoSelection)?;
+        if selection.is_empty() {
+            return Err(AppError::NoSelection);
+        }
+        let image = self.image();
+        let mut bounds: Option<((u32, u32), (u32, u32))> = None;
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                if selection.contains(x, y) {
+                    bounds = Some(match bounds {
+                        None => ((x, y), (x, y)),
+                        Some((min, max)) => {
+                            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+                        }
+                    });
+                }
+            }
+        }
+        // `selection.is_empty()` above already rules this out for a selection
+        // sized to match `image`, but a 0x0 image can never produce a
+        // non-empty selection either way - this keeps the bounding box from
+        // ever being seeded with `width() - 1`/`height() - 1`, which would
+        // underflow for one.
+        let (min, max) = bounds.ok_or(AppError::NoSelection)?;
+
+        let width = max.0 - min.0 + 1;
+        let height = max.1 - min.1 + 1;
+        let mut cropped = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (min.0 + x, min.1 + y);
+                if selection.contains(sx, sy) {
+                    let color = image.get_pixel(sx, sy).expect("in bounds");
+                    cropped.set_pixel(x, y, color).expect("in bounds");
+                }
+            }
+        }
+        cropped.save(path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_timelapse_writes_one_frame_per_step() {
+        let mut app = App::new(Image::new(2, 2), None);
+        for i in 0..5u8 {
+            app.set_pixel(0, 0, [i, i, i, 255]).unwrap();
+        }
+        let dir = std::env::temp_dir().join(format!("tie-timelapse-test-{}", std::process::id()));
+        app.export_timelapse(&dir, 2).unwrap();
+        let frames: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        // 6 snapshots total (initial + 5 edits), every=2 -> frames 0,2,4 -> 3 files.
+        assert_eq!(frames.len(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_apng_writes_one_animated_file_covering_every_step() {
+        let mut app = App::new(Image::new(2, 2), None);
+        for i in 0..5u8 {
+            app.set_pixel(0, 0, [i, i, i, 255]).unwrap();
+        }
+        let path = std::env::temp_dir().join(format!("tie-apng-test-{}.png", std::process::id()));
+        app.export_apng(&path, 2, 50).unwrap();
+        assert!(path.exists());
+        // Loading it back yields the first frame, at the frames' 2x2 size.
+        let loaded = Image::load(&path).unwrap();
+        assert_eq!((loaded.width(), loaded.height()), (2, 2));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn palette_snaps_painted_colors_to_the_nearest_preset_entry() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.set_pixel(0, 0, [250, 10, 70, 255]).unwrap();
+        assert_eq!(
+            app.image().get_pixel(0, 0).unwrap(),
+            [0xFF, 0x00, 0x4D, 255]
+        );
+    }
+
+    #[test]
+    fn naming_and_recoloring_a_palette_cell_affects_painting() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.run(Command::PaletteNameCell {
+            index: 8,
+            name: "skin".to_string(),
+        })
+        .unwrap();
+        app.run(Command::SetNamedColor {
+            name: "skin".to_string(),
+            page: None,
+            color: [240, 200, 180],
+        })
+        .unwrap();
+
+        app.set_pixel(0, 0, [240, 200, 180, 255]).unwrap();
+
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [240, 200, 180, 255]);
+    }
+
+    #[test]
+    fn palette_from_file_adopts_another_images_distinct_colors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.png");
+        let mut other = Image::new(2, 1);
+        other.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        other.set_pixel(1, 0, [0, 255, 0, 255]).unwrap();
+        other.save(&path).unwrap();
+
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+
+        app.run(Command::PaletteFromFile(path)).unwrap();
+
+        assert_eq!(app.palette, PaletteName::None);
+        assert_eq!(app.palette_slots.colors(), &[[255, 0, 0], [0, 255, 0]]);
+    }
+
+    #[test]
+    fn swapping_palette_cells_moves_their_export_index() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.set_pixel(0, 0, [0, 0, 0, 255]).unwrap();
+        assert_eq!(
+            app.palette_slots
+                .index_of([0, 0, 0, 255], app.palette_metric),
+            0
+        );
+
+        app.run(Command::PaletteSwap { a: 0, b: 1 }).unwrap();
+
+        assert_eq!(
+            app.palette_slots
+                .index_of([0, 0, 0, 255], app.palette_metric),
+            1
+        );
+    }
+
+    #[test]
+    fn switching_palette_presets_resets_names_and_overrides() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.run(Command::PaletteNameCell {
+            index: 0,
+            name: "ink".to_string(),
+        })
+        .unwrap();
+
+        app.run(Command::SetPalette(PaletteName::Tic80)).unwrap();
+
+        assert!(matches!(
+            app.run(Command::SetNamedColor {
+                name: "ink".to_string(),
+                page: None,
+                color: [1, 2, 3]
+            }),
+            Err(AppError::Palette(PaletteError::UnknownName(name))) if name == "ink"
+        ));
+    }
+
+    #[test]
+    fn select_palette_cell_sets_the_active_color_keeping_alpha() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.active_color = [1, 2, 3, 128];
+
+        app.select_palette_cell(1);
+
+        let [r, g, b] = app.palette_slots.colors()[1];
+        assert_eq!(app.active_color, [r, g, b, 128]);
+    }
+
+    #[test]
+    fn cycle_palette_selection_wraps_around_in_both_directions() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        let len = app.palette_slots.colors().len() as u8;
+
+        app.cycle_palette_selection(false);
+        assert_eq!(app.selected_palette_cell, Some(0));
+
+        app.cycle_palette_selection(false);
+        assert_eq!(app.selected_palette_cell, Some(len - 1));
+
+        app.cycle_palette_selection(true);
+        app.cycle_palette_selection(true);
+        assert_eq!(app.selected_palette_cell, Some(1));
+    }
+
+    #[test]
+    fn cycle_palette_selection_is_a_no_op_with_no_palette_active() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.cycle_palette_selection(true);
+        assert_eq!(app.selected_palette_cell, None);
+    }
+
+    #[test]
+    fn toggle_focus_switches_between_canvas_and_palette() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert_eq!(app.focus, Focus::Canvas);
+
+        app.toggle_focus();
+        assert_eq!(app.focus, Focus::Palette);
+
+        app.toggle_focus();
+        assert_eq!(app.focus, Focus::Canvas);
+    }
+
+    #[test]
+    fn step_palette_page_wraps_around_in_both_directions() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        let page_count = app.palette_slots.page_count();
+        assert!(page_count > 1, "pico8 should span more than one page");
+
+        app.step_palette_page(false);
+        assert_eq!(app.palette_page, page_count - 1);
+
+        app.step_palette_page(true);
+        assert_eq!(app.palette_page, 0);
+    }
+
+    #[test]
+    fn switching_palette_presets_resets_the_selected_cell() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.cycle_palette_selection(true);
+        assert!(app.selected_palette_cell.is_some());
+
+        app.run(Command::SetPalette(PaletteName::Tic80)).unwrap();
+
+        assert_eq!(app.selected_palette_cell, None);
+    }
+
+    #[test]
+    fn paint_with_selected_cell_paints_without_changing_active_color() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.active_color = [9, 9, 9, 255];
+        app.cycle_palette_selection(true);
+        let selected_color = app.palette_slots.colors()[0];
+
+        app.paint_with_selected_cell().unwrap();
+
+        let [r, g, b, _] = app.image().get_pixel(0, 0).unwrap();
+        assert_eq!([r, g, b], selected_color);
+        assert_eq!(app.active_color, [9, 9, 9, 255]);
+    }
+
+    #[test]
+    fn paint_with_selected_cell_is_a_no_op_with_nothing_selected() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.paint_with_selected_cell().unwrap();
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn palettemetric_ciede2000_can_snap_to_a_different_cell_than_rgb() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        let color = [60, 253, 230, 255];
+
+        app.set_pixel(0, 0, color).unwrap();
+        assert_eq!(
+            app.image().get_pixel(0, 0).unwrap(),
+            [0x29, 0xAD, 0xFF, 255]
+        );
+
+        app.run(Command::SetPaletteMetric(DistanceMetric::Ciede2000))
+            .unwrap();
+        app.set_pixel(0, 0, color).unwrap();
+        assert_eq!(
+            app.image().get_pixel(0, 0).unwrap(),
+            [0x00, 0xE4, 0x36, 255]
+        );
+    }
+
+    #[test]
+    fn select_palette_cell_out_of_range_is_a_no_op() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.active_color = [1, 2, 3, 128];
+
+        app.select_palette_cell(5);
+
+        assert_eq!(app.active_color, [1, 2, 3, 128]);
+    }
+
+    #[test]
+    fn select_palette_cell_is_relative_to_the_active_page() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.active_color = [1, 2, 3, 128];
+        app.set_palette_page(1);
+
+        app.select_palette_cell(3);
+
+        let [r, g, b] = app.palette_slots.colors()[13];
+        assert_eq!(app.active_color, [r, g, b, 128]);
+    }
+
+    #[test]
+    fn set_palette_page_clamps_to_the_last_page_the_palette_has() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+
+        app.set_palette_page(3);
+
+        assert_eq!(app.palette_page, 1);
+    }
+
+    #[test]
+    fn switching_palette_presets_resets_the_palette_page() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.set_palette_page(1);
+
+        app.run(Command::SetPalette(PaletteName::Tic80)).unwrap();
+
+        assert_eq!(app.palette_page, 0);
+    }
+
+    #[test]
+    fn lockpalette_rejects_a_color_not_in_the_palette() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.run(Command::SetLockPalette(true)).unwrap();
+
+        let err = app.set_pixel(0, 0, [1, 2, 3, 255]).unwrap_err();
+
+        assert!(matches!(err, AppError::LockedPalette(1, 2, 3)));
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn paint_at_cursors_sets_last_error_when_the_palette_is_locked() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.run(Command::SetLockPalette(true)).unwrap();
+
+        assert!(app.paint_at_cursors([1, 2, 3, 255]).is_err());
+
+        assert!(app.last_error.is_some());
+    }
+
+    #[test]
+    fn paint_at_cursors_clears_a_stale_last_error_on_success() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.last_error = Some("stale".to_string());
+
+        app.paint_at_cursors([1, 2, 3, 255]).unwrap();
+
+        assert!(app.last_error.is_none());
+    }
+
+    #[test]
+    fn lockpalette_allows_an_exact_palette_color() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.run(Command::SetLockPalette(true)).unwrap();
+
+        app.set_pixel(0, 0, [0xFF, 0x00, 0x4D, 255]).unwrap();
+
+        assert_eq!(
+            app.image().get_pixel(0, 0).unwrap(),
+            [0xFF, 0x00, 0x4D, 255]
+        );
+    }
+
+    #[test]
+    fn lockpalette_is_a_no_op_without_an_active_palette() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetLockPalette(true)).unwrap();
+
+        app.set_pixel(0, 0, [1, 2, 3, 255]).unwrap();
+
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn palette_check_lists_colors_outside_the_active_palette() {
+        let mut image = Image::new(1, 1);
+        image.set_pixel(0, 0, [1, 2, 3, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+
+        let err = app.check_palette().unwrap_err();
+
+        assert!(matches!(err, AppError::OutOfPalette(1, _)));
+    }
+
+    #[test]
+    fn palette_check_passes_when_every_pixel_matches_the_palette() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        assert!(app.check_palette().is_ok());
+    }
+
+    #[test]
+    fn palette_check_is_a_no_op_without_an_active_palette() {
+        let app = App::new(Image::new(1, 1), None);
+        assert!(app.check_palette().is_ok());
+    }
+
+    #[test]
+    fn setting_palette_remembers_it_for_the_open_path_when_enabled() {
+        let mut app = App::new(Image::new(1, 1), Some(PathBuf::from("sprite.png")));
+        app.palette_state = PaletteState::default();
+        app.remember_palette = true;
+        app.run(Command::SetPalette(PaletteName::Tic80)).unwrap();
+        assert_eq!(
+            app.palette_state.get(Path::new("sprite.png")),
+            Some(PaletteName::Tic80)
+        );
+    }
+
+    #[test]
+    fn setting_palette_does_not_remember_it_when_disabled() {
+        let mut app = App::new(Image::new(1, 1), Some(PathBuf::from("sprite.png")));
+        app.palette_state = PaletteState::default();
+        app.remember_palette = false;
+        app.run(Command::SetPalette(PaletteName::Tic80)).unwrap();
+        assert_eq!(app.palette_state.get(Path::new("sprite.png")), None);
+    }
+
+    #[test]
+    fn quitting_remembers_the_cursor_position_for_the_open_path() {
+        let mut app = App::new(Image::new(4, 4), Some(PathBuf::from("sprite.png")));
+        app.session_state = SessionState::default();
+        app.cursor = (2, 3);
+        app.run(Command::Quit).unwrap();
+        assert_eq!(
+            app.session_state.cursor(Path::new("sprite.png")),
+            Some((2, 3))
+        );
+        assert_eq!(app.session_state.last_file(), Some(Path::new("sprite.png")));
+    }
+
+    #[test]
+    fn export_pico8_gfx_writes_one_hex_digit_per_pixel() {
+        let mut app = App::new(Image::new(2, 1), None);
+        app.run(Command::SetPalette(PaletteName::Pico8)).unwrap();
+        app.set_pixel(0, 0, [0, 0, 0, 255]).unwrap();
+        app.set_pixel(1, 0, [0xFF, 0xCC, 0xAA, 255]).unwrap();
+
+        let path = std::env::temp_dir().join(format!("tie-pico8-test-{}", std::process::id()));
+        app.export_pico8_gfx(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "0f\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn export_selection_without_a_selection_errors() {
+        let app = App::new(Image::new(2, 2), None);
+        let path = std::env::temp_dir().join(format!("tie-export-sel-test-{}", std::process::id()));
+        assert!(matches!(
+            app.export_selection(&path),
+            Err(AppError::NoSelection)
+        ));
+    }
+
+    #[test]
+    fn export_selection_on_a_zero_sized_image_errors_instead_of_panicking() {
+        let mut app = App::new(Image::new(0, 0), None);
+        app.selection = Some(Selection::all(0, 0));
+        let path =
+            std::env::temp_dir().join(format!("tie-export-sel-zero-test-{}", std::process::id()));
+        assert!(matches!(
+            app.export_selection(&path),
+            Err(AppError::NoSelection)
+        ));
+    }
+
+    #[test]
+    fn adjust_on_a_zero_sized_image_does_not_underflow() {
+        let mut app = App::new(Image::new(0, 0), None);
+        app.adjust(5);
+        assert_eq!((app.image().width(), app.image().height()), (0, 0));
+    }
+
+    #[test]
+    fn export_selection_crops_to_the_bounding_box_and_blanks_unselected_cells() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.set_pixel(1, 1, [1, 0, 0, 255]).unwrap();
+        app.set_pixel(2, 2, [2, 0, 0, 255]).unwrap();
+        app.set_pixel(1, 2, [3, 0, 0, 255]).unwrap();
+        let mut sel = Selection::empty(4, 4);
+        sel.set(1, 1, true);
+        sel.set(2, 2, true);
+        app.selection = Some(sel);
+
+        let path =
+            std::env::temp_dir().join(format!("tie-export-sel-test-{}-2.png", std::process::id()));
+        app.export_selection(&path).unwrap();
+        let cropped = Image::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((cropped.width(), cropped.height()), (2, 2));
+        assert_eq!(cropped.get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(cropped.get_pixel(1, 1).unwrap(), [2, 0, 0, 255]);
+        assert_eq!(
+            cropped.get_pixel(1, 0).unwrap(),
+            [0, 0, 0, 0],
+            "unselected cell within the bounding box is blanked"
+        );
+    }
+
+    #[test]
+    fn paint_at_cursors_paints_the_primary_and_every_extra_cursor() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.cursor = (0, 0);
+        app.add_cursor(2, 2);
+        app.add_cursor(3, 3);
+        let color = app.active_color;
+        app.paint_at_cursors(color).unwrap();
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), color);
+        assert_eq!(app.image().get_pixel(2, 2).unwrap(), color);
+        assert_eq!(app.image().get_pixel(3, 3).unwrap(), color);
+        assert_eq!(app.image().get_pixel(1, 1).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn mirror_cursors_tiles_a_grid_anchored_at_the_primary_cursor() {
+        let mut app = App::new(Image::new(6, 6), None);
+        app.cursor = (1, 1);
+        app.mirror_cursors(2);
+        assert!(app.extra_cursors.contains(&(1, 3)));
+        assert!(app.extra_cursors.contains(&(3, 1)));
+        assert!(app.extra_cursors.contains(&(5, 5)));
+        assert!(!app.extra_cursors.contains(&(1, 1)));
+    }
+
+    #[test]
+    fn clear_extra_cursors_drops_them_all() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.add_cursor(1, 1);
+        app.clear_extra_cursors();
+        assert!(app.extra_cursors.is_empty());
+    }
+
+    #[test]
+    fn mark_then_jump_returns_the_cursor_to_the_bookmarked_position() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.cursor = (2, 3);
+        app.mark('a');
+        app.cursor = (0, 0);
+
+        app.jump_to_mark('a').unwrap();
+
+        assert_eq!(app.cursor, (2, 3));
+    }
+
+    #[test]
+    fn jump_to_an_unset_mark_errors() {
+        let mut app = App::new(Image::new(4, 4), None);
+        assert!(app.jump_to_mark('a').is_err());
+    }
+
+    #[test]
+    fn jump_to_a_mark_left_behind_by_a_shrinking_edit_clamps_to_the_new_bounds() {
+        let mut app = App::new(Image::new(5, 5), None);
+        app.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        app.cursor = (4, 4);
+        app.mark('a');
+        app.run(Command::AutoCrop).unwrap();
+
+        app.jump_to_mark('a').unwrap();
+
+        assert_eq!(app.cursor, (0, 0));
+        app.paint_at_cursors([255, 0, 0, 255]).unwrap();
+    }
+
+    #[test]
+    fn jump_to_a_mark_on_a_zero_sized_image_does_not_underflow() {
+        let mut app = App::new(Image::new(0, 0), None);
+        app.mark('a');
+
+        app.jump_to_mark('a').unwrap();
+
+        assert_eq!(app.cursor, (0, 0));
+    }
+
+    #[test]
+    fn toggle_guide_adds_then_removes_the_same_position() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.toggle_guide(crate::image::Axis::X, 2);
+        assert_eq!(app.x_guides, vec![2]);
+        app.toggle_guide(crate::image::Axis::X, 2);
+        assert!(app.x_guides.is_empty());
+    }
+
+    #[test]
+    fn toggle_guide_keeps_the_x_and_y_lists_independent() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.toggle_guide(crate::image::Axis::Y, 1);
+        assert_eq!(app.x_guides, Vec::<u32>::new());
+        assert_eq!(app.y_guides, vec![1]);
+    }
+
+    #[test]
+    fn drag_paints_every_cell_passed_through() {
+        let mut app = App::new(Image::new(5, 1), None);
+        app.drag(Direction::Right, 3);
+        assert_eq!(app.cursor, (3, 0));
+        for x in 1..=3 {
+            assert_eq!(app.image().get_pixel(x, 0).unwrap(), app.active_color);
+        }
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn drag_stops_at_image_edge() {
+        let mut app = App::new(Image::new(2, 1), None);
+        app.drag(Direction::Right, DRAG_STEPS);
+        assert_eq!(app.cursor, (1, 0));
+    }
+
+    #[test]
+    fn grid_snap_moves_by_the_configured_grid_size() {
+        let mut app = App::new(Image::new(32, 1), None);
+        app.grid_size = 8;
+        app.toggle_grid_snap();
+        app.move_cursor(Direction::Right);
+        assert_eq!(app.cursor, (8, 0));
+        app.move_cursor(Direction::Right);
+        assert_eq!(app.cursor, (16, 0));
+    }
+
+    #[test]
+    fn grid_snap_does_not_move_past_a_jump_that_would_cross_the_edge() {
+        let mut app = App::new(Image::new(10, 1), None);
+        app.grid_size = 8;
+        app.toggle_grid_snap();
+        app.cursor = (8, 0);
+        app.move_cursor(Direction::Right);
+        assert_eq!(app.cursor, (8, 0));
+    }
+
+    #[test]
+    fn axis_lock_confines_movement_to_the_first_direction_moved() {
+        let mut app = App::new(Image::new(5, 5), None);
+        app.cursor = (2, 2);
+        app.toggle_axis_lock();
+        app.move_cursor(Direction::Right);
+        assert_eq!(app.cursor, (3, 2));
+        app.move_cursor(Direction::Down);
+        assert_eq!(app.cursor, (3, 2), "perpendicular move should be ignored");
+        app.move_cursor(Direction::Left);
+        assert_eq!(app.cursor, (2, 2), "same-axis move should still work");
+    }
+
+    #[test]
+    fn toggling_axis_lock_off_and_on_resets_the_locked_axis() {
+        let mut app = App::new(Image::new(5, 5), None);
+        app.cursor = (2, 2);
+        app.toggle_axis_lock();
+        app.move_cursor(Direction::Right);
+        app.toggle_axis_lock();
+        app.toggle_axis_lock();
+        app.move_cursor(Direction::Down);
+        assert_eq!(app.cursor, (3, 3));
+    }
+
+    #[test]
+    fn pen_down_paints_on_plain_movement_but_not_when_off() {
+        let mut app = App::new(Image::new(3, 1), None);
+        app.move_cursor(Direction::Right);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [0, 0, 0, 0]);
+
+        app.toggle_pen();
+        app.move_cursor(Direction::Right);
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), app.active_color);
+    }
+
+    #[test]
+    fn pixel_perfect_erases_the_corner_of_an_l_shaped_turn() {
+        let mut app = App::new(Image::new(3, 2), None);
+        app.pixel_perfect = true;
+        app.toggle_pen();
+        app.move_cursor(Direction::Right); // (0,0) -> (1,0)
+        app.move_cursor(Direction::Right); // (1,0) -> (2,0), corner candidate
+        app.move_cursor(Direction::Down); // (2,0) -> (2,1), completes the L
+
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), [0, 0, 0, 0]);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), app.active_color);
+        assert_eq!(app.image().get_pixel(2, 1).unwrap(), app.active_color);
+    }
+
+    #[test]
+    fn pixel_perfect_leaves_a_straight_stroke_untouched() {
+        let mut app = App::new(Image::new(3, 1), None);
+        app.pixel_perfect = true;
+        app.toggle_pen();
+        app.move_cursor(Direction::Right);
+        app.move_cursor(Direction::Right);
+
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), app.active_color);
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), app.active_color);
+    }
+
+    #[test]
+    fn fill_respects_active_selection() {
+        let mut app = App::new(Image::new(2, 1), None);
+        let mut sel = Selection::empty(2, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+        app.fill([1, 2, 3, 4]);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn fill_applies_to_whole_image_without_selection() {
+        let mut app = App::new(Image::new(2, 1), None);
+        app.fill([9, 9, 9, 9]);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn generate_checker_fills_alternating_squares() {
+        let mut app = App::new(Image::new(4, 2), None);
+        app.run(Command::GenerateChecker {
+            size: 2,
+            a: [1, 2, 3, 4],
+            b: [5, 6, 7, 8],
+        })
+        .unwrap();
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), [5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn generate_respects_active_selection() {
+        let mut app = App::new(Image::new(2, 1), None);
+        let mut sel = Selection::empty(2, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+        app.run(Command::GenerateNoise {
+            a: [1, 2, 3, 4],
+            b: [5, 6, 7, 8],
+        })
+        .unwrap();
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn filter_respects_active_selection() {
+        let mut app = App::new(Image::new(2, 1), None);
+        app.fill([10, 20, 30, 255]);
+        let mut sel = Selection::empty(2, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+        app.filter(crate::filter::SHARPEN);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn filter_blur_softens_a_sharp_edge() {
+        let mut image = Image::new(3, 1);
+        image.set_pixel(1, 0, [255, 255, 255, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.filter(crate::filter::BLUR);
+        let [r, ..] = app.image().get_pixel(0, 0).unwrap();
+        assert!(r > 0);
+    }
+
+    #[test]
+    fn autocrop_shrinks_the_canvas_to_the_opaque_content() {
+        let mut image = Image::new(4, 4);
+        image.set_pixel(1, 1, [9, 9, 9, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.run(Command::AutoCrop).unwrap();
+        assert_eq!((app.image().width(), app.image().height()), (1, 1));
+    }
+
+    #[test]
+    fn autocrop_pulls_a_cursor_left_outside_the_shrunken_canvas_back_in_bounds() {
+        let mut image = Image::new(5, 5);
+        image.set_pixel(0, 0, [9, 9, 9, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.cursor = (4, 4);
+
+        app.run(Command::AutoCrop).unwrap();
+
+        assert_eq!((app.image().width(), app.image().height()), (1, 1));
+        assert_eq!(app.cursor, (0, 0));
+        app.paint_at_cursors([255, 0, 0, 255]).unwrap();
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn pad_grows_the_canvas_and_fills_new_pixels_with_the_given_color() {
+        let image = Image::new(2, 2);
+        let mut app = App::new(image, None);
+        app.run(Command::Pad {
+            edge: crate::image::Edge::Left,
+            amount: 1,
+            color: [1, 2, 3, 255],
+        })
+        .unwrap();
+        assert_eq!((app.image().width(), app.image().height()), (3, 2));
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn center_moves_the_drawing_to_the_middle_of_the_canvas() {
+        let mut image = Image::new(5, 5);
+        image.set_pixel(0, 0, [9, 8, 7, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.run(Command::Center(crate::image::Axis::Both)).unwrap();
+        assert_eq!(app.image().get_pixel(2, 2).unwrap(), [9, 8, 7, 255]);
+    }
+
+    #[test]
+    fn delrow_removes_the_cursors_row_and_shrinks_the_canvas() {
+        let mut image = Image::new(2, 3);
+        image.set_pixel(0, 2, [9, 8, 7, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.cursor = (0, 1);
+        app.run(Command::DeleteRow).unwrap();
+        assert_eq!((app.image().width(), app.image().height()), (2, 2));
+        assert_eq!(app.image().get_pixel(0, 1).unwrap(), [9, 8, 7, 255]);
+    }
+
+    #[test]
+    fn delrow_pulls_the_cursor_back_onto_the_canvas_when_its_row_was_last() {
+        let mut app = App::new(Image::new(2, 2), None);
+        app.cursor = (0, 1);
+        app.run(Command::DeleteRow).unwrap();
+        assert_eq!(app.cursor, (0, 0));
+    }
+
+    #[test]
+    fn delrow_on_a_one_row_image_reports_an_error_and_leaves_it_unchanged() {
+        let mut app = App::new(Image::new(2, 1), None);
+        assert!(app.run(Command::DeleteRow).is_err());
+        assert_eq!(app.image().height(), 1);
+    }
+
+    #[test]
+    fn delcol_removes_the_cursors_column_and_shrinks_the_canvas() {
+        let mut image = Image::new(3, 2);
+        image.set_pixel(2, 0, [9, 8, 7, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.cursor = (1, 0);
+        app.run(Command::DeleteColumn).unwrap();
+        assert_eq!((app.image().width(), app.image().height()), (2, 2));
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [9, 8, 7, 255]);
+    }
+
+    #[test]
+    fn insrow_adds_a_blank_row_at_the_cursor_and_shifts_pixels_down() {
+        let mut image = Image::new(2, 2);
+        image.set_pixel(0, 1, [9, 8, 7, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.cursor = (0, 1);
+        app.run(Command::InsertRow).unwrap();
+        assert_eq!((app.image().width(), app.image().height()), (2, 3));
+        assert_eq!(app.image().get_pixel(0, 1).unwrap(), [0, 0, 0, 0]);
+        assert_eq!(app.image().get_pixel(0, 2).unwrap(), [9, 8, 7, 255]);
+    }
+
+    #[test]
+    fn inscol_adds_a_blank_column_at_the_cursor_and_shifts_pixels_right() {
+        let mut image = Image::new(2, 2);
+        image.set_pixel(1, 0, [9, 8, 7, 255]).unwrap();
+        let mut app = App::new(image, None);
+        app.cursor = (1, 0);
+        app.run(Command::InsertColumn).unwrap();
+        assert_eq!((app.image().width(), app.image().height()), (3, 2));
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [0, 0, 0, 0]);
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), [9, 8, 7, 255]);
+    }
+
+    #[test]
+    fn filter_scanlines_darkens_only_odd_rows() {
+        let mut app = App::new(Image::new(1, 2), None);
+        app.fill([200, 200, 200, 255]);
+        app.run(Command::FilterScanlines(50)).unwrap();
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [200, 200, 200, 255]);
+        assert_eq!(app.image().get_pixel(0, 1).unwrap(), [100, 100, 100, 255]);
+    }
+
+    #[test]
+    fn repeat_stamps_the_last_change_offset_n_times() {
+        let mut app = App::new(Image::new(10, 1), None);
+        app.cursor = (0, 0);
+        let color = app.active_color;
+        app.paint_at_cursors(color).unwrap();
+        app.repeat(2, 0, 3);
+        for x in [0, 2, 4, 6] {
+            assert_eq!(app.image().get_pixel(x, 0).unwrap(), color);
+        }
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn repeat_skips_stamps_that_land_out_of_bounds() {
+        let mut app = App::new(Image::new(4, 1), None);
+        app.cursor = (3, 0);
+        let color = app.active_color;
+        app.paint_at_cursors(color).unwrap();
+        app.repeat(1, 0, 2);
+        assert_eq!(app.image().get_pixel(3, 0).unwrap(), color);
+    }
+
+    #[test]
+    fn repeat_with_no_prior_edit_is_a_no_op() {
+        let mut app = App::new(Image::new(4, 1), None);
+        app.repeat(1, 0, 3);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn select_invert_of_none_selects_everything() {
+        let mut app = App::new(Image::new(2, 1), None);
+        app.execute("select invert").unwrap();
+        assert!(app.selection.as_ref().unwrap().contains(0, 0));
+        assert!(app.selection.as_ref().unwrap().contains(1, 0));
+    }
+
+    #[test]
+    fn select_rect_via_command_selects_the_given_rectangle() {
+        let mut app = App::new(Image::new(4, 4), None);
+        app.execute("select 1 1 2 2").unwrap();
+        let sel = app.selection.as_ref().unwrap();
+        assert!(sel.contains(1, 1) && sel.contains(2, 2));
+        assert!(!sel.contains(0, 0) && !sel.contains(3, 3));
+    }
+
+    #[test]
+    fn select_wand_contiguous_stops_at_color_boundary() {
+        let mut app = App::new(Image::new(3, 1), None);
+        app.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        app.cursor = (0, 0);
+        app.select_wand(false).unwrap();
+        let sel = app.selection.as_ref().unwrap();
+        assert!(sel.contains(0, 0));
+        assert!(!sel.contains(1, 0));
+    }
+
+    #[test]
+    fn select_wand_global_matches_anywhere() {
+        let mut app = App::new(Image::new(3, 1), None);
+        app.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        app.set_pixel(2, 0, [1, 0, 0, 255]).unwrap();
+        app.cursor = (0, 0);
+        app.select_wand(true).unwrap();
+        let sel = app.selection.as_ref().unwrap();
+        assert!(sel.contains(0, 0));
+        assert!(!sel.contains(1, 0));
+        assert!(sel.contains(2, 0));
+    }
+
+    #[test]
+    fn grab_without_selection_errors() {
+        let mut app = App::new(Image::new(2, 2), None);
+        assert!(matches!(app.grab(), Err(AppError::NoSelection)));
+    }
+
+    #[test]
+    fn grab_move_and_drop_relocates_pixels() {
+        let mut app = App::new(Image::new(3, 1), None);
+        app.set_pixel(0, 0, [1, 2, 3, 255]).unwrap();
+        let mut sel = Selection::empty(3, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+
+        app.grab().unwrap();
+        assert_eq!(
+            app.floating
+                .as_ref()
+                .unwrap()
+                .base()
+                .get_pixel(0, 0)
+                .unwrap(),
+            [0, 0, 0, 0]
+        );
+        assert_eq!(
+            app.image().get_pixel(0, 0).unwrap(),
+            [1, 2, 3, 255],
+            "history untouched until drop"
+        );
+        app.nudge_floating(2, 0);
+
+        app.drop_floating();
+        assert!(app.floating.is_none());
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), [1, 2, 3, 255]);
+        assert!(app.selection.as_ref().unwrap().contains(2, 0));
+    }
+
+    #[test]
+    fn cancel_floating_restores_original_image() {
+        let mut app = App::new(Image::new(2, 1), None);
+        app.set_pixel(0, 0, [9, 9, 9, 255]).unwrap();
+        let mut sel = Selection::empty(2, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+
+        app.grab().unwrap();
+        app.nudge_floating(1, 0);
+        app.cancel_floating();
+        assert!(app.floating.is_none());
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [9, 9, 9, 255]);
+    }
+
+    #[test]
+    fn yank_without_selection_errors() {
+        let mut app = App::new(Image::new(2, 2), None);
+        assert!(matches!(app.yank(), Err(AppError::NoSelection)));
+    }
+
+    #[test]
+    fn paste_without_a_clipboard_errors() {
+        let mut app = App::new(Image::new(2, 2), None);
+        assert!(matches!(app.paste(), Err(AppError::NothingToPaste)));
+    }
+
+    #[test]
+    fn yank_then_paste_copies_pixels_to_the_cursor_without_cutting() {
+        let mut app = App::new(Image::new(4, 1), None);
+        app.set_pixel(0, 0, [1, 2, 3, 255]).unwrap();
+        let mut sel = Selection::empty(4, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+
+        app.yank().unwrap();
+        assert_eq!(
+            app.image().get_pixel(0, 0).unwrap(),
+            [1, 2, 3, 255],
+            "yank doesn't cut, unlike grab"
+        );
+
+        app.cursor = (2, 0);
+        app.paste().unwrap();
+        assert_eq!(
+            app.floating
+                .as_ref()
+                .unwrap()
+                .base()
+                .get_pixel(0, 0)
+                .unwrap(),
+            [1, 2, 3, 255],
+            "paste's base is the unmodified image, since nothing was cut"
+        );
+        app.drop_floating();
+        assert!(app.floating.is_none());
+        assert_eq!(app.image().get_pixel(2, 0).unwrap(), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn paste_can_be_repeated_at_a_different_cursor_position() {
+        let mut app = App::new(Image::new(4, 1), None);
+        app.set_pixel(0, 0, [1, 2, 3, 255]).unwrap();
+        let mut sel = Selection::empty(4, 1);
+        sel.set(0, 0, true);
+        app.selection = Some(sel);
+        app.yank().unwrap();
+
+        app.cursor = (1, 0);
+        app.paste().unwrap();
+        app.drop_floating();
+
+        app.cursor = (3, 0);
+        app.paste().unwrap();
+        app.drop_floating();
+
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [1, 2, 3, 255]);
+        assert_eq!(app.image().get_pixel(3, 0).unwrap(), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn shift_moves_pixels_and_wraps_without_a_selection() {
+        let mut app = App::new(Image::new(3, 1), None);
+        app.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        app.shift(1, 0);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn shift_row_wraps_only_the_cursor_row_without_a_selection() {
+        let mut app = App::new(Image::new(3, 2), None);
+        app.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        app.set_pixel(0, 1, [2, 0, 0, 255]).unwrap();
+        app.cursor = (0, 0);
+
+        app.shift_row(1);
+
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(app.image().get_pixel(0, 1).unwrap(), [2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn shift_row_with_a_selection_shifts_every_selected_row_independently() {
+        let mut app = App::new(Image::new(3, 2), None);
+        app.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        app.set_pixel(0, 1, [2, 0, 0, 255]).unwrap();
+        app.selection = Some(Selection::rect(3, 2, 0, 0, 0, 1));
+
+        app.shift_row(1);
+
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(app.image().get_pixel(1, 1).unwrap(), [2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn shift_column_wraps_only_the_cursor_column_without_a_selection() {
+        let mut app = App::new(Image::new(2, 3), None);
+        app.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        app.set_pixel(1, 0, [2, 0, 0, 255]).unwrap();
+        app.cursor = (0, 0);
+
+        app.shift_column(1);
+
+        assert_eq!(app.image().get_pixel(0, 1).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(app.image().get_pixel(1, 0).unwrap(), [2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn failed_command_sets_localized_last_error() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.locale = Locale::Ja;
+        assert!(app.execute("w").is_err());
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some(crate::i18n::error_no_path(Locale::Ja))
+        );
+    }
+
+    #[test]
+    fn successful_command_clears_last_error() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.last_error = Some("stale".to_string());
+        app.execute("u").unwrap();
+        assert_eq!(app.last_error, None);
+    }
+
+    #[test]
+    fn successful_command_echoes_itself_as_the_status_message() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.execute("u").unwrap();
+        assert_eq!(app.status_message.as_deref(), Some(":u"));
+    }
+
+    #[test]
+    fn every_executed_command_is_appended_to_the_message_log() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.execute("u").unwrap();
+        assert!(app.execute("w").is_err());
+
+        assert_eq!(app.message_log.len(), 2);
+        assert_eq!(app.message_log[0].command, "u");
+        assert_eq!(app.message_log[0].error, None);
+        assert_eq!(app.message_log[1].command, "w");
+        assert!(app.message_log[1].error.is_some());
+    }
+
+    #[test]
+    fn message_log_drops_the_oldest_entry_past_its_capacity() {
+        let mut app = App::new(Image::new(1, 1), None);
+        for _ in 0..MESSAGE_LOG_CAPACITY + 1 {
+            app.execute("u").unwrap();
+        }
+        assert_eq!(app.message_log.len(), MESSAGE_LOG_CAPACITY);
+    }
+
+    #[test]
+    fn execute_outcome_reports_the_echoed_message_and_no_error_on_success() {
+        let mut app = App::new(Image::new(1, 1), None);
+        let outcome = app.execute_outcome("histogram");
+        assert_eq!(outcome.message.as_deref(), Some(":histogram"));
+        assert_eq!(outcome.error, None);
+    }
+
+    #[test]
+    fn execute_outcome_reports_the_localized_error_on_failure() {
+        let mut app = App::new(Image::new(1, 1), None);
+        let outcome = app.execute_outcome("fill 300 0 0 255");
+        assert!(outcome.error.is_some());
+    }
+
+    #[test]
+    fn execute_outcome_reports_changed_only_when_the_image_differs() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert!(!app.execute_outcome("histogram").changed);
+        assert!(app.execute_outcome("fill 0 0 0 255").changed);
+    }
+
+    #[test]
+    fn toggle_messages_flips_show_messages() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert!(!app.show_messages);
+        app.run(Command::ToggleMessages).unwrap();
+        assert!(app.show_messages);
+        app.run(Command::ToggleMessages).unwrap();
+        assert!(!app.show_messages);
+    }
+
+    #[test]
+    fn toggle_toolbar_flips_show_toolbar() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert!(!app.show_toolbar);
+        app.run(Command::ToggleToolbar).unwrap();
+        assert!(app.show_toolbar);
+        app.run(Command::ToggleToolbar).unwrap();
+        assert!(!app.show_toolbar);
+    }
+
+    #[test]
+    fn set_tool_switches_the_active_tool() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert_eq!(app.active_tool, ToolKind::Pencil);
+        app.set_tool(ToolKind::Select);
+        assert_eq!(app.active_tool, ToolKind::Select);
+    }
+
+    #[test]
+    fn help_shows_a_command_s_summary_in_the_status_message() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::Help(Some("fill".to_string()))).unwrap();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some(":fill - paint every selected pixel (or the whole image) with a solid color")
+        );
+    }
+
+    #[test]
+    fn map_with_no_arguments_reports_no_bindings_when_empty() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::Map(None)).unwrap();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("no custom key bindings")
+        );
+    }
+
+    #[test]
+    fn map_binds_a_key_to_an_action() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::Map(Some(('h', MappableAction::Left))))
+            .unwrap();
+        assert_eq!(app.key_map.get(&'h'), Some(&MappableAction::Left));
+        assert_eq!(app.status_message.as_deref(), Some("h bound to left"));
+    }
+
+    #[test]
+    fn map_warns_when_overriding_an_existing_binding() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::Map(Some(('h', MappableAction::Left))))
+            .unwrap();
+        app.run(Command::Map(Some(('h', MappableAction::Undo))))
+            .unwrap();
+        assert_eq!(app.key_map.get(&'h'), Some(&MappableAction::Undo));
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("h was bound to left; now bound to undo")
+        );
+    }
+
+    #[test]
+    fn unmap_removes_a_binding() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::Map(Some(('h', MappableAction::Left))))
+            .unwrap();
+        app.run(Command::Unmap('h')).unwrap();
+        assert_eq!(app.key_map.get(&'h'), None);
+        assert_eq!(app.status_message.as_deref(), Some("h unbound (was left)"));
+    }
+
+    #[test]
+    fn unmap_reports_an_unbound_key() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.run(Command::Unmap('h')).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("h was not bound"));
+    }
+
+    #[test]
+    fn executing_help_shows_its_own_text_instead_of_echoing_the_command_line() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.execute("help fill").unwrap();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some(":fill - paint every selected pixel (or the whole image) with a solid color")
+        );
+    }
+
+    #[test]
+    fn invalid_argument_records_the_offending_token() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert!(app.execute("fill 300 0 0 255").is_err());
+        assert_eq!(app.invalid_token.as_deref(), Some("300"));
+    }
+
+    #[test]
+    fn successful_command_clears_the_invalid_token() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.invalid_token = Some("300".to_string());
+        app.execute("u").unwrap();
+        assert_eq!(app.invalid_token, None);
+    }
+
+    #[test]
+    fn command_insert_and_backspace_edit_at_the_cursor() {
+        let mut app = App::new(Image::new(1, 1), None);
+        for c in "wq".chars() {
+            app.command_insert(c);
+        }
+        app.command_move_left();
+        app.command_insert('x');
+        assert_eq!(app.command_line, "wxq");
+
+        app.command_backspace();
+        assert_eq!(app.command_line, "wq");
+        assert_eq!(app.command_cursor, 1);
+    }
+
+    #[test]
+    fn command_home_and_end_jump_to_the_line_boundaries() {
+        let mut app = App::new(Image::new(1, 1), None);
+        for c in "write".chars() {
+            app.command_insert(c);
+        }
+        app.command_move_home();
+        assert_eq!(app.command_cursor, 0);
+        app.command_move_end();
+        assert_eq!(app.command_cursor, 5);
+    }
+
+    #[test]
+    fn command_delete_word_backward_removes_the_preceding_word_and_its_whitespace() {
+        let mut app = App::new(Image::new(1, 1), None);
+        for c in "export gb ".chars() {
+            app.command_insert(c);
+        }
+        app.command_delete_word_backward();
+        assert_eq!(app.command_line, "export ");
+        assert_eq!(app.command_cursor, 7);
+    }
+
+    #[test]
+    fn command_paste_strips_newlines_and_control_characters() {
+        let mut app = App::new(Image::new(1, 1), None);
+        app.command_insert('w');
+        app.command_insert(' ');
+
+        app.command_paste("out\r\n.png\t!");
+
+        assert_eq!(app.command_line, "w out.png!");
+    }
+
+    #[test]
+    fn command_paste_inserts_at_the_cursor() {
+        let mut app = App::new(Image::new(1, 1), None);
+        for c in "w .png".chars() {
+            app.command_insert(c);
+        }
+        app.command_move_left();
+        app.command_move_left();
+        app.command_move_left();
+        app.command_move_left();
+
+        app.command_paste("out");
+
+        assert_eq!(app.command_line, "w out.png");
+    }
+
+    #[test]
+    fn command_clear_resets_the_line_and_cursor() {
+        let mut app = App::new(Image::new(1, 1), None);
+        for c in "quit".chars() {
+            app.command_insert(c);
+        }
+        app.command_clear();
+        assert_eq!(app.command_line, "");
+        assert_eq!(app.command_cursor, 0);
+    }
+
+    #[test]
+    fn write_without_path_errors() {
+        let app_result = App::new(Image::new(1, 1), None).write(None, false);
+        assert!(matches!(app_result, Err(AppError::NoPath)));
+    }
+
+    #[test]
+    fn write_creates_missing_parent_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/deeper/sprite.png");
+        let mut app = App::new(Image::new(1, 1), None);
+
+        app.write(Some(&path), false).unwrap();
+
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn write_to_an_existing_path_defers_to_a_pending_overwrite_instead_of_saving() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        std::fs::write(&path, b"not a real png").unwrap();
+        let mut app = App::new(Image::new(1, 1), None);
+
+        app.write(Some(&path), false).unwrap();
+
+        assert_eq!(app.pending_overwrite.as_deref(), Some(path.as_path()));
+        assert_eq!(std::fs::read(&path).unwrap(), b"not a real png");
+    }
+
+    #[test]
+    fn write_bang_overwrites_an_existing_path_immediately() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        std::fs::write(&path, b"not a real png").unwrap();
+        let mut app = App::new(Image::new(1, 1), None);
+
+        app.write(Some(&path), true).unwrap();
+
+        assert!(app.pending_overwrite.is_none());
+        assert_ne!(std::fs::read(&path).unwrap(), b"not a real png");
+    }
+
+    #[test]
+    fn confirm_overwrite_saves_the_pending_path_and_clears_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        std::fs::write(&path, b"not a real png").unwrap();
+        let mut app = App::new(Image::new(1, 1), None);
+        app.write(Some(&path), false).unwrap();
+
+        app.confirm_overwrite().unwrap();
+
+        assert!(app.pending_overwrite.is_none());
+        assert_ne!(std::fs::read(&path).unwrap(), b"not a real png");
+    }
+
+    #[test]
+    fn cancel_overwrite_leaves_the_file_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        std::fs::write(&path, b"not a real png").unwrap();
+        let mut app = App::new(Image::new(1, 1), None);
+        app.write(Some(&path), false).unwrap();
+
+        app.cancel_overwrite();
+
+        assert!(app.pending_overwrite.is_none());
+        assert_eq!(std::fs::read(&path).unwrap(), b"not a real png");
+    }
+
+    #[test]
+    fn post_save_cmd_runs_with_the_saved_path_substituted_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        let mut app = App::new(Image::new(1, 1), None);
+        app.post_save_cmd = Some("touch {file}.done".to_string());
+
+        app.write(Some(&path), true).unwrap();
+
+        assert!(path.with_extension("png.done").exists());
+    }
+
+    #[test]
+    fn post_save_cmd_does_not_let_a_crafted_path_run_extra_shell_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let evil_dir = dir.path().join("$(touch pwned)'; touch pwned2; echo '");
+        std::fs::create_dir_all(&evil_dir).unwrap();
+        let path = evil_dir.join("sprite.png");
+        let mut app = App::new(Image::new(1, 1), None);
+        app.post_save_cmd = Some("touch {file}.done".to_string());
+
+        app.write(Some(&path), true).unwrap();
+
+        assert!(path.with_extension("png.done").exists());
+        assert!(!dir.path().join("pwned").exists());
+        assert!(!dir.path().join("pwned2").exists());
+    }
+
+    #[test]
+    fn post_save_cmd_failure_does_not_fail_the_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        let mut app = App::new(Image::new(1, 1), None);
+        app.post_save_cmd = Some("exit 1".to_string());
+
+        app.write(Some(&path), true).unwrap();
+
+        assert!(path.exists());
+    }
+
+    /// Initializes a git repo in `dir` with a usable identity, so `git
+    /// commit` succeeds without relying on any ambient user config.
+    fn init_git_repo(dir: &Path) {
+        for args in [
+            &["init", "-q"][..],
+            &["config", "user.email", "tie-test@example.com"],
+            &["config", "user.name", "tie"],
+        ] {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(args)
+                .output()
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn write_and_commit_saves_then_commits_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_repo(dir.path());
+        let path = dir.path().join("sprite.png");
+        let mut app = App::new(Image::new(1, 1), None);
+
+        app.write_and_commit(Some(&path), false, "add sprite")
+            .unwrap();
+
+        assert!(path.exists());
+        let log = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(["log", "--format=%s"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&log.stdout).trim(), "add sprite");
+    }
+
+    #[test]
+    fn write_and_commit_skips_the_commit_when_the_save_is_deferred() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_repo(dir.path());
+        let path = dir.path().join("sprite.png");
+        std::fs::write(&path, b"not a real png").unwrap();
+        let mut app = App::new(Image::new(1, 1), None);
+
+        app.write_and_commit(Some(&path), false, "add sprite")
+            .unwrap();
+
+        assert_eq!(app.pending_overwrite.as_deref(), Some(path.as_path()));
+        let log = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(["log", "--format=%s"])
+            .output()
+            .unwrap();
+        assert!(!log.status.success() || log.stdout.is_empty());
+    }
+
+    #[test]
+    fn write_reports_the_file_s_git_status_in_the_status_message() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_repo(dir.path());
+        let path = dir.path().join("sprite.png");
+        let mut app = App::new(Image::new(1, 1), None);
+
+        app.write(Some(&path), false).unwrap();
+
+        assert_eq!(app.status_message.as_deref(), Some(":w [git: untracked]"));
+    }
+
+    #[test]
+    fn is_dirty_reflects_whether_the_image_changed_since_open() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert!(!app.is_dirty());
+        app.set_pixel(0, 0, [255, 255, 255, 255]).unwrap();
+        assert!(app.is_dirty());
+    }
+
+    #[test]
+    fn revert_without_unsaved_changes_reloads_right_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        Image::new(2, 2).save(&path).unwrap();
+        let mut app = App::new(Image::load(&path).unwrap(), Some(path.clone()));
+
+        app.revert(false).unwrap();
+
+        assert!(!app.pending_revert);
+    }
+
+    #[test]
+    fn revert_with_unsaved_changes_defers_to_a_pending_revert() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        Image::new(2, 2).save(&path).unwrap();
+        let mut app = App::new(Image::load(&path).unwrap(), Some(path.clone()));
+        app.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+
+        app.revert(false).unwrap();
+
+        assert!(app.pending_revert);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn revert_bang_discards_unsaved_changes_immediately() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        Image::new(2, 2).save(&path).unwrap();
+        let mut app = App::new(Image::load(&path).unwrap(), Some(path.clone()));
+        app.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+
+        app.revert(true).unwrap();
+
+        assert!(!app.pending_revert);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn confirm_revert_reloads_and_clears_the_pending_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        Image::new(2, 2).save(&path).unwrap();
+        let mut app = App::new(Image::load(&path).unwrap(), Some(path.clone()));
+        app.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        app.revert(false).unwrap();
+
+        app.confirm_revert().unwrap();
+
+        assert!(!app.pending_revert);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn cancel_revert_keeps_the_in_memory_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprite.png");
+        Image::new(2, 2).save(&path).unwrap();
+        let mut app = App::new(Image::load(&path).unwrap(), Some(path.clone()));
+        app.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        app.revert(false).unwrap();
+
+        app.cancel_revert();
+
+        assert!(!app.pending_revert);
+        assert_eq!(app.image().get_pixel(0, 0).unwrap(), [255, 0, 0, 255]);
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[test]
+    fn copy_color_under_cursor_shows_the_hex_readout_without_a_clipboard() {
+        let mut app = App::new(Image::new(2, 2), None);
+        app.set_pixel(0, 0, [18, 52, 86, 255]).unwrap();
+        app.cursor = (0, 0);
+
+        app.copy_color_under_cursor().unwrap();
+
+        assert_eq!(app.status_message, Some("#123456".to_string()));
+    }
+
+    #[test]
+    fn revert_without_a_path_errors() {
+        let mut app = App::new(Image::new(1, 1), None);
+        assert!(matches!(app.revert(true), Err(AppError::NoPath)));
+    }
+}
@@ -0,0 +1,198 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::Widget;
+
+use crate::display::{braille_char, DisplaySettings, PreviewMode, BRAILLE_DOT_BITS};
+use crate::floating::Floating;
+use crate::image::{Coord, Image};
+use crate::selection::Selection;
+
+/// Renders an [`Image`] into the terminal, [`DisplaySettings::pixel_width`]
+/// character cells per pixel, using the cell background color to represent
+/// the pixel's RGB value - or, in [`PreviewMode::Braille`], one braille
+/// character per 2x4 block of pixels.
+pub struct Canvas<'a> {
+    image: &'a Image,
+    cursor: Coord,
+    extra_cursors: &'a [(u32, u32)],
+    floating: Option<&'a Floating>,
+    display: DisplaySettings,
+    selection: Option<(&'a Selection, Color)>,
+    guides: (&'a [u32], &'a [u32], Color),
+    cursor_glyph: char,
+}
+
+impl<'a> Canvas<'a> {
+    pub fn new(image: &'a Image, cursor: Coord) -> Self {
+        Self {
+            image,
+            cursor,
+            extra_cursors: &[],
+            floating: None,
+            display: DisplaySettings::default(),
+            selection: None,
+            guides: (&[], &[], Color::Reset),
+            cursor_glyph: 'X',
+        }
+    }
+
+    /// The character drawn at the cursor's cell - `X` by default, or
+    /// whichever glyph the active [`Tool`](crate::tool::Tool) uses.
+    pub fn with_cursor_glyph(mut self, glyph: char) -> Self {
+        self.cursor_glyph = glyph;
+        self
+    }
+
+    /// Extra cursors (`:cursor add`/`:cursor mirror`) drawn the same as the
+    /// primary cursor.
+    pub fn with_extra_cursors(mut self, extra_cursors: &'a [(u32, u32)]) -> Self {
+        self.extra_cursors = extra_cursors;
+        self
+    }
+
+    /// Composites a grabbed region's live preview over the base image.
+    pub fn with_floating(mut self, floating: Option<&'a Floating>) -> Self {
+        self.floating = floating;
+        self
+    }
+
+    /// How transparent cells are shown, per `:set checker` / `:set bgcolor`.
+    pub fn with_display(mut self, display: DisplaySettings) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Underlines selected cells in the theme's selection highlight color.
+    pub fn with_selection_highlight(
+        mut self,
+        selection: Option<&'a Selection>,
+        color: Color,
+    ) -> Self {
+        self.selection = selection.map(|selection| (selection, color));
+        self
+    }
+
+    /// Tints every cell on a persistent `:guide x`/`:guide y` column or row.
+    pub fn with_guides(mut self, x_guides: &'a [u32], y_guides: &'a [u32], color: Color) -> Self {
+        self.guides = (x_guides, y_guides, color);
+        self
+    }
+}
+
+impl Widget for Canvas<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.display.preview == PreviewMode::Braille {
+            return self.render_braille(area, buf);
+        }
+        let base = self.floating.map_or(self.image, Floating::base);
+        let pixel_width = self.display.pixel_width.max(1) as u32;
+        for y in 0..base.height().min(area.height as u32) {
+            for x in 0..base.width() {
+                if x * pixel_width >= area.width as u32 {
+                    break;
+                }
+                let [r, g, b, a] = base.get_pixel(x, y).expect("in-bounds by loop range");
+                let color = if a == 0 {
+                    match self.display.background_at(x, y) {
+                        Some((r, g, b)) => crate::color::Color::rgb(r, g, b).into(),
+                        None => Color::Reset,
+                    }
+                } else {
+                    crate::color::Color::rgb(r, g, b).into()
+                };
+                for dx in 0..pixel_width {
+                    let buf_x = x * pixel_width + dx;
+                    if buf_x >= area.width as u32 {
+                        break;
+                    }
+                    let cell = buf.get_mut(area.x + buf_x as u16, area.y + y as u16);
+                    cell.set_bg(color);
+                    if Coord::new(x, y) == self.cursor || self.extra_cursors.contains(&(x, y)) {
+                        let mut encoded = [0u8; 4];
+                        cell.set_symbol(self.cursor_glyph.encode_utf8(&mut encoded));
+                    } else {
+                        cell.set_symbol(" ");
+                    }
+                    let (x_guides, y_guides, guide_color) = self.guides;
+                    if x_guides.contains(&x) || y_guides.contains(&y) {
+                        cell.set_fg(guide_color);
+                        cell.modifier |= Modifier::DIM;
+                    }
+                    if let Some((selection, highlight)) = self.selection {
+                        if selection.contains(x, y) {
+                            cell.set_fg(highlight);
+                            cell.modifier |= Modifier::UNDERLINED;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(floating) = self.floating {
+            for (x, y, [r, g, b, _a]) in floating.preview_pixels() {
+                if x < 0 || y < 0 || x as u32 >= base.width() || y as u32 >= base.height() {
+                    continue;
+                }
+                for dx in 0..pixel_width {
+                    let buf_x = x as u32 * pixel_width + dx;
+                    if buf_x >= area.width as u32 {
+                        break;
+                    }
+                    buf.get_mut(area.x + buf_x as u16, area.y + y as u16)
+                        .set_bg(crate::color::Color::rgb(r, g, b).into());
+                }
+            }
+        }
+    }
+}
+
+impl Canvas<'_> {
+    /// Packs each 2x4 block of pixels into a braille character, for a dense
+    /// read-only preview of images too large to fit the terminal at 1:1.
+    /// Transparent pixels count as dots off; a cell's dots are colored by
+    /// the average RGB of its opaque pixels.
+    fn render_braille(self, area: Rect, buf: &mut Buffer) {
+        let image = self.image;
+        let cells_wide = (image.width().div_ceil(2)).min(area.width as u32);
+        let cells_high = (image.height().div_ceil(4)).min(area.height as u32);
+        for cell_y in 0..cells_high {
+            for cell_x in 0..cells_wide {
+                let mut bits = 0u8;
+                let mut sum = [0u32; 3];
+                let mut count = 0u32;
+                for (row, row_bits) in BRAILLE_DOT_BITS.iter().enumerate() {
+                    for (col, &bit) in row_bits.iter().enumerate() {
+                        let x = cell_x * 2 + col as u32;
+                        let y = cell_y * 4 + row as u32;
+                        if x >= image.width() || y >= image.height() {
+                            continue;
+                        }
+                        let [r, g, b, a] = image.get_pixel(x, y).expect("in-bounds by loop range");
+                        if a != 0 {
+                            bits |= bit;
+                            sum[0] += r as u32;
+                            sum[1] += g as u32;
+                            sum[2] += b as u32;
+                            count += 1;
+                        }
+                    }
+                }
+                let cell = buf.get_mut(area.x + cell_x as u16, area.y + cell_y as u16);
+                let mut encoded = [0u8; 4];
+                cell.set_symbol(braille_char(bits).encode_utf8(&mut encoded));
+                if count > 0 {
+                    let divisor = count;
+                    cell.set_fg(
+                        crate::color::Color::rgb(
+                            (sum[0] / divisor) as u8,
+                            (sum[1] / divisor) as u8,
+                            (sum[2] / divisor) as u8,
+                        )
+                        .into(),
+                    );
+                }
+            }
+        }
+    }
+}
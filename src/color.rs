@@ -0,0 +1,160 @@
+//! A single RGBA color with conversions to the representations scattered
+//! across the editor - a `#rrggbb` hex string, HSV for palette
+//! harmonization, and `ratatui`'s own [`ratatui::style::Color`] for
+//! rendering - so those conversions live in one place instead of being
+//! re-derived at each call site.
+
+use ratatui::style::Color as TuiColor;
+
+/// An 8-bit-per-channel RGBA color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// An opaque color - the common case for palette entries and painted
+    /// pixels, which don't carry their own alpha.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 255)
+    }
+
+    /// Formats as a lowercase `#rrggbb` hex string, dropping alpha - the
+    /// form shown in the status bar and pixel inspector.
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Parses a `RRGGBB` hex color, with or without a leading `#`. Always
+    /// opaque - hex colors in this editor never carry alpha.
+    pub fn parse_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim_start_matches('#');
+        if hex.len() != 6 {
+            return None;
+        }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        Some(Self::rgb(r, g, b))
+    }
+
+    /// Converts to `(hue in 0.0..360.0, saturation, value)`, each of the
+    /// latter two in `0.0..=1.0` - alpha doesn't participate in HSV.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * (((g - b) / delta).rem_euclid(6.0))
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        (hue, saturation, max)
+    }
+
+    /// Builds an opaque color from `(hue, saturation, value)` - hue in any
+    /// range (wrapped to `0.0..360.0`), saturation and value in `0.0..=1.0`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0);
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+        let m = value - chroma;
+        let (r, g, b) = match (hue / 60.0) as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let to_byte = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Self::rgb(to_byte(r), to_byte(g), to_byte(b))
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
+impl From<Color> for [u8; 3] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b]
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+impl From<Color> for TuiColor {
+    fn from(color: Color) -> Self {
+        TuiColor::Rgb(color.r, color.g, color.b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_formats_lowercase_without_alpha() {
+        assert_eq!(Color::new(0x12, 0x34, 0x56, 0).hex(), "#123456");
+    }
+
+    #[test]
+    fn parse_hex_accepts_a_leading_hash() {
+        assert_eq!(
+            Color::parse_hex("#ff00aa"),
+            Some(Color::rgb(0xff, 0x00, 0xaa))
+        );
+        assert_eq!(
+            Color::parse_hex("ff00aa"),
+            Some(Color::rgb(0xff, 0x00, 0xaa))
+        );
+    }
+
+    #[test]
+    fn parse_hex_rejects_the_wrong_length() {
+        assert_eq!(Color::parse_hex("fff"), None);
+    }
+
+    #[test]
+    fn hsv_round_trips_through_rgb() {
+        let color = Color::rgb(0x3c, 0x9a, 0xe8);
+        let (h, s, v) = color.to_hsv();
+        assert_eq!(Color::from_hsv(h, s, v), color);
+    }
+
+    #[test]
+    fn tui_color_conversion_drops_alpha() {
+        assert_eq!(
+            TuiColor::from(Color::new(10, 20, 30, 0)),
+            TuiColor::Rgb(10, 20, 30)
+        );
+    }
+}
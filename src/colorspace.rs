@@ -0,0 +1,117 @@
+use serde::Deserialize;
+
+/// Whether brightness math (`:adjust`) works directly on raw sRGB bytes, or
+/// converts to linear light first, where the same step raises a dark
+/// channel by many more byte values than a bright one. Switchable live with
+/// `:set colorspace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorSpace {
+    #[default]
+    Gamma,
+    Raw,
+}
+
+/// Decodes an 8-bit sRGB-encoded channel into linear light, `0.0..=1.0`.
+fn to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a linear-light value, `0.0..=1.0`, back into an 8-bit sRGB channel.
+fn from_linear(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let c = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+impl ColorSpace {
+    /// Adds `delta` to a channel, clamped to `0..=255`. In `Gamma` mode the
+    /// addition happens in linear light (tie assumes every PNG is
+    /// sRGB-encoded, since `image` does not surface gAMA/sRGB chunk data);
+    /// in `Raw` mode it is plain byte math, for anyone who wants that instead.
+    pub fn adjust_channel(self, channel: u8, delta: i16) -> u8 {
+        match self {
+            ColorSpace::Raw => (channel as i16 + delta).clamp(0, 255) as u8,
+            ColorSpace::Gamma => {
+                let delta_linear = delta as f32 / 255.0;
+                from_linear(to_linear(channel) + delta_linear)
+            }
+        }
+    }
+
+    /// Interpolates a channel between `a` and `b`, `t` in `0.0..=1.0`, for
+    /// gradients like `:palette ramp`. In `Gamma` mode the blend happens in
+    /// linear light, so a 50% step looks like the midpoint brightness
+    /// instead of the midpoint byte value; in `Raw` mode it's a plain byte
+    /// lerp.
+    pub fn lerp_channel(self, a: u8, b: u8, t: f32) -> u8 {
+        match self {
+            ColorSpace::Raw => (a as f32 + (b as f32 - a as f32) * t)
+                .round()
+                .clamp(0.0, 255.0) as u8,
+            ColorSpace::Gamma => from_linear(to_linear(a) + (to_linear(b) - to_linear(a)) * t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_mode_is_plain_byte_math() {
+        assert_eq!(ColorSpace::Raw.adjust_channel(100, 10), 110);
+        assert_eq!(ColorSpace::Raw.adjust_channel(250, 10), 255);
+        assert_eq!(ColorSpace::Raw.adjust_channel(5, -10), 0);
+    }
+
+    #[test]
+    fn gamma_mode_brightens_shadows_more_than_raw_does() {
+        let raw = ColorSpace::Raw.adjust_channel(20, 20);
+        let gamma = ColorSpace::Gamma.adjust_channel(20, 20);
+        assert!(
+            gamma > raw,
+            "gamma={gamma} should brighten more than raw={raw}"
+        );
+    }
+
+    #[test]
+    fn raw_lerp_channel_is_plain_byte_interpolation() {
+        assert_eq!(ColorSpace::Raw.lerp_channel(0, 100, 0.5), 50);
+        assert_eq!(ColorSpace::Raw.lerp_channel(10, 10, 0.5), 10);
+    }
+
+    #[test]
+    fn gamma_lerp_channel_midpoint_is_brighter_than_the_raw_byte_midpoint() {
+        let raw_mid = ColorSpace::Raw.lerp_channel(0, 255, 0.5);
+        let gamma_mid = ColorSpace::Gamma.lerp_channel(0, 255, 0.5);
+        assert!(
+            gamma_mid > raw_mid,
+            "gamma={gamma_mid} should be brighter than raw={raw_mid} at the midpoint"
+        );
+    }
+
+    #[test]
+    fn lerp_channel_at_the_endpoints_returns_the_endpoints_exactly() {
+        for colorspace in [ColorSpace::Raw, ColorSpace::Gamma] {
+            assert_eq!(colorspace.lerp_channel(20, 200, 0.0), 20);
+            assert_eq!(colorspace.lerp_channel(20, 200, 1.0), 200);
+        }
+    }
+
+    #[test]
+    fn linear_round_trip_is_lossless_at_8_bits() {
+        for channel in 0..=255u8 {
+            assert_eq!(from_linear(to_linear(channel)), channel);
+        }
+    }
+}
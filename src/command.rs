@@ -0,0 +1,1987 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::colorspace::ColorSpace;
+use crate::display::{Checker, PreviewMode};
+use crate::filter::Kernel;
+use crate::generate::StripeDirection;
+use crate::image::{Axis, Coord, Edge};
+use crate::keyconfig::MappableAction;
+use crate::palette::{DistanceMetric, Harmony, PaletteName};
+use crate::theme::ThemeName;
+use crate::tool::ToolKind;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CommandError {
+    #[error("unknown command: {0}")]
+    Unknown(String),
+    #[error("'{0}' expects a path argument")]
+    MissingPath(String),
+    #[error("invalid argument '{0}'")]
+    InvalidArgument(String),
+}
+
+/// A parsed `:`-command, as typed into the command line.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// `w [path]` - save, asking to confirm if `path` already exists.
+    /// `w! [path]` (`force: true`) overwrites without asking.
+    Write {
+        path: Option<PathBuf>,
+        force: bool,
+    },
+    /// `w [path] +commit "message"` - save, then `git add` and `git commit`
+    /// the saved file with `message`, for asset repos that want every edit
+    /// recorded as its own commit. `w!` still applies to the save half;
+    /// the commit only runs if the save happened right away rather than
+    /// deferring to an overwrite confirmation.
+    WriteAndCommit {
+        path: Option<PathBuf>,
+        force: bool,
+        message: String,
+    },
+    /// `revert`/`e!` - discard in-memory edits and reload the image from
+    /// disk, asking to confirm first if there are unsaved changes. `revert!`
+    /// (`force: true`) skips that and reloads immediately.
+    Revert {
+        force: bool,
+    },
+    Quit,
+    Undo,
+    Redo,
+    /// `histogram` - toggle a per-channel (R/G/B and luminance) value
+    /// histogram of the current image below the canvas, for judging
+    /// exposure and color balance when importing reference images.
+    ToggleHistogram,
+    /// `inspect` - toggle a magnified neighborhood of pixels around the
+    /// cursor, each labelled with its exact hex color, below the canvas -
+    /// for precise inspection without zooming the main view.
+    ToggleInspector,
+    /// `messages` - toggle a log of recently executed command lines and
+    /// whether each succeeded, below the canvas - mirrors vim's
+    /// `:messages`, for checking a command's result after the status bar
+    /// has moved on.
+    ToggleMessages,
+    /// `toolbar` - toggle a strip listing the available tools and their
+    /// hotkeys below the canvas, highlighting the active one - a
+    /// discoverable alternative to memorizing `F5`-`F7`/`:tool`.
+    ToggleToolbar,
+    /// `help [command]` - with no argument, list every command name known
+    /// to [`COMMANDS`] in the status bar. With one, show that command's
+    /// one-line summary.
+    Help(Option<String>),
+    /// `autocrop` - trim transparent rows/columns from every edge,
+    /// shrinking the canvas to fit the opaque drawing.
+    AutoCrop,
+    /// `pad <left|right|top|bottom|all> <amount> [color]` - grow the canvas
+    /// in a direction, filling the new pixels with `color` (transparent
+    /// black if omitted) - the opposite of [`Command::AutoCrop`], for a
+    /// sprite that's outgrown its original bounds.
+    Pad {
+        edge: Edge,
+        amount: u32,
+        color: [u8; 4],
+    },
+    /// `center [x|y]` - translate the opaque drawing so its bounding box is
+    /// centered on the canvas, along both axes or just the given one.
+    Center(Axis),
+    /// `delrow` - delete the row the cursor is on, shrinking the canvas by
+    /// one row - handy for trimming a stray row from a sprite sheet.
+    DeleteRow,
+    /// `delcol` - like [`Command::DeleteRow`] but for the cursor's column.
+    DeleteColumn,
+    /// `insrow` - insert a blank row before the cursor's row, growing the
+    /// canvas by one row - the complement of [`Command::DeleteRow`], for
+    /// opening up a line of spacing in a sprite.
+    InsertRow,
+    /// `inscol` - like [`Command::InsertRow`] but for the cursor's column.
+    InsertColumn,
+    /// `export timelapse <dir> [every=N]` - dump one PNG per `every`-th history
+    /// snapshot into `dir`, so the edit session can be replayed.
+    ExportTimelapse {
+        dir: PathBuf,
+        every: u32,
+    },
+    /// `export apng <path> [every=N] [delay=MS]` - like [`Command::ExportTimelapse`],
+    /// but writes the same `every`-th history snapshots into one animated PNG
+    /// instead of a directory of loose files, so the edit session can be
+    /// replayed without converting anything after the fact.
+    ExportApng {
+        path: PathBuf,
+        every: u32,
+        delay_ms: u32,
+    },
+    /// `export ico <path> <sizes>` - resample the buffer to each comma-separated
+    /// size (e.g. `16,32,48`) and write a multi-resolution ICO/favicon.
+    ExportIco {
+        path: PathBuf,
+        sizes: Vec<u32>,
+    },
+    /// `export pico8 <path>` - write the image as PICO-8 `__gfx__` hex: one
+    /// hex digit per pixel, the index of its nearest active-palette color.
+    ExportPico8Gfx(PathBuf),
+    /// `export gb <path>` - write the image as raw Game Boy 2bpp tile data.
+    ExportGameBoyTiles(PathBuf),
+    /// `export selection <path>` - crop to the active selection's bounding
+    /// box and write it as a standalone PNG, to share part of the artwork
+    /// without cropping externally.
+    ExportSelection(PathBuf),
+    /// `cursor add x y` - place an extra cursor that paint operations also
+    /// apply at, alongside the primary one.
+    CursorAdd(Coord),
+    /// `cursor clear` - drop every extra cursor.
+    CursorClear,
+    /// `cursor mirror N` - replace the extra cursors with a grid spaced `N`
+    /// pixels apart in both axes, anchored at the primary cursor.
+    CursorMirror(u32),
+    /// `mark <letter>` - bookmark the cursor's current position under
+    /// `letter`, jumped back to with `'<letter>` in normal mode.
+    Mark(char),
+    /// `guide x|y <n>` - toggle a persistent guide line at column/row `n` on
+    /// the canvas, on if it wasn't already set, off if it was.
+    Guide {
+        axis: Axis,
+        position: u32,
+    },
+    SelectAll,
+    SelectNone,
+    SelectInvert,
+    /// `select wand [global]` - select pixels matching the color under the
+    /// cursor: contiguous by default, or anywhere in the image with `global`.
+    SelectWand {
+        global: bool,
+    },
+    /// `select x0 y0 x1 y1` - select the inclusive rectangle between two
+    /// corners, e.g. `select 0 0 15 15` - for precise selections and
+    /// driving the selection feature from scripts/headless mode, without a
+    /// terminal to drag a selection by hand.
+    SelectRect {
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+    },
+    /// `yank` - copy the active selection's opaque pixels to the clipboard,
+    /// relative to its top-left corner, without modifying the image.
+    Yank,
+    /// `paste` - drop the last yanked region onto the canvas as a floating
+    /// layer anchored at the cursor, previewed like a grabbed selection
+    /// until it is dropped or cancelled. Scoped to the current buffer -
+    /// there's no multi-buffer/tab support to share it across files yet.
+    Paste,
+    /// `fill r g b a` - paint every selected pixel (or every pixel, if no
+    /// selection is active) with a solid color.
+    Fill([u8; 4]),
+    /// `replace r1 g1 b1 a1 r2 g2 b2 a2` - swap one color for another within
+    /// the active selection.
+    Replace {
+        from: [u8; 4],
+        to: [u8; 4],
+    },
+    /// `adjust <delta>` - add `delta` to every RGB channel of selected pixels.
+    Adjust(i16),
+    /// `shift dx dy` - wrap-shift selected pixels by `(dx, dy)`.
+    Shift(i32, i32),
+    /// `shiftrow N` - wrap-shift the cursor's row left (`N` negative) or
+    /// right by `N` pixels. With an active selection, every row containing
+    /// a selected pixel is shifted independently instead of just the
+    /// cursor's row - the classic trick for animating water/flag rows at
+    /// different speeds.
+    ShiftRow(i32),
+    /// `shiftcol N` - like [`Command::ShiftRow`], but for the cursor's
+    /// column (or every selected column), shifted up/down.
+    ShiftColumn(i32),
+    /// `repeat dx dy n` - re-apply the most recent edit `n` more times,
+    /// each offset by `(dx, dy)` from the last, to stamp out fences,
+    /// bricks, and dotted lines.
+    Repeat {
+        dx: i32,
+        dy: i32,
+        n: u32,
+    },
+    /// `set checker dark|light|off` - how transparent cells are shown.
+    SetChecker(Checker),
+    /// `set bgcolor r g b` - show transparent cells as a solid color.
+    SetBgColor(u8, u8, u8),
+    /// `set bgcolor none` - go back to the checkerboard.
+    ClearBgColor,
+    /// `set colorspace gamma|raw` - whether `:adjust` works in linear light
+    /// or directly on raw sRGB bytes.
+    SetColorSpace(ColorSpace),
+    /// `set preview normal|braille` - render one pixel per cell, or a dense
+    /// braille preview for images too large to fit at 1:1.
+    SetPreview(PreviewMode),
+    /// `set pixelwidth 1|2|3|4` - how many character cells wide a pixel
+    /// renders, to correct for non-square terminal fonts.
+    SetPixelWidth(u8),
+    /// `set grid N` - how many pixels a grid-snapped move (`G` to toggle)
+    /// covers.
+    SetGridSize(u32),
+    /// `theme dark|light|high-contrast` - switch the UI chrome's color theme.
+    SetTheme(ThemeName),
+    /// `tool pencil|eyedropper|select` - switch the active editing tool,
+    /// changing the canvas cursor's glyph.
+    SetTool(ToolKind),
+    /// `palette use pico8|tic80|none` - constrain paint operations to a
+    /// fixed fantasy-console palette.
+    SetPalette(PaletteName),
+    /// `palette name <index> <name>` - label a palette cell so it can be
+    /// addressed by name instead of its numeric index.
+    PaletteNameCell {
+        index: u8,
+        name: String,
+    },
+    /// `palette swap <a> <b>` - swap two palette cells' colors, keeping
+    /// each name attached to its label rather than its position.
+    PaletteSwap {
+        a: u8,
+        b: u8,
+    },
+    /// `palette from-file <path>` - replace the working palette with the
+    /// distinct colors found in another image, so a whole sprite set can
+    /// share one project's colors.
+    PaletteFromFile(PathBuf),
+    /// `palette lospec <slug>` - fetch a community palette by its Lospec
+    /// slug and load it into the working palette, caching it locally.
+    /// Available only when built with the `network` feature.
+    #[cfg(feature = "network")]
+    PaletteLospec(String),
+    /// `set <name> r g b` - override the color of a palette cell previously
+    /// labeled with `:palette name`. `set <name>@<page> r g b` restricts
+    /// the match to the cell named `<name>` on palette page `<page>`, for
+    /// palettes where the same name was reused across pages.
+    SetNamedColor {
+        name: String,
+        page: Option<u8>,
+        color: [u8; 3],
+    },
+    /// `palette harmonize <seed> complementary|triadic|analogous` - fill
+    /// the other cells with colors derived from cell `seed`'s hue.
+    PaletteHarmonize {
+        seed: u8,
+        harmony: Harmony,
+    },
+    /// `palette ramp <start> <end> <steps>` - write a `steps`-step shading
+    /// gradient from cell `start` to cell `end` into `steps` consecutive
+    /// cells beginning at `start`.
+    PaletteRamp {
+        start: u8,
+        end: u8,
+        steps: u32,
+    },
+    /// `set lockpalette on|off` - reject paint operations whose color isn't
+    /// already an exact palette entry, instead of snapping to the nearest
+    /// one, for strict retro-style workflows.
+    SetLockPalette(bool),
+    /// `set palettemetric rgb|ciede2000` - how closeness is measured when
+    /// snapping paint to the nearest palette cell.
+    SetPaletteMetric(DistanceMetric),
+    /// `set pixelperfect on|off` - while painting continuously (pen-down
+    /// movement or a drag-paint chord), retroactively erase the corner
+    /// pixel of an L-shaped turn so diagonal strokes read as a clean
+    /// staircase instead of doubled-up pixels, as Aseprite's "pixel
+    /// perfect" mode does.
+    SetPixelPerfect(bool),
+    /// `palette check` - list colors in the image that aren't in the
+    /// active palette.
+    PaletteCheck,
+    /// `generate noise <r1 g1 b1 a1> <r2 g2 b2 a2>` - scatter two colors
+    /// pixel-by-pixel, deterministically by position.
+    GenerateNoise {
+        a: [u8; 4],
+        b: [u8; 4],
+    },
+    /// `generate checker <size> <r1 g1 b1 a1> <r2 g2 b2 a2>` - a
+    /// checkerboard of `size`-by-`size` squares alternating two colors.
+    GenerateChecker {
+        size: u32,
+        a: [u8; 4],
+        b: [u8; 4],
+    },
+    /// `generate stripes <size> horizontal|vertical <r1 g1 b1 a1> <r2 g2 b2 a2>`
+    /// - bands of `size` pixels alternating two colors.
+    GenerateStripes {
+        size: u32,
+        direction: StripeDirection,
+        a: [u8; 4],
+        b: [u8; 4],
+    },
+    /// `filter blur`/`filter sharpen` - run a preset 3x3 convolution over
+    /// selected pixels (the whole image, if no selection is active).
+    /// `filter kernel "w0 w1 ... w8"` runs a custom one instead, divided by
+    /// the weights' sum (or left undivided if they sum to zero or less).
+    Filter(Kernel),
+    /// `filter scanlines <0-1>` - darken every other row of selected
+    /// pixels by the given fraction, for a retro CRT look.
+    FilterScanlines(u8),
+    /// `filter crt` - scanlines plus a repeating red/green/blue phosphor
+    /// mask across columns.
+    FilterCrt,
+    /// `map` - list every key bound via a previous `:map`, in the status
+    /// bar. `map <key> <action>` binds `key` to `action` in normal mode,
+    /// warning (but still applying the new binding) if `key` was already
+    /// mapped to something else.
+    Map(Option<(char, MappableAction)>),
+    /// `unmap <key>` - remove a binding added via `:map`.
+    Unmap(char),
+}
+
+/// Parses a command line (without the leading `:`) into a [`Command`].
+pub fn parse(line: &str) -> Result<Command, CommandError> {
+    let mut words = line.split_whitespace();
+    let name = words
+        .next()
+        .ok_or_else(|| CommandError::Unknown(String::new()))?;
+
+    match name {
+        "w" | "write" => parse_write(words, false),
+        "w!" | "write!" => parse_write(words, true),
+        "revert" => Ok(Command::Revert { force: false }),
+        "revert!" | "e!" => Ok(Command::Revert { force: true }),
+        "q" | "quit" => Ok(Command::Quit),
+        "u" | "undo" => Ok(Command::Undo),
+        "redo" => Ok(Command::Redo),
+        "histogram" => Ok(Command::ToggleHistogram),
+        "inspect" => Ok(Command::ToggleInspector),
+        "messages" => Ok(Command::ToggleMessages),
+        "toolbar" => Ok(Command::ToggleToolbar),
+        "help" => Ok(Command::Help(words.next().map(str::to_string))),
+        "autocrop" => Ok(Command::AutoCrop),
+        "pad" => parse_pad(words),
+        "center" => match words.next() {
+            Some("x") => Ok(Command::Center(Axis::X)),
+            Some("y") => Ok(Command::Center(Axis::Y)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Ok(Command::Center(Axis::Both)),
+        },
+        "mark" => {
+            let letter = words
+                .next()
+                .and_then(|w| w.chars().next())
+                .ok_or_else(|| CommandError::InvalidArgument("mark".to_string()))?;
+            Ok(Command::Mark(letter))
+        }
+        "guide" => {
+            let axis = match words.next() {
+                Some("x") => Axis::X,
+                Some("y") => Axis::Y,
+                Some(other) => return Err(CommandError::InvalidArgument(other.to_string())),
+                None => return Err(CommandError::InvalidArgument("guide".to_string())),
+            };
+            let position = parse_u32(words.next(), "guide")?;
+            Ok(Command::Guide { axis, position })
+        }
+        "delrow" => Ok(Command::DeleteRow),
+        "delcol" => Ok(Command::DeleteColumn),
+        "insrow" => Ok(Command::InsertRow),
+        "inscol" => Ok(Command::InsertColumn),
+        "export" => parse_export(words),
+        "cursor" => parse_cursor(words),
+        "select" => parse_select(words),
+        "yank" => Ok(Command::Yank),
+        "paste" => Ok(Command::Paste),
+        "fill" => Ok(Command::Fill(parse_color(&mut words)?)),
+        "replace" => {
+            let from = parse_color(&mut words)?;
+            let to = parse_color(&mut words)?;
+            Ok(Command::Replace { from, to })
+        }
+        "adjust" => {
+            let delta = words
+                .next()
+                .ok_or_else(|| CommandError::InvalidArgument("adjust".to_string()))?;
+            let delta = delta
+                .parse()
+                .map_err(|_| CommandError::InvalidArgument(delta.to_string()))?;
+            Ok(Command::Adjust(delta))
+        }
+        "shift" => {
+            let dx = parse_i32(words.next(), "shift")?;
+            let dy = parse_i32(words.next(), "shift")?;
+            Ok(Command::Shift(dx, dy))
+        }
+        "shiftrow" => Ok(Command::ShiftRow(parse_i32(words.next(), "shiftrow")?)),
+        "shiftcol" => Ok(Command::ShiftColumn(parse_i32(words.next(), "shiftcol")?)),
+        "repeat" => {
+            let dx = parse_i32(words.next(), "repeat")?;
+            let dy = parse_i32(words.next(), "repeat")?;
+            let n = parse_u32(words.next(), "repeat")?;
+            Ok(Command::Repeat { dx, dy, n })
+        }
+        "set" => parse_set(words),
+        "generate" => parse_generate(words),
+        "filter" => parse_filter(words),
+        "palette" => match words.next() {
+            Some("use") => match words.next() {
+                Some("pico8") => Ok(Command::SetPalette(PaletteName::Pico8)),
+                Some("tic80") => Ok(Command::SetPalette(PaletteName::Tic80)),
+                Some("none") => Ok(Command::SetPalette(PaletteName::None)),
+                Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+                None => Err(CommandError::InvalidArgument("palette use".to_string())),
+            },
+            Some("name") => {
+                let index = parse_u8(words.next(), "palette name")?;
+                let name = words
+                    .next()
+                    .ok_or_else(|| CommandError::InvalidArgument("palette name".to_string()))?;
+                Ok(Command::PaletteNameCell {
+                    index,
+                    name: name.to_string(),
+                })
+            }
+            Some("swap") => {
+                let a = parse_u8(words.next(), "palette swap")?;
+                let b = parse_u8(words.next(), "palette swap")?;
+                Ok(Command::PaletteSwap { a, b })
+            }
+            Some("from-file") => {
+                let path = words.next().ok_or_else(|| {
+                    CommandError::InvalidArgument("palette from-file".to_string())
+                })?;
+                Ok(Command::PaletteFromFile(expand_path(path)))
+            }
+            #[cfg(feature = "network")]
+            Some("lospec") => {
+                let slug = words
+                    .next()
+                    .ok_or_else(|| CommandError::InvalidArgument("palette lospec".to_string()))?;
+                Ok(Command::PaletteLospec(slug.to_string()))
+            }
+            Some("harmonize") => {
+                let seed = parse_u8(words.next(), "palette harmonize")?;
+                let harmony = match words.next() {
+                    Some("complementary") => Harmony::Complementary,
+                    Some("triadic") => Harmony::Triadic,
+                    Some("analogous") => Harmony::Analogous,
+                    Some(other) => return Err(CommandError::InvalidArgument(other.to_string())),
+                    None => {
+                        return Err(CommandError::InvalidArgument(
+                            "palette harmonize".to_string(),
+                        ))
+                    }
+                };
+                Ok(Command::PaletteHarmonize { seed, harmony })
+            }
+            Some("ramp") => {
+                let start = parse_u8(words.next(), "palette ramp")?;
+                let end = parse_u8(words.next(), "palette ramp")?;
+                let steps = parse_u32(words.next(), "palette ramp")?;
+                Ok(Command::PaletteRamp { start, end, steps })
+            }
+            Some("check") => Ok(Command::PaletteCheck),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("palette".to_string())),
+        },
+        "theme" => match words.next() {
+            Some("dark") => Ok(Command::SetTheme(ThemeName::Dark)),
+            Some("light") => Ok(Command::SetTheme(ThemeName::Light)),
+            Some("high-contrast") => Ok(Command::SetTheme(ThemeName::HighContrast)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("theme".to_string())),
+        },
+        "tool" => match words.next() {
+            Some("pencil") => Ok(Command::SetTool(ToolKind::Pencil)),
+            Some("eyedropper") => Ok(Command::SetTool(ToolKind::Eyedropper)),
+            Some("select") => Ok(Command::SetTool(ToolKind::Select)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("tool".to_string())),
+        },
+        "map" => match words.next() {
+            None => Ok(Command::Map(None)),
+            Some(key) => {
+                let key = single_char(key, "map")?;
+                let action = words
+                    .next()
+                    .ok_or_else(|| CommandError::InvalidArgument("map".to_string()))?;
+                let action = MappableAction::parse(action)
+                    .ok_or_else(|| CommandError::InvalidArgument(action.to_string()))?;
+                Ok(Command::Map(Some((key, action))))
+            }
+        },
+        "unmap" => {
+            let key = words
+                .next()
+                .ok_or_else(|| CommandError::InvalidArgument("unmap".to_string()))?;
+            Ok(Command::Unmap(single_char(key, "unmap")?))
+        }
+        other => Err(CommandError::Unknown(other.to_string())),
+    }
+}
+
+/// One entry in [`COMMANDS`]: a top-level command name as typed after the
+/// `:` prompt, paired with a one-line summary of what it does.
+pub struct CommandHelp {
+    pub name: &'static str,
+    pub summary: &'static str,
+}
+
+/// Every top-level command name [`parse`] recognizes, paired with a
+/// one-line summary - the single place `:help` reads from instead of
+/// duplicating a description of each command beside its doc comment.
+/// Aliases (`w!`, `revert!`, `e!`, ...) aren't listed separately; look them
+/// up under their primary name.
+pub const COMMANDS: &[CommandHelp] = &[
+    CommandHelp {
+        name: "w",
+        summary:
+            "save, asking to confirm if the path already exists (w! to overwrite without asking); +commit \"msg\" also git-commits it",
+    },
+    CommandHelp {
+        name: "revert",
+        summary: "discard in-memory edits and reload from disk (revert!/e! skips the confirmation)",
+    },
+    CommandHelp {
+        name: "q",
+        summary: "quit",
+    },
+    CommandHelp {
+        name: "u",
+        summary: "undo the last edit",
+    },
+    CommandHelp {
+        name: "redo",
+        summary: "redo the last undone edit",
+    },
+    CommandHelp {
+        name: "histogram",
+        summary: "toggle a per-channel value histogram below the canvas",
+    },
+    CommandHelp {
+        name: "inspect",
+        summary: "toggle a magnified pixel inspector below the canvas",
+    },
+    CommandHelp {
+        name: "messages",
+        summary: "toggle a log of recently executed commands below the canvas",
+    },
+    CommandHelp {
+        name: "toolbar",
+        summary: "toggle a strip of tools and their hotkeys below the canvas",
+    },
+    CommandHelp {
+        name: "help",
+        summary: "list every command, or show one command's summary",
+    },
+    CommandHelp {
+        name: "autocrop",
+        summary: "trim transparent rows/columns from every edge",
+    },
+    CommandHelp {
+        name: "pad",
+        summary: "grow the canvas in a direction, filling the new pixels with a color",
+    },
+    CommandHelp {
+        name: "center",
+        summary: "translate the opaque drawing so it's centered on the canvas",
+    },
+    CommandHelp {
+        name: "delrow",
+        summary: "delete the row the cursor is on",
+    },
+    CommandHelp {
+        name: "delcol",
+        summary: "delete the column the cursor is on",
+    },
+    CommandHelp {
+        name: "insrow",
+        summary: "insert a blank row before the cursor's row",
+    },
+    CommandHelp {
+        name: "inscol",
+        summary: "insert a blank column before the cursor's column",
+    },
+    CommandHelp {
+        name: "export",
+        summary: "write the image out as a timelapse, APNG, ICO, PICO-8, Game Boy, or selection crop",
+    },
+    CommandHelp {
+        name: "cursor",
+        summary: "add, clear, or mirror extra cursors alongside the primary one",
+    },
+    CommandHelp {
+        name: "mark",
+        summary: "bookmark the cursor's position under a letter",
+    },
+    CommandHelp {
+        name: "guide",
+        summary: "toggle a persistent guide line at a column or row",
+    },
+    CommandHelp {
+        name: "select",
+        summary: "select all/none, invert, match the color under the cursor, or a rectangle",
+    },
+    CommandHelp {
+        name: "yank",
+        summary: "copy the active selection's opaque pixels to the clipboard",
+    },
+    CommandHelp {
+        name: "paste",
+        summary: "drop the last yanked region onto the canvas at the cursor",
+    },
+    CommandHelp {
+        name: "fill",
+        summary: "paint every selected pixel (or the whole image) with a solid color",
+    },
+    CommandHelp {
+        name: "replace",
+        summary: "swap one color for another within the active selection",
+    },
+    CommandHelp {
+        name: "adjust",
+        summary: "add a delta to every RGB channel of selected pixels",
+    },
+    CommandHelp {
+        name: "shift",
+        summary: "wrap-shift selected pixels by (dx, dy)",
+    },
+    CommandHelp {
+        name: "shiftrow",
+        summary: "wrap-shift the cursor's row (or every selected row) left/right",
+    },
+    CommandHelp {
+        name: "shiftcol",
+        summary: "wrap-shift the cursor's column (or every selected column) up/down",
+    },
+    CommandHelp {
+        name: "repeat",
+        summary: "re-apply the most recent edit, offset, a number of times",
+    },
+    CommandHelp {
+        name: "set",
+        summary: "change a display/editing setting, e.g. checker, grid, pixelwidth, lockpalette",
+    },
+    CommandHelp {
+        name: "generate",
+        summary: "fill the image with noise, a checkerboard, or stripes",
+    },
+    CommandHelp {
+        name: "filter",
+        summary: "run a convolution, scanlines, or a CRT effect over selected pixels",
+    },
+    CommandHelp {
+        name: "palette",
+        summary: "use, name, swap, import, harmonize, ramp, or check the working palette",
+    },
+    CommandHelp {
+        name: "theme",
+        summary: "switch the UI chrome's color theme",
+    },
+    CommandHelp {
+        name: "tool",
+        summary: "switch the active tool (pencil, eyedropper, select)",
+    },
+    CommandHelp {
+        name: "map",
+        summary: "bind a key to an action (left, right, up, down, undo, paint, pen), or list custom bindings",
+    },
+    CommandHelp {
+        name: "unmap",
+        summary: "remove a key binding added with :map",
+    },
+];
+
+/// The text `:help` shows in the status bar: every command name if `topic`
+/// is `None`, or one command's summary if it names a known command.
+pub fn help_text(topic: Option<&str>) -> String {
+    match topic {
+        Some(name) => COMMANDS
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| format!(":{} - {}", c.name, c.summary))
+            .unwrap_or_else(|| format!("no help for \"{name}\"")),
+        None => COMMANDS
+            .iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn parse_set<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    match words.next() {
+        Some("checker") => match words.next() {
+            Some("dark") => Ok(Command::SetChecker(Checker::Dark)),
+            Some("light") => Ok(Command::SetChecker(Checker::Light)),
+            Some("off") => Ok(Command::SetChecker(Checker::Off)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("set checker".to_string())),
+        },
+        Some("bgcolor") => match words.next() {
+            Some("none") => Ok(Command::ClearBgColor),
+            Some(r) => {
+                let r = r
+                    .parse()
+                    .map_err(|_| CommandError::InvalidArgument(r.to_string()))?;
+                let g = words
+                    .next()
+                    .ok_or_else(|| CommandError::InvalidArgument("set bgcolor".to_string()))?;
+                let g = g
+                    .parse()
+                    .map_err(|_| CommandError::InvalidArgument(g.to_string()))?;
+                let b = words
+                    .next()
+                    .ok_or_else(|| CommandError::InvalidArgument("set bgcolor".to_string()))?;
+                let b = b
+                    .parse()
+                    .map_err(|_| CommandError::InvalidArgument(b.to_string()))?;
+                Ok(Command::SetBgColor(r, g, b))
+            }
+            None => Err(CommandError::InvalidArgument("set bgcolor".to_string())),
+        },
+        Some("colorspace") => match words.next() {
+            Some("gamma") => Ok(Command::SetColorSpace(ColorSpace::Gamma)),
+            Some("raw") => Ok(Command::SetColorSpace(ColorSpace::Raw)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("set colorspace".to_string())),
+        },
+        Some("preview") => match words.next() {
+            Some("normal") => Ok(Command::SetPreview(PreviewMode::Normal)),
+            Some("braille") => Ok(Command::SetPreview(PreviewMode::Braille)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("set preview".to_string())),
+        },
+        Some("pixelwidth") => match words.next() {
+            Some(n) => {
+                let n: u8 = n
+                    .parse()
+                    .map_err(|_| CommandError::InvalidArgument(n.to_string()))?;
+                if (1..=4).contains(&n) {
+                    Ok(Command::SetPixelWidth(n))
+                } else {
+                    Err(CommandError::InvalidArgument(n.to_string()))
+                }
+            }
+            None => Err(CommandError::InvalidArgument("set pixelwidth".to_string())),
+        },
+        Some("grid") => match words.next() {
+            Some(n) => {
+                let n: u32 = n
+                    .parse()
+                    .map_err(|_| CommandError::InvalidArgument(n.to_string()))?;
+                if n >= 1 {
+                    Ok(Command::SetGridSize(n))
+                } else {
+                    Err(CommandError::InvalidArgument(n.to_string()))
+                }
+            }
+            None => Err(CommandError::InvalidArgument("set grid".to_string())),
+        },
+        Some("lockpalette") => match words.next() {
+            Some("on") => Ok(Command::SetLockPalette(true)),
+            Some("off") => Ok(Command::SetLockPalette(false)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument("set lockpalette".to_string())),
+        },
+        Some("pixelperfect") => match words.next() {
+            Some("on") => Ok(Command::SetPixelPerfect(true)),
+            Some("off") => Ok(Command::SetPixelPerfect(false)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument(
+                "set pixelperfect".to_string(),
+            )),
+        },
+        Some("palettemetric") => match words.next() {
+            Some("rgb") => Ok(Command::SetPaletteMetric(DistanceMetric::Rgb)),
+            Some("ciede2000") => Ok(Command::SetPaletteMetric(DistanceMetric::Ciede2000)),
+            Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+            None => Err(CommandError::InvalidArgument(
+                "set palettemetric".to_string(),
+            )),
+        },
+        Some(other) => {
+            let (name, page) = match other.split_once('@') {
+                Some((name, page)) => {
+                    let page = page
+                        .parse()
+                        .map_err(|_| CommandError::InvalidArgument(other.to_string()))?;
+                    (name, Some(page))
+                }
+                None => (other, None),
+            };
+            let r = parse_u8(words.next(), other)?;
+            let g = parse_u8(words.next(), other)?;
+            let b = parse_u8(words.next(), other)?;
+            Ok(Command::SetNamedColor {
+                name: name.to_string(),
+                page,
+                color: [r, g, b],
+            })
+        }
+        None => Err(CommandError::InvalidArgument("set".to_string())),
+    }
+}
+
+fn parse_cursor<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    match words.next() {
+        Some("add") => {
+            let x = parse_u32(words.next(), "cursor add")?;
+            let y = parse_u32(words.next(), "cursor add")?;
+            Ok(Command::CursorAdd(Coord::new(x, y)))
+        }
+        Some("clear") => Ok(Command::CursorClear),
+        Some("mirror") => {
+            let spacing = parse_u32(words.next(), "cursor mirror")?;
+            Ok(Command::CursorMirror(spacing))
+        }
+        Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+        None => Err(CommandError::InvalidArgument("cursor".to_string())),
+    }
+}
+
+fn parse_u32(word: Option<&str>, context: &str) -> Result<u32, CommandError> {
+    let word = word.ok_or_else(|| CommandError::InvalidArgument(context.to_string()))?;
+    word.parse()
+        .map_err(|_| CommandError::InvalidArgument(word.to_string()))
+}
+
+fn parse_u8(word: Option<&str>, context: &str) -> Result<u8, CommandError> {
+    let word = word.ok_or_else(|| CommandError::InvalidArgument(context.to_string()))?;
+    word.parse()
+        .map_err(|_| CommandError::InvalidArgument(word.to_string()))
+}
+
+/// Parses a word as a single character - used for `:map`/`:unmap`'s key
+/// argument, which (unlike `:mark`'s letter) rejects anything longer than
+/// one character instead of silently taking its first.
+fn single_char(word: &str, context: &str) -> Result<char, CommandError> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(CommandError::InvalidArgument(context.to_string())),
+    }
+}
+
+fn parse_select<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    match words.next() {
+        Some("all") => Ok(Command::SelectAll),
+        Some("none") => Ok(Command::SelectNone),
+        Some("invert") => Ok(Command::SelectInvert),
+        Some("wand") => Ok(Command::SelectWand {
+            global: words.next() == Some("global"),
+        }),
+        Some(x0) => {
+            let x0 = x0
+                .parse()
+                .map_err(|_| CommandError::InvalidArgument(x0.to_string()))?;
+            let y0 = parse_u32(words.next(), "select")?;
+            let x1 = parse_u32(words.next(), "select")?;
+            let y1 = parse_u32(words.next(), "select")?;
+            Ok(Command::SelectRect { x0, y0, x1, y1 })
+        }
+        None => Err(CommandError::InvalidArgument("select".to_string())),
+    }
+}
+
+fn parse_generate<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    match words.next() {
+        Some("noise") => {
+            let a = parse_color(&mut words)?;
+            let b = parse_color(&mut words)?;
+            Ok(Command::GenerateNoise { a, b })
+        }
+        Some("checker") => {
+            let size = parse_u32(words.next(), "generate checker")?;
+            let a = parse_color(&mut words)?;
+            let b = parse_color(&mut words)?;
+            Ok(Command::GenerateChecker { size, a, b })
+        }
+        Some("stripes") => {
+            let size = parse_u32(words.next(), "generate stripes")?;
+            let direction = match words.next() {
+                Some("horizontal") => StripeDirection::Horizontal,
+                Some("vertical") => StripeDirection::Vertical,
+                Some(other) => return Err(CommandError::InvalidArgument(other.to_string())),
+                None => {
+                    return Err(CommandError::InvalidArgument(
+                        "generate stripes".to_string(),
+                    ))
+                }
+            };
+            let a = parse_color(&mut words)?;
+            let b = parse_color(&mut words)?;
+            Ok(Command::GenerateStripes {
+                size,
+                direction,
+                a,
+                b,
+            })
+        }
+        Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+        None => Err(CommandError::InvalidArgument("generate".to_string())),
+    }
+}
+
+/// Parses the shared body of `w`/`w!`: an optional path, followed by an
+/// optional `+commit "message"` suffix. The message is every word after
+/// `+commit`, rejoined with single spaces and stripped of any leading and
+/// trailing quotes, the same quote-stripping `filter kernel` does for its
+/// weights.
+fn parse_write<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+    force: bool,
+) -> Result<Command, CommandError> {
+    let mut path = None;
+    while let Some(word) = words.next() {
+        if word == "+commit" {
+            let message: Vec<&str> = words.by_ref().collect();
+            if message.is_empty() {
+                return Err(CommandError::InvalidArgument("w +commit".to_string()));
+            }
+            let message = message.join(" ").trim_matches('"').to_string();
+            return Ok(Command::WriteAndCommit {
+                path,
+                force,
+                message,
+            });
+        }
+        path = Some(expand_path(word));
+    }
+    Ok(Command::Write { path, force })
+}
+
+fn parse_filter<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    match words.next() {
+        Some("blur") => Ok(Command::Filter(crate::filter::BLUR)),
+        Some("sharpen") => Ok(Command::Filter(crate::filter::SHARPEN)),
+        Some("scanlines") => {
+            let fraction = parse_fraction_percent(words.next(), "filter scanlines")?;
+            Ok(Command::FilterScanlines(fraction))
+        }
+        Some("crt") => Ok(Command::FilterCrt),
+        Some("kernel") => {
+            let weights: Vec<i32> = words
+                .map(|word| word.trim_matches('"'))
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    word.parse()
+                        .map_err(|_| CommandError::InvalidArgument(word.to_string()))
+                })
+                .collect::<Result<_, _>>()?;
+            let weights: [i32; 9] = weights
+                .try_into()
+                .map_err(|_| CommandError::InvalidArgument("filter kernel".to_string()))?;
+            let sum: i32 = weights.iter().sum();
+            let divisor = if sum == 0 { 1 } else { sum };
+            Ok(Command::Filter(Kernel { weights, divisor }))
+        }
+        Some(other) => Err(CommandError::InvalidArgument(other.to_string())),
+        None => Err(CommandError::InvalidArgument("filter".to_string())),
+    }
+}
+
+fn parse_pad<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    let edge = match words.next() {
+        Some("left") => Edge::Left,
+        Some("right") => Edge::Right,
+        Some("top") => Edge::Top,
+        Some("bottom") => Edge::Bottom,
+        Some("all") => Edge::All,
+        Some(other) => return Err(CommandError::InvalidArgument(other.to_string())),
+        None => return Err(CommandError::InvalidArgument("pad".to_string())),
+    };
+    let amount = parse_u32(words.next(), "pad")?;
+    let rest: Vec<&str> = words.collect();
+    let color = if rest.is_empty() {
+        [0, 0, 0, 0]
+    } else {
+        parse_color(&mut rest.into_iter())?
+    };
+    Ok(Command::Pad {
+        edge,
+        amount,
+        color,
+    })
+}
+
+/// Parses a `0.0`-`1.0` fraction into a whole percent, for filters whose
+/// strength is more natural to type as a fraction than a percent.
+fn parse_fraction_percent(word: Option<&str>, context: &str) -> Result<u8, CommandError> {
+    let word = word.ok_or_else(|| CommandError::InvalidArgument(context.to_string()))?;
+    let fraction: f32 = word
+        .parse()
+        .map_err(|_| CommandError::InvalidArgument(word.to_string()))?;
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(CommandError::InvalidArgument(word.to_string()));
+    }
+    Ok((fraction * 100.0).round() as u8)
+}
+
+fn parse_i32(word: Option<&str>, context: &str) -> Result<i32, CommandError> {
+    let word = word.ok_or_else(|| CommandError::InvalidArgument(context.to_string()))?;
+    word.parse()
+        .map_err(|_| CommandError::InvalidArgument(word.to_string()))
+}
+
+fn parse_color<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<[u8; 4], CommandError> {
+    let mut channels = [0u8; 4];
+    for channel in &mut channels {
+        let word = words
+            .next()
+            .ok_or_else(|| CommandError::InvalidArgument("color".to_string()))?;
+        *channel = word
+            .parse()
+            .map_err(|_| CommandError::InvalidArgument(word.to_string()))?;
+    }
+    Ok(channels)
+}
+
+/// Expands a leading `~` and `$VAR`/`${VAR}` references in a user-typed
+/// path, e.g. `~/art/sprite.png` or `$ASSETS/x.png`. A variable that isn't
+/// set, or a `~` when the home directory can't be determined, is left as
+/// literal text so the resulting path still produces a normal "not found"
+/// error from the OS rather than silently resolving to something else.
+fn expand_path(raw: &str) -> PathBuf {
+    let expanded = expand_env_vars(raw);
+    match expanded.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches('/')),
+            None => PathBuf::from(expanded),
+        },
+        _ => PathBuf::from(expanded),
+    }
+}
+
+fn expand_env_vars(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+            match std::env::var(&name) {
+                Ok(value) => out.push_str(&value),
+                Err(_) => out.push_str(&format!("${{{name}}}")),
+            }
+        } else {
+            let mut name = String::new();
+            while matches!(chars.peek(), Some(c) if c.is_alphanumeric() || *c == '_') {
+                name.push(chars.next().expect("just peeked"));
+            }
+            if name.is_empty() {
+                out.push('$');
+            } else {
+                match std::env::var(&name) {
+                    Ok(value) => out.push_str(&value),
+                    Err(_) => out.push_str(&format!("${name}")),
+                }
+            }
+        }
+    }
+    out
+}
+
+fn parse_export<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, CommandError> {
+    match words.next() {
+        Some("timelapse") => {
+            let dir = words
+                .next()
+                .map(expand_path)
+                .ok_or_else(|| CommandError::MissingPath("export timelapse".to_string()))?;
+            let mut every = 1u32;
+            for arg in words {
+                if let Some(value) = arg.strip_prefix("every=") {
+                    every = value
+                        .parse()
+                        .map_err(|_| CommandError::InvalidArgument(arg.to_string()))?;
+                } else {
+                    return Err(CommandError::InvalidArgument(arg.to_string()));
+                }
+            }
+            Ok(Command::ExportTimelapse { dir, every })
+        }
+        Some("apng") => {
+            let path = words
+                .next()
+                .map(expand_path)
+                .ok_or_else(|| CommandError::MissingPath("export apng".to_string()))?;
+            let mut every = 1u32;
+            let mut delay_ms = 100u32;
+            for arg in words {
+                if let Some(value) = arg.strip_prefix("every=") {
+                    every = value
+                        .parse()
+                        .map_err(|_| CommandError::InvalidArgument(arg.to_string()))?;
+                } else if let Some(value) = arg.strip_prefix("delay=") {
+                    delay_ms = value
+                        .parse()
+                        .map_err(|_| CommandError::InvalidArgument(arg.to_string()))?;
+                } else {
+                    return Err(CommandError::InvalidArgument(arg.to_string()));
+                }
+            }
+            Ok(Command::ExportApng {
+                path,
+                every,
+                delay_ms,
+            })
+        }
+        Some("ico") => {
+            let path = words
+                .next()
+                .map(expand_path)
+                .ok_or_else(|| CommandError::MissingPath("export ico".to_string()))?;
+            let sizes = words
+                .next()
+                .ok_or_else(|| CommandError::InvalidArgument("export ico".to_string()))?;
+            let sizes = sizes
+                .split(',')
+                .map(|size| {
+                    size.parse()
+                        .map_err(|_| CommandError::InvalidArgument(size.to_string()))
+                })
+                .collect::<Result<Vec<u32>, _>>()?;
+            Ok(Command::ExportIco { path, sizes })
+        }
+        Some("pico8") => {
+            let path = words
+                .next()
+                .map(expand_path)
+                .ok_or_else(|| CommandError::MissingPath("export pico8".to_string()))?;
+            Ok(Command::ExportPico8Gfx(path))
+        }
+        Some("gb") => {
+            let path = words
+                .next()
+                .map(expand_path)
+                .ok_or_else(|| CommandError::MissingPath("export gb".to_string()))?;
+            Ok(Command::ExportGameBoyTiles(path))
+        }
+        Some("selection") => {
+            let path = words
+                .next()
+                .map(expand_path)
+                .ok_or_else(|| CommandError::MissingPath("export selection".to_string()))?;
+            Ok(Command::ExportSelection(path))
+        }
+        Some(other) => Err(CommandError::Unknown(format!("export {other}"))),
+        None => Err(CommandError::Unknown("export".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quit_and_its_alias() {
+        assert_eq!(parse("q"), Ok(Command::Quit));
+        assert_eq!(parse("quit"), Ok(Command::Quit));
+    }
+
+    #[test]
+    fn parses_histogram() {
+        assert_eq!(parse("histogram"), Ok(Command::ToggleHistogram));
+    }
+
+    #[test]
+    fn parses_inspect() {
+        assert_eq!(parse("inspect"), Ok(Command::ToggleInspector));
+    }
+
+    #[test]
+    fn parses_messages() {
+        assert_eq!(parse("messages"), Ok(Command::ToggleMessages));
+    }
+
+    #[test]
+    fn parses_toolbar() {
+        assert_eq!(parse("toolbar"), Ok(Command::ToggleToolbar));
+    }
+
+    #[test]
+    fn parses_help_with_no_topic() {
+        assert_eq!(parse("help"), Ok(Command::Help(None)));
+    }
+
+    #[test]
+    fn parses_help_with_a_topic() {
+        assert_eq!(
+            parse("help fill"),
+            Ok(Command::Help(Some("fill".to_string())))
+        );
+    }
+
+    #[test]
+    fn help_text_with_no_topic_lists_every_command_name() {
+        assert!(help_text(None)
+            .split(' ')
+            .eq(COMMANDS.iter().map(|c| c.name)));
+    }
+
+    #[test]
+    fn help_text_with_a_known_topic_shows_its_summary() {
+        assert_eq!(
+            help_text(Some("fill")),
+            ":fill - paint every selected pixel (or the whole image) with a solid color"
+        );
+    }
+
+    #[test]
+    fn help_text_with_an_unknown_topic_says_so() {
+        assert_eq!(help_text(Some("bogus")), "no help for \"bogus\"");
+    }
+
+    #[test]
+    fn parses_map_with_no_arguments() {
+        assert_eq!(parse("map"), Ok(Command::Map(None)));
+    }
+
+    #[test]
+    fn parses_map_with_a_key_and_an_action() {
+        assert_eq!(
+            parse("map h left"),
+            Ok(Command::Map(Some(('h', MappableAction::Left))))
+        );
+    }
+
+    #[test]
+    fn map_rejects_a_key_longer_than_one_character() {
+        assert_eq!(
+            parse("map hh left"),
+            Err(CommandError::InvalidArgument("map".to_string()))
+        );
+    }
+
+    #[test]
+    fn map_rejects_an_unknown_action() {
+        assert_eq!(
+            parse("map h bogus"),
+            Err(CommandError::InvalidArgument("bogus".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_unmap() {
+        assert_eq!(parse("unmap h"), Ok(Command::Unmap('h')));
+    }
+
+    #[test]
+    fn parses_autocrop() {
+        assert_eq!(parse("autocrop"), Ok(Command::AutoCrop));
+    }
+
+    #[test]
+    fn parses_pad_with_a_default_transparent_fill() {
+        assert_eq!(
+            parse("pad left 4"),
+            Ok(Command::Pad {
+                edge: Edge::Left,
+                amount: 4,
+                color: [0, 0, 0, 0],
+            })
+        );
+    }
+
+    #[test]
+    fn parses_pad_with_an_explicit_fill_color() {
+        assert_eq!(
+            parse("pad all 2 255 0 0 255"),
+            Ok(Command::Pad {
+                edge: Edge::All,
+                amount: 2,
+                color: [255, 0, 0, 255],
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_pad_with_an_unknown_edge() {
+        assert!(parse("pad sideways 2").is_err());
+    }
+
+    #[test]
+    fn parses_center_with_no_argument_as_both_axes() {
+        assert_eq!(parse("center"), Ok(Command::Center(Axis::Both)));
+    }
+
+    #[test]
+    fn parses_center_with_an_axis() {
+        assert_eq!(parse("center x"), Ok(Command::Center(Axis::X)));
+        assert_eq!(parse("center y"), Ok(Command::Center(Axis::Y)));
+    }
+
+    #[test]
+    fn parses_delrow_and_delcol() {
+        assert_eq!(parse("delrow"), Ok(Command::DeleteRow));
+        assert_eq!(parse("delcol"), Ok(Command::DeleteColumn));
+    }
+
+    #[test]
+    fn parses_insrow_and_inscol() {
+        assert_eq!(parse("insrow"), Ok(Command::InsertRow));
+        assert_eq!(parse("inscol"), Ok(Command::InsertColumn));
+    }
+
+    #[test]
+    fn parses_write_with_optional_path() {
+        assert_eq!(
+            parse("w"),
+            Ok(Command::Write {
+                path: None,
+                force: false
+            })
+        );
+        assert_eq!(
+            parse("w out.png"),
+            Ok(Command::Write {
+                path: Some(PathBuf::from("out.png")),
+                force: false
+            })
+        );
+    }
+
+    #[test]
+    fn parses_write_bang_as_a_forced_overwrite() {
+        assert_eq!(
+            parse("w!"),
+            Ok(Command::Write {
+                path: None,
+                force: true
+            })
+        );
+        assert_eq!(
+            parse("w! out.png"),
+            Ok(Command::Write {
+                path: Some(PathBuf::from("out.png")),
+                force: true
+            })
+        );
+    }
+
+    #[test]
+    fn parses_revert_and_its_forced_aliases() {
+        assert_eq!(parse("revert"), Ok(Command::Revert { force: false }));
+        assert_eq!(parse("revert!"), Ok(Command::Revert { force: true }));
+        assert_eq!(parse("e!"), Ok(Command::Revert { force: true }));
+    }
+
+    #[test]
+    fn parses_write_expanding_tilde_and_env_vars() {
+        std::env::set_var("TIE_TEST_ASSETS", "/assets");
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(
+            parse("w ~/art/sprite.png"),
+            Ok(Command::Write {
+                path: Some(home.join("art/sprite.png")),
+                force: false
+            })
+        );
+        assert_eq!(
+            parse("w $TIE_TEST_ASSETS/x.png"),
+            Ok(Command::Write {
+                path: Some(PathBuf::from("/assets/x.png")),
+                force: false
+            })
+        );
+        assert_eq!(
+            parse("w ${TIE_TEST_ASSETS}/x.png"),
+            Ok(Command::Write {
+                path: Some(PathBuf::from("/assets/x.png")),
+                force: false
+            })
+        );
+
+        std::env::remove_var("TIE_TEST_ASSETS");
+    }
+
+    #[test]
+    fn parses_write_with_a_commit_message() {
+        assert_eq!(
+            parse(r#"w +commit "fix eyes""#),
+            Ok(Command::WriteAndCommit {
+                path: None,
+                force: false,
+                message: "fix eyes".to_string()
+            })
+        );
+        assert_eq!(
+            parse(r#"w! out.png +commit "fix eyes""#),
+            Ok(Command::WriteAndCommit {
+                path: Some(PathBuf::from("out.png")),
+                force: true,
+                message: "fix eyes".to_string()
+            })
+        );
+        assert_eq!(
+            parse(r#"w +commit redraw eyes"#),
+            Ok(Command::WriteAndCommit {
+                path: None,
+                force: false,
+                message: "redraw eyes".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn write_commit_without_a_message_is_an_invalid_argument() {
+        assert_eq!(
+            parse("w +commit"),
+            Err(CommandError::InvalidArgument("w +commit".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_write_leaving_unset_vars_and_tilde_user_paths_untouched() {
+        assert_eq!(
+            parse("w $TIE_TEST_UNSET/x.png"),
+            Ok(Command::Write {
+                path: Some(PathBuf::from("$TIE_TEST_UNSET/x.png")),
+                force: false
+            })
+        );
+        // `~name` (no leading slash) isn't home-dir expansion, so it passes through.
+        assert_eq!(
+            parse("w ~user/x.png"),
+            Ok(Command::Write {
+                path: Some(PathBuf::from("~user/x.png")),
+                force: false
+            })
+        );
+    }
+
+    #[test]
+    fn parses_export_timelapse_with_default_and_explicit_every() {
+        assert_eq!(
+            parse("export timelapse out/"),
+            Ok(Command::ExportTimelapse {
+                dir: PathBuf::from("out/"),
+                every: 1
+            })
+        );
+        assert_eq!(
+            parse("export timelapse out/ every=10"),
+            Ok(Command::ExportTimelapse {
+                dir: PathBuf::from("out/"),
+                every: 10
+            })
+        );
+    }
+
+    #[test]
+    fn parses_export_apng_with_defaults_and_explicit_options() {
+        assert_eq!(
+            parse("export apng out.png"),
+            Ok(Command::ExportApng {
+                path: PathBuf::from("out.png"),
+                every: 1,
+                delay_ms: 100,
+            })
+        );
+        assert_eq!(
+            parse("export apng out.png every=2 delay=250"),
+            Ok(Command::ExportApng {
+                path: PathBuf::from("out.png"),
+                every: 2,
+                delay_ms: 250,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_export_ico_with_multiple_sizes() {
+        assert_eq!(
+            parse("export ico favicon.ico 16,32,48"),
+            Ok(Command::ExportIco {
+                path: PathBuf::from("favicon.ico"),
+                sizes: vec![16, 32, 48]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_export_pico8_gfx() {
+        assert_eq!(
+            parse("export pico8 sprite.txt"),
+            Ok(Command::ExportPico8Gfx(PathBuf::from("sprite.txt")))
+        );
+    }
+
+    #[test]
+    fn parses_export_gb_tiles() {
+        assert_eq!(
+            parse("export gb tiles.2bpp"),
+            Ok(Command::ExportGameBoyTiles(PathBuf::from("tiles.2bpp")))
+        );
+    }
+
+    #[test]
+    fn parses_export_selection() {
+        assert_eq!(
+            parse("export selection out.png"),
+            Ok(Command::ExportSelection(PathBuf::from("out.png")))
+        );
+    }
+
+    #[test]
+    fn parses_palette_use() {
+        assert_eq!(
+            parse("palette use pico8"),
+            Ok(Command::SetPalette(PaletteName::Pico8))
+        );
+        assert_eq!(
+            parse("palette use tic80"),
+            Ok(Command::SetPalette(PaletteName::Tic80))
+        );
+        assert_eq!(
+            parse("palette use none"),
+            Ok(Command::SetPalette(PaletteName::None))
+        );
+    }
+
+    #[test]
+    fn parses_palette_name_and_swap() {
+        assert_eq!(
+            parse("palette name 8 skin"),
+            Ok(Command::PaletteNameCell {
+                index: 8,
+                name: "skin".to_string()
+            })
+        );
+        assert_eq!(
+            parse("palette swap 0 1"),
+            Ok(Command::PaletteSwap { a: 0, b: 1 })
+        );
+    }
+
+    #[test]
+    fn parses_palette_from_file() {
+        assert_eq!(
+            parse("palette from-file other.png"),
+            Ok(Command::PaletteFromFile(PathBuf::from("other.png")))
+        );
+    }
+
+    #[cfg(feature = "network")]
+    #[test]
+    fn parses_palette_lospec() {
+        assert_eq!(
+            parse("palette lospec sweetie-16"),
+            Ok(Command::PaletteLospec("sweetie-16".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_palette_harmonize() {
+        assert_eq!(
+            parse("palette harmonize 0 complementary"),
+            Ok(Command::PaletteHarmonize {
+                seed: 0,
+                harmony: Harmony::Complementary
+            })
+        );
+        assert_eq!(
+            parse("palette harmonize 3 triadic"),
+            Ok(Command::PaletteHarmonize {
+                seed: 3,
+                harmony: Harmony::Triadic
+            })
+        );
+    }
+
+    #[test]
+    fn parses_palette_ramp() {
+        assert_eq!(
+            parse("palette ramp 0 5 6"),
+            Ok(Command::PaletteRamp {
+                start: 0,
+                end: 5,
+                steps: 6
+            })
+        );
+    }
+
+    #[test]
+    fn parses_set_named_color() {
+        assert_eq!(
+            parse("set skin 240 200 180"),
+            Ok(Command::SetNamedColor {
+                name: "skin".to_string(),
+                page: None,
+                color: [240, 200, 180]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_set_named_color_with_a_page() {
+        assert_eq!(
+            parse("set ink@2 240 200 180"),
+            Ok(Command::SetNamedColor {
+                name: "ink".to_string(),
+                page: Some(2),
+                color: [240, 200, 180]
+            })
+        );
+    }
+
+    #[test]
+    fn set_named_color_rejects_a_non_numeric_page() {
+        assert_eq!(
+            parse("set ink@oops 240 200 180"),
+            Err(CommandError::InvalidArgument("ink@oops".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_set_lockpalette() {
+        assert_eq!(
+            parse("set lockpalette on"),
+            Ok(Command::SetLockPalette(true))
+        );
+        assert_eq!(
+            parse("set lockpalette off"),
+            Ok(Command::SetLockPalette(false))
+        );
+        assert!(parse("set lockpalette sideways").is_err());
+    }
+
+    #[test]
+    fn parses_set_pixelperfect() {
+        assert_eq!(
+            parse("set pixelperfect on"),
+            Ok(Command::SetPixelPerfect(true))
+        );
+        assert_eq!(
+            parse("set pixelperfect off"),
+            Ok(Command::SetPixelPerfect(false))
+        );
+        assert!(parse("set pixelperfect sideways").is_err());
+    }
+
+    #[test]
+    fn parses_set_palettemetric() {
+        assert_eq!(
+            parse("set palettemetric rgb"),
+            Ok(Command::SetPaletteMetric(DistanceMetric::Rgb))
+        );
+        assert_eq!(
+            parse("set palettemetric ciede2000"),
+            Ok(Command::SetPaletteMetric(DistanceMetric::Ciede2000))
+        );
+        assert!(parse("set palettemetric sideways").is_err());
+    }
+
+    #[test]
+    fn parses_palette_check() {
+        assert_eq!(parse("palette check"), Ok(Command::PaletteCheck));
+    }
+
+    #[test]
+    fn parses_cursor_add_clear_and_mirror() {
+        assert_eq!(
+            parse("cursor add 3 4"),
+            Ok(Command::CursorAdd(Coord::new(3, 4)))
+        );
+        assert_eq!(parse("cursor clear"), Ok(Command::CursorClear));
+        assert_eq!(parse("cursor mirror 8"), Ok(Command::CursorMirror(8)));
+    }
+
+    #[test]
+    fn parses_mark_and_guide() {
+        assert_eq!(parse("mark a"), Ok(Command::Mark('a')));
+        assert_eq!(
+            parse("guide x 16"),
+            Ok(Command::Guide {
+                axis: Axis::X,
+                position: 16
+            })
+        );
+        assert_eq!(
+            parse("guide y 8"),
+            Ok(Command::Guide {
+                axis: Axis::Y,
+                position: 8
+            })
+        );
+        assert!(parse("guide z 1").is_err());
+        assert!(parse("mark").is_err());
+    }
+
+    #[test]
+    fn parses_select_variants() {
+        assert_eq!(parse("select all"), Ok(Command::SelectAll));
+        assert_eq!(parse("select none"), Ok(Command::SelectNone));
+        assert_eq!(parse("select invert"), Ok(Command::SelectInvert));
+    }
+
+    #[test]
+    fn parses_select_with_a_rectangle() {
+        assert_eq!(
+            parse("select 0 0 15 15"),
+            Ok(Command::SelectRect {
+                x0: 0,
+                y0: 0,
+                x1: 15,
+                y1: 15
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_select_rect_with_a_non_numeric_coordinate() {
+        assert!(parse("select 0 0 15 many").is_err());
+    }
+
+    #[test]
+    fn parses_yank_and_paste() {
+        assert_eq!(parse("yank"), Ok(Command::Yank));
+        assert_eq!(parse("paste"), Ok(Command::Paste));
+    }
+
+    #[test]
+    fn parses_select_wand_contiguous_and_global() {
+        assert_eq!(
+            parse("select wand"),
+            Ok(Command::SelectWand { global: false })
+        );
+        assert_eq!(
+            parse("select wand global"),
+            Ok(Command::SelectWand { global: true })
+        );
+    }
+
+    #[test]
+    fn parses_fill_and_replace() {
+        assert_eq!(parse("fill 1 2 3 4"), Ok(Command::Fill([1, 2, 3, 4])));
+        assert_eq!(
+            parse("replace 1 2 3 4 5 6 7 8"),
+            Ok(Command::Replace {
+                from: [1, 2, 3, 4],
+                to: [5, 6, 7, 8]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_adjust_and_shift() {
+        assert_eq!(parse("adjust -10"), Ok(Command::Adjust(-10)));
+        assert_eq!(parse("shift 1 -2"), Ok(Command::Shift(1, -2)));
+    }
+
+    #[test]
+    fn parses_shiftrow_and_shiftcol() {
+        assert_eq!(parse("shiftrow -1"), Ok(Command::ShiftRow(-1)));
+        assert_eq!(parse("shiftcol 2"), Ok(Command::ShiftColumn(2)));
+    }
+
+    #[test]
+    fn parses_generate_noise() {
+        assert_eq!(
+            parse("generate noise 1 2 3 4 5 6 7 8"),
+            Ok(Command::GenerateNoise {
+                a: [1, 2, 3, 4],
+                b: [5, 6, 7, 8]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_generate_checker() {
+        assert_eq!(
+            parse("generate checker 4 1 2 3 4 5 6 7 8"),
+            Ok(Command::GenerateChecker {
+                size: 4,
+                a: [1, 2, 3, 4],
+                b: [5, 6, 7, 8]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_generate_stripes() {
+        assert_eq!(
+            parse("generate stripes 2 horizontal 1 2 3 4 5 6 7 8"),
+            Ok(Command::GenerateStripes {
+                size: 2,
+                direction: StripeDirection::Horizontal,
+                a: [1, 2, 3, 4],
+                b: [5, 6, 7, 8]
+            })
+        );
+        assert_eq!(
+            parse("generate stripes 2 vertical 1 2 3 4 5 6 7 8"),
+            Ok(Command::GenerateStripes {
+                size: 2,
+                direction: StripeDirection::Vertical,
+                a: [1, 2, 3, 4],
+                b: [5, 6, 7, 8]
+            })
+        );
+        assert!(parse("generate stripes 2 sideways 1 2 3 4 5 6 7 8").is_err());
+    }
+
+    #[test]
+    fn parses_filter_blur_and_sharpen() {
+        assert_eq!(
+            parse("filter blur"),
+            Ok(Command::Filter(crate::filter::BLUR))
+        );
+        assert_eq!(
+            parse("filter sharpen"),
+            Ok(Command::Filter(crate::filter::SHARPEN))
+        );
+    }
+
+    #[test]
+    fn parses_filter_kernel_stripping_quotes_and_deriving_the_divisor() {
+        assert_eq!(
+            parse("filter kernel \"0 -1 0 -1 5 -1 0 -1 0\""),
+            Ok(Command::Filter(Kernel {
+                weights: [0, -1, 0, -1, 5, -1, 0, -1, 0],
+                divisor: 1,
+            }))
+        );
+        assert_eq!(
+            parse("filter kernel 1 1 1 1 1 1 1 1 1"),
+            Ok(Command::Filter(Kernel {
+                weights: [1; 9],
+                divisor: 9,
+            }))
+        );
+    }
+
+    #[test]
+    fn parses_filter_kernel_with_a_zero_sum_without_dividing_by_zero() {
+        assert_eq!(
+            parse("filter kernel 1 0 -1 2 0 -2 1 0 -1"),
+            Ok(Command::Filter(Kernel {
+                weights: [1, 0, -1, 2, 0, -2, 1, 0, -1],
+                divisor: 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn parses_filter_kernel_rejecting_the_wrong_count() {
+        assert!(parse("filter kernel 1 2 3").is_err());
+    }
+
+    #[test]
+    fn parses_filter_scanlines_and_crt() {
+        assert_eq!(
+            parse("filter scanlines 0.8"),
+            Ok(Command::FilterScanlines(80))
+        );
+        assert_eq!(parse("filter crt"), Ok(Command::FilterCrt));
+        assert!(parse("filter scanlines 1.5").is_err());
+    }
+
+    #[test]
+    fn parses_repeat() {
+        assert_eq!(
+            parse("repeat 2 0 5"),
+            Ok(Command::Repeat { dx: 2, dy: 0, n: 5 })
+        );
+    }
+
+    #[test]
+    fn parses_set_checker_and_bgcolor() {
+        assert_eq!(
+            parse("set checker light"),
+            Ok(Command::SetChecker(Checker::Light))
+        );
+        assert_eq!(parse("set bgcolor 1 2 3"), Ok(Command::SetBgColor(1, 2, 3)));
+        assert_eq!(parse("set bgcolor none"), Ok(Command::ClearBgColor));
+    }
+
+    #[test]
+    fn parses_set_colorspace() {
+        assert_eq!(
+            parse("set colorspace raw"),
+            Ok(Command::SetColorSpace(ColorSpace::Raw))
+        );
+        assert_eq!(
+            parse("set colorspace gamma"),
+            Ok(Command::SetColorSpace(ColorSpace::Gamma))
+        );
+    }
+
+    #[test]
+    fn parses_set_preview() {
+        assert_eq!(
+            parse("set preview braille"),
+            Ok(Command::SetPreview(PreviewMode::Braille))
+        );
+        assert_eq!(
+            parse("set preview normal"),
+            Ok(Command::SetPreview(PreviewMode::Normal))
+        );
+    }
+
+    #[test]
+    fn parses_set_pixelwidth() {
+        assert_eq!(parse("set pixelwidth 2"), Ok(Command::SetPixelWidth(2)));
+        assert!(matches!(
+            parse("set pixelwidth 5"),
+            Err(CommandError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            parse("set pixelwidth 0"),
+            Err(CommandError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn parses_set_grid() {
+        assert_eq!(parse("set grid 8"), Ok(Command::SetGridSize(8)));
+        assert!(matches!(
+            parse("set grid 0"),
+            Err(CommandError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn parses_theme_command() {
+        assert_eq!(
+            parse("theme high-contrast"),
+            Ok(Command::SetTheme(ThemeName::HighContrast))
+        );
+    }
+
+    #[test]
+    fn parses_tool_command() {
+        assert_eq!(
+            parse("tool eyedropper"),
+            Ok(Command::SetTool(crate::tool::ToolKind::Eyedropper))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_command() {
+        assert_eq!(
+            parse("frobnicate"),
+            Err(CommandError::Unknown("frobnicate".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_export_timelapse_without_dir() {
+        assert_eq!(
+            parse("export timelapse"),
+            Err(CommandError::MissingPath("export timelapse".to_string()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::parse;
+
+    proptest! {
+        /// However mangled, a command line must parse to a well-defined
+        /// `Command` or `CommandError`, never panic - this is the property a
+        /// fuzz target (`fuzz/fuzz_targets/parse_command.rs`) also checks.
+        #[test]
+        fn parse_never_panics(line in ".*") {
+            let _ = parse(&line);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::colorspace::ColorSpace;
+use crate::i18n::Locale;
+use crate::palette::PaletteName;
+use crate::theme::ThemeName;
+
+/// User configuration loaded from `tie/config.toml` in the platform config
+/// directory. Every field has a sensible default, so a missing or partially
+/// filled-in file is never an error.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub theme: ThemeName,
+    /// UI language. Falls back to `LANG` when unset.
+    pub locale: Option<Locale>,
+    /// Whether `:adjust` works in linear light or on raw sRGB bytes.
+    /// Defaults to gamma-correct.
+    #[serde(default)]
+    pub colorspace: ColorSpace,
+    /// A fixed color palette that paint operations snap to. Defaults to
+    /// unconstrained.
+    #[serde(default)]
+    pub palette: PaletteName,
+    /// Whether `:palette use` is remembered per file and restored the next
+    /// time that file is opened. Defaults to on.
+    #[serde(default = "default_true")]
+    pub remember_palette: bool,
+    /// A shell command run after every successful save, with `{file}`
+    /// replaced by the saved path - e.g. `"oxipng {file}"` to shrink PNGs
+    /// on write. Unset by default; a failure is logged and otherwise
+    /// ignored, since the save itself already succeeded.
+    pub post_save_cmd: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: ThemeName::default(),
+            locale: None,
+            colorspace: ColorSpace::default(),
+            palette: PaletteName::default(),
+            remember_palette: true,
+            post_save_cmd: None,
+        }
+    }
+}
+
+impl Config {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("tie").join("config.toml"))
+    }
+
+    /// Loads the config file if present, falling back to defaults if it is
+    /// missing, unreadable, or fails to parse.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_theme_from_toml() {
+        let config: Config = toml::from_str("theme = \"light\"").unwrap();
+        assert_eq!(config.theme, ThemeName::Light);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.theme, ThemeName::default());
+        assert!(config.remember_palette);
+        assert_eq!(config.post_save_cmd, None);
+    }
+
+    #[test]
+    fn parses_post_save_cmd_from_toml() {
+        let config: Config = toml::from_str(r#"post_save_cmd = "oxipng {file}""#).unwrap();
+        assert_eq!(config.post_save_cmd, Some("oxipng {file}".to_string()));
+    }
+}
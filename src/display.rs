@@ -0,0 +1,125 @@
+/// How transparent cells are shown, since a hard-coded black background
+/// makes dark sprites hard to make out. Terminal cells beyond the image's
+/// edges are not painted by the canvas, so this does not apply to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checker {
+    Dark,
+    Light,
+    Off,
+}
+
+impl Checker {
+    /// The two alternating colors of the checkerboard, or `None` if disabled.
+    pub fn colors(self) -> Option<[(u8, u8, u8); 2]> {
+        match self {
+            Checker::Dark => Some([(32, 32, 32), (48, 48, 48)]),
+            Checker::Light => Some([(200, 200, 200), (220, 220, 220)]),
+            Checker::Off => None,
+        }
+    }
+}
+
+/// How the canvas maps pixels to terminal cells, set via `:set preview`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewMode {
+    /// One pixel per cell, full RGB - the normal editing view.
+    #[default]
+    Normal,
+    /// One cell per 2x4 block of pixels, rendered as a Unicode braille
+    /// pattern dot-per-pixel, for a dense monochrome preview of images too
+    /// large to fit the terminal at 1:1. Cursor, selection, and floating
+    /// previews are not shown in this mode.
+    Braille,
+}
+
+/// User-configurable display of transparency, set via `:set checker` and
+/// `:set bgcolor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplaySettings {
+    pub checker: Checker,
+    /// When set, transparent cells are shown as this solid color instead of
+    /// the checkerboard.
+    pub bgcolor: Option<(u8, u8, u8)>,
+    pub preview: PreviewMode,
+    /// How many character cells wide a single pixel renders, set via
+    /// `:set pixelwidth` to correct for non-square terminal fonts. Ignored
+    /// by [`PreviewMode::Braille`], which has its own fixed dot density.
+    pub pixel_width: u8,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            checker: Checker::Dark,
+            bgcolor: None,
+            preview: PreviewMode::default(),
+            pixel_width: 1,
+        }
+    }
+}
+
+/// Maps a 2x4 grid position `[row][col]` to the bit of a braille cell byte
+/// that the corresponding Unicode dot occupies. Unicode numbers the dots
+/// 1-8 top-to-bottom within each column, left column then right, with dot
+/// `n` living at bit `n - 1`: the layout is not simple raster order.
+pub const BRAILLE_DOT_BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The braille character for a byte of dots packed via [`BRAILLE_DOT_BITS`].
+pub fn braille_char(bits: u8) -> char {
+    char::from_u32(0x2800 + bits as u32).expect("0x2800..=0x28FF is in the braille block")
+}
+
+impl DisplaySettings {
+    /// The color to show at `(x, y)` when that cell is transparent.
+    pub fn background_at(&self, x: u32, y: u32) -> Option<(u8, u8, u8)> {
+        if let Some(color) = self.bgcolor {
+            return Some(color);
+        }
+        let [even, odd] = self.checker.colors()?;
+        Some(if (x + y).is_multiple_of(2) { even } else { odd })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bgcolor_overrides_checker() {
+        let settings = DisplaySettings {
+            checker: Checker::Dark,
+            bgcolor: Some((1, 2, 3)),
+            ..Default::default()
+        };
+        assert_eq!(settings.background_at(0, 0), Some((1, 2, 3)));
+    }
+
+    #[test]
+    fn checker_off_with_no_bgcolor_is_transparent() {
+        let settings = DisplaySettings {
+            checker: Checker::Off,
+            ..Default::default()
+        };
+        assert_eq!(settings.background_at(0, 0), None);
+    }
+
+    #[test]
+    fn checker_alternates_by_cell_parity() {
+        let settings = DisplaySettings {
+            checker: Checker::Dark,
+            ..Default::default()
+        };
+        assert_ne!(settings.background_at(0, 0), settings.background_at(1, 0));
+    }
+
+    #[test]
+    fn braille_bits_pack_dots_in_unicode_dot_order() {
+        // Top-left, top-right, and bottom-right dots on; rest off.
+        assert_eq!(braille_char(0b1000_1001), '\u{2889}');
+    }
+
+    #[test]
+    fn braille_bits_all_off_is_blank_braille_cell() {
+        assert_eq!(braille_char(0), '\u{2800}');
+    }
+}
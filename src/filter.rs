@@ -0,0 +1,199 @@
+//! Filters for `:filter`: 3x3 convolutions (`blur`/`sharpen`/`kernel`), plus
+//! the position-only stylistic effects `scanlines`/`crt`. Kernels use
+//! integer weights plus a divisor (rather than floats) so [`Kernel`] stays
+//! comparable and fits [`crate::command::Command`]'s derived `Eq`; the
+//! stylistic filters take their strength as an integer percent for the
+//! same reason.
+
+use crate::image::{Image, Pixel};
+
+/// A 3x3 convolution kernel, row-major (`weights[0]` is the top-left
+/// neighbor, `weights[4]` the center pixel itself): each output channel is
+/// `sum(weight * neighbor channel) / divisor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kernel {
+    pub weights: [i32; 9],
+    pub divisor: i32,
+}
+
+/// A soft box blur: every neighbor (including the center) contributes
+/// equally.
+pub const BLUR: Kernel = Kernel {
+    weights: [1, 1, 1, 1, 1, 1, 1, 1, 1],
+    divisor: 9,
+};
+
+/// The classic unsharp-mask kernel: boosts the center pixel at its
+/// neighbors' expense.
+pub const SHARPEN: Kernel = Kernel {
+    weights: [0, -1, 0, -1, 5, -1, 0, -1, 0],
+    divisor: 1,
+};
+
+/// Convolves the 3x3 neighborhood around `(x, y)` in `image` with `kernel`,
+/// clamping coordinates that fall outside the image to its edge so border
+/// pixels are still filtered instead of darkening toward black. Alpha is
+/// left untouched - only RGB is blended.
+pub fn convolve(image: &Image, x: u32, y: u32, kernel: Kernel) -> Pixel {
+    let (width, height) = (image.width(), image.height());
+    let mut rgb = [0i64; 3];
+    for (i, &weight) in kernel.weights.iter().enumerate() {
+        let dx = (i % 3) as i64 - 1;
+        let dy = (i / 3) as i64 - 1;
+        let sx = (x as i64 + dx).clamp(0, width as i64 - 1) as u32;
+        let sy = (y as i64 + dy).clamp(0, height as i64 - 1) as u32;
+        let neighbor = image.get_pixel(sx, sy).expect("clamped into bounds");
+        for (channel, sum) in neighbor[..3].iter().zip(rgb.iter_mut()) {
+            *sum += *channel as i64 * weight as i64;
+        }
+    }
+    let divisor = kernel.divisor.max(1) as i64;
+    let [.., a] = image.get_pixel(x, y).expect("in bounds");
+    [
+        ((rgb[0] as f64 / divisor as f64).round() as i64).clamp(0, 255) as u8,
+        ((rgb[1] as f64 / divisor as f64).round() as i64).clamp(0, 255) as u8,
+        ((rgb[2] as f64 / divisor as f64).round() as i64).clamp(0, 255) as u8,
+        a,
+    ]
+}
+
+/// How much [`crt`] darkens alternating rows.
+const CRT_SCANLINE_DARKEN_PERCENT: u8 = 40;
+/// How much [`crt`] dims the two channels each column's phosphor mask
+/// doesn't emphasize.
+const CRT_PHOSPHOR_DARKEN_PERCENT: u8 = 60;
+
+/// Darkens every other row by `darken_percent` (0-100, clamped), emulating
+/// a CRT's visible scan lines. Alpha is left untouched.
+pub fn scanlines(pixel: Pixel, y: u32, darken_percent: u8) -> Pixel {
+    if y.is_multiple_of(2) {
+        pixel
+    } else {
+        darken(pixel, darken_percent)
+    }
+}
+
+/// A simple CRT phosphor-mask effect: [`scanlines`] plus a repeating
+/// red/green/blue subpixel tint across columns, each column's two
+/// unemphasized channels dimmed by [`CRT_PHOSPHOR_DARKEN_PERCENT`].
+pub fn crt(pixel: Pixel, x: u32, y: u32) -> Pixel {
+    let [r, g, b, a] = scanlines(pixel, y, CRT_SCANLINE_DARKEN_PERCENT);
+    let dim = |channel| darken_channel(channel, CRT_PHOSPHOR_DARKEN_PERCENT);
+    let [r, g, b] = match x % 3 {
+        0 => [r, dim(g), dim(b)],
+        1 => [dim(r), g, dim(b)],
+        _ => [dim(r), dim(g), b],
+    };
+    [r, g, b, a]
+}
+
+/// Scales every RGB channel down by `percent` (0-100, clamped), leaving alpha.
+fn darken(pixel: Pixel, percent: u8) -> Pixel {
+    let [r, g, b, a] = pixel;
+    [
+        darken_channel(r, percent),
+        darken_channel(g, percent),
+        darken_channel(b, percent),
+        a,
+    ]
+}
+
+fn darken_channel(channel: u8, percent: u8) -> u8 {
+    let percent = percent.min(100) as u32;
+    (channel as u32 * (100 - percent) / 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blur_of_a_solid_color_is_unchanged() {
+        let mut image = Image::new(3, 3);
+        for y in 0..3 {
+            for x in 0..3 {
+                image.set_pixel(x, y, [100, 150, 200, 255]).unwrap();
+            }
+        }
+        assert_eq!(convolve(&image, 1, 1, BLUR), [100, 150, 200, 255]);
+    }
+
+    #[test]
+    fn blur_averages_a_bright_center_into_its_dark_neighbors() {
+        let mut image = Image::new(3, 3);
+        for y in 0..3 {
+            for x in 0..3 {
+                image.set_pixel(x, y, [0, 0, 0, 255]).unwrap();
+            }
+        }
+        image.set_pixel(1, 1, [255, 255, 255, 255]).unwrap();
+        let [r, ..] = convolve(&image, 1, 1, BLUR);
+        assert_eq!(r, 255 / 9);
+    }
+
+    #[test]
+    fn sharpen_of_a_solid_color_is_unchanged() {
+        let mut image = Image::new(3, 3);
+        for y in 0..3 {
+            for x in 0..3 {
+                image.set_pixel(x, y, [10, 20, 30, 255]).unwrap();
+            }
+        }
+        assert_eq!(convolve(&image, 1, 1, SHARPEN), [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn convolve_clamps_to_the_image_edge_instead_of_sampling_out_of_bounds() {
+        let mut image = Image::new(2, 2);
+        image.set_pixel(0, 0, [10, 0, 0, 255]).unwrap();
+        image.set_pixel(1, 0, [20, 0, 0, 255]).unwrap();
+        image.set_pixel(0, 1, [30, 0, 0, 255]).unwrap();
+        image.set_pixel(1, 1, [40, 0, 0, 255]).unwrap();
+        // No out-of-bounds panic at the corner, and alpha passes through.
+        let pixel = convolve(&image, 0, 0, BLUR);
+        assert_eq!(pixel[3], 255);
+    }
+
+    #[test]
+    fn convolve_preserves_the_center_pixels_alpha() {
+        let mut image = Image::new(1, 1);
+        image.set_pixel(0, 0, [5, 5, 5, 42]).unwrap();
+        assert_eq!(convolve(&image, 0, 0, SHARPEN)[3], 42);
+    }
+
+    #[test]
+    fn scanlines_leaves_even_rows_untouched() {
+        let pixel = [200, 200, 200, 255];
+        assert_eq!(scanlines(pixel, 0, 80), pixel);
+        assert_eq!(scanlines(pixel, 2, 80), pixel);
+    }
+
+    #[test]
+    fn scanlines_darkens_odd_rows_by_the_given_percent() {
+        assert_eq!(scanlines([200, 200, 200, 255], 1, 50), [100, 100, 100, 255]);
+    }
+
+    #[test]
+    fn scanlines_preserves_alpha() {
+        assert_eq!(scanlines([1, 2, 3, 42], 1, 100)[3], 42);
+    }
+
+    #[test]
+    fn crt_emphasizes_a_different_channel_each_column() {
+        let pixel = [200, 200, 200, 255];
+        let [r0, g0, b0, _] = crt(pixel, 0, 0);
+        assert!(r0 > g0 && r0 > b0);
+        let [r1, g1, b1, _] = crt(pixel, 1, 0);
+        assert!(g1 > r1 && g1 > b1);
+        let [r2, g2, b2, _] = crt(pixel, 2, 0);
+        assert!(b2 > r2 && b2 > g2);
+    }
+
+    #[test]
+    fn crt_also_darkens_odd_rows() {
+        let pixel = [200, 200, 200, 255];
+        let lit = crt(pixel, 0, 0);
+        let dimmed = crt(pixel, 0, 1);
+        assert!(dimmed[0] < lit[0]);
+    }
+}
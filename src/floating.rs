@@ -0,0 +1,41 @@
+use crate::image::Image;
+
+/// A region of pixels lifted off the canvas by a grab-and-move operation.
+/// The image it was cut from is kept around so the move can be cancelled
+/// without disturbing undo history.
+pub struct Floating {
+    /// Pixels cut from the selection, as `(original_x, original_y, color)`.
+    /// Only opaque pixels are carried - moving never drags transparency along.
+    pixels: Vec<(i32, i32, [u8; 4])>,
+    /// Offset from each pixel's original position to where it is previewed now.
+    offset: (i32, i32),
+    /// The image as it was immediately after the selected pixels were lifted.
+    base: Image,
+}
+
+impl Floating {
+    pub fn new(base: Image, pixels: Vec<(i32, i32, [u8; 4])>) -> Self {
+        Self {
+            pixels,
+            offset: (0, 0),
+            base,
+        }
+    }
+
+    pub fn base(&self) -> &Image {
+        &self.base
+    }
+
+    pub fn nudge(&mut self, dx: i32, dy: i32) {
+        self.offset.0 += dx;
+        self.offset.1 += dy;
+    }
+
+    /// Pixels at their current, post-move positions: `(x, y, color)`.
+    pub fn preview_pixels(&self) -> impl Iterator<Item = (i32, i32, [u8; 4])> + '_ {
+        let (dx, dy) = self.offset;
+        self.pixels
+            .iter()
+            .map(move |&(x, y, color)| (x + dx, y + dy, color))
+    }
+}
@@ -0,0 +1,105 @@
+//! Pure pixel generators for `:generate noise|checker|stripes`, which fill
+//! the buffer (or active selection, via [`crate::app::App::map_selected`])
+//! procedurally with two configurable colors - quick starting points for
+//! textures instead of painting a whole background by hand.
+
+use crate::image::Pixel;
+
+/// Which axis `:generate stripes`' bands run across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StripeDirection {
+    /// Bands run the full width of the image, stacked along `y`.
+    Horizontal,
+    /// Bands run the full height of the image, stacked along `x`.
+    Vertical,
+}
+
+/// A checkerboard of `size`-by-`size` squares alternating between `a` and `b`.
+pub fn checker(x: u32, y: u32, size: u32, a: Pixel, b: Pixel) -> Pixel {
+    let size = size.max(1);
+    if ((x / size) + (y / size)).is_multiple_of(2) {
+        a
+    } else {
+        b
+    }
+}
+
+/// Bands of `size` pixels alternating between `a` and `b`, running across
+/// `direction`.
+pub fn stripes(x: u32, y: u32, size: u32, direction: StripeDirection, a: Pixel, b: Pixel) -> Pixel {
+    let size = size.max(1);
+    let band = match direction {
+        StripeDirection::Horizontal => y / size,
+        StripeDirection::Vertical => x / size,
+    };
+    if band.is_multiple_of(2) {
+        a
+    } else {
+        b
+    }
+}
+
+/// A deterministic pseudo-random scatter of `a` and `b`, so the same image
+/// size and colors always reproduce the same texture.
+pub fn noise(x: u32, y: u32, a: Pixel, b: Pixel) -> Pixel {
+    if hash(x, y) & 1 == 0 {
+        a
+    } else {
+        b
+    }
+}
+
+/// A cheap position hash - not cryptographic, just enough to scatter
+/// neighboring pixels unpredictably for [`noise`].
+fn hash(x: u32, y: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x9E37_79B1);
+    h ^= y.wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0xC2B2_AE35);
+    h ^ (h >> 13)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: Pixel = [255, 0, 0, 255];
+    const B: Pixel = [0, 0, 255, 255];
+
+    #[test]
+    fn checker_alternates_by_square() {
+        assert_eq!(checker(0, 0, 2, A, B), A);
+        assert_eq!(checker(2, 0, 2, A, B), B);
+        assert_eq!(checker(0, 2, 2, A, B), B);
+        assert_eq!(checker(2, 2, 2, A, B), A);
+    }
+
+    #[test]
+    fn horizontal_stripes_vary_with_y_only() {
+        assert_eq!(stripes(0, 0, 2, StripeDirection::Horizontal, A, B), A);
+        assert_eq!(stripes(5, 0, 2, StripeDirection::Horizontal, A, B), A);
+        assert_eq!(stripes(0, 2, 2, StripeDirection::Horizontal, A, B), B);
+    }
+
+    #[test]
+    fn vertical_stripes_vary_with_x_only() {
+        assert_eq!(stripes(0, 0, 2, StripeDirection::Vertical, A, B), A);
+        assert_eq!(stripes(0, 5, 2, StripeDirection::Vertical, A, B), A);
+        assert_eq!(stripes(2, 0, 2, StripeDirection::Vertical, A, B), B);
+    }
+
+    #[test]
+    fn noise_is_deterministic_for_the_same_position() {
+        assert_eq!(noise(3, 7, A, B), noise(3, 7, A, B));
+    }
+
+    #[test]
+    fn noise_only_ever_produces_the_two_given_colors() {
+        for x in 0..8 {
+            for y in 0..8 {
+                let pixel = noise(x, y, A, B);
+                assert!(pixel == A || pixel == B);
+            }
+        }
+    }
+}
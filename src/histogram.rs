@@ -0,0 +1,119 @@
+//! A `:histogram` overlay: one row per channel (red, green, blue, then
+//! luminance) showing that channel's distribution of values across the
+//! image's opaque pixels, bucketed to fit the available width and drawn
+//! with sub-cell block glyphs - the same "stack detail inside one
+//! character cell" trick [`crate::display::braille_char`] uses for a dense
+//! preview, but with solid bars instead of dots. Useful for judging
+//! exposure and color balance when importing reference images.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::widgets::Widget;
+
+use crate::image::{Image, Pixel};
+
+/// Sub-cell bar heights, from empty to full.
+const LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How many character cells the channel label column takes, before the bars.
+const LABEL_WIDTH: u16 = 2;
+
+/// How many rows [`Histogram`] renders - one per entry in
+/// [`CHANNELS`].
+pub const HEIGHT: u16 = 4;
+
+type Channel = (&'static str, Color, fn(Pixel) -> u8);
+
+const CHANNELS: [Channel; 4] = [
+    ("R", Color::Red, |[r, ..]| r),
+    ("G", Color::Green, |[_, g, ..]| g),
+    ("B", Color::Blue, |[_, _, b, _]| b),
+    ("L", Color::White, luminance),
+];
+
+/// Per-channel value histograms of `image`'s opaque pixels, as a compact
+/// bar chart.
+pub struct Histogram<'a> {
+    image: &'a Image,
+}
+
+impl<'a> Histogram<'a> {
+    pub fn new(image: &'a Image) -> Self {
+        Self { image }
+    }
+}
+
+impl Widget for Histogram<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let bars_width = area.width.saturating_sub(LABEL_WIDTH);
+        for (row, &(label, color, channel)) in CHANNELS.iter().enumerate() {
+            let y = area.y + row as u16;
+            if y >= area.y + area.height {
+                break;
+            }
+            buf.set_string(area.x, y, label, ratatui::style::Style::new().fg(color));
+            let counts = bucket_counts(self.image, bars_width, channel);
+            let max = counts.iter().copied().max().unwrap_or(0).max(1);
+            for (x, &count) in counts.iter().enumerate() {
+                let level = (count as u64 * 8 / max as u64) as usize;
+                let cell = buf.get_mut(area.x + LABEL_WIDTH + x as u16, y);
+                cell.set_char(LEVELS[level]);
+                cell.set_fg(color);
+            }
+        }
+    }
+}
+
+/// Perceptual luminance, matching [`crate::palette_widget`]'s readable-text
+/// check.
+fn luminance([r, g, b, _]: Pixel) -> u8 {
+    (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32).round() as u8
+}
+
+/// Counts `channel`'s values across every opaque pixel in `image`, bucketed
+/// into `width` columns spanning the 0-255 range.
+fn bucket_counts(image: &Image, width: u16, channel: fn(Pixel) -> u8) -> Vec<u32> {
+    let width = width.max(1) as usize;
+    let mut counts = vec![0u32; width];
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            let pixel = image.get_pixel(x, y).expect("(x, y) is in bounds");
+            if pixel[3] == 0 {
+                continue;
+            }
+            let bucket = (channel(pixel) as usize * width) / 256;
+            counts[bucket.min(width - 1)] += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luminance_matches_black_and_white() {
+        assert_eq!(luminance([0, 0, 0, 255]), 0);
+        assert_eq!(luminance([255, 255, 255, 255]), 255);
+    }
+
+    #[test]
+    fn bucket_counts_skips_transparent_pixels() {
+        let mut image = Image::new(2, 1);
+        image.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        image.set_pixel(1, 0, [255, 0, 0, 0]).unwrap();
+        let counts = bucket_counts(&image, 1, |[r, ..]| r);
+        assert_eq!(counts, vec![1]);
+    }
+
+    #[test]
+    fn bucket_counts_spreads_values_across_the_width() {
+        let mut image = Image::new(2, 1);
+        image.set_pixel(0, 0, [0, 0, 0, 255]).unwrap();
+        image.set_pixel(1, 0, [255, 0, 0, 255]).unwrap();
+        let counts = bucket_counts(&image, 2, |[r, ..]| r);
+        assert_eq!(counts, vec![1, 1]);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::image::Image;
+
+/// Tracks the sequence of image states produced while editing, enabling undo/redo
+/// as well as retrospective tooling (e.g. timelapse export) over the whole session.
+pub struct History {
+    /// The states kept for undo/redo, in chronological order. `snapshots[0]`
+    /// is the state the image was opened in; a push after an undo discards
+    /// the undone states.
+    snapshots: Vec<Image>,
+    /// Index into `snapshots` of the state currently shown to the user.
+    cursor: usize,
+}
+
+impl History {
+    pub fn new(initial: Image) -> Self {
+        Self {
+            snapshots: vec![initial],
+            cursor: 0,
+        }
+    }
+
+    /// Records a new state as the result of an edit, discarding any undone states.
+    pub fn push(&mut self, image: Image) {
+        self.snapshots.truncate(self.cursor + 1);
+        self.snapshots.push(image);
+        self.cursor = self.snapshots.len() - 1;
+    }
+
+    pub fn current(&self) -> &Image {
+        &self.snapshots[self.cursor]
+    }
+
+    pub fn undo(&mut self) -> Option<&Image> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        Some(self.current())
+    }
+
+    pub fn redo(&mut self) -> Option<&Image> {
+        if self.cursor + 1 >= self.snapshots.len() {
+            return None;
+        }
+        self.cursor += 1;
+        Some(self.current())
+    }
+
+    /// All states currently kept, in chronological order - undone states stay
+    /// only until the next edit discards them. Used for e.g. timelapse
+    /// export, which replays this history.
+    pub fn snapshots(&self) -> &[Image] {
+        &self.snapshots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_then_undo_restores_previous_state() {
+        let mut history = History::new(Image::new(1, 1));
+        let mut edited = Image::new(1, 1);
+        edited.set_pixel(0, 0, [255, 255, 255, 255]).unwrap();
+        history.push(edited.clone());
+        assert_eq!(history.current(), &edited);
+        assert_eq!(history.undo(), Some(&Image::new(1, 1)));
+    }
+
+    #[test]
+    fn push_after_undo_discards_redo_branch() {
+        let mut history = History::new(Image::new(1, 1));
+        history.push(Image::new(2, 2));
+        history.push(Image::new(3, 3));
+        history.undo();
+        history.push(Image::new(4, 4));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.snapshots().len(), 3);
+    }
+}
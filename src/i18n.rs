@@ -0,0 +1,74 @@
+use serde::Deserialize;
+
+/// UI language. Chosen by the config file, falling back to `LANG`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Locale {
+    /// Guesses the locale from the `LANG` environment variable, defaulting
+    /// to English if it is unset or not recognized.
+    pub fn from_env() -> Self {
+        match std::env::var("LANG") {
+            Ok(lang) if lang.starts_with("ja") => Locale::Ja,
+            _ => Locale::En,
+        }
+    }
+}
+
+/// Message shown when `:w` is used with no path yet known for the image.
+pub fn error_no_path(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => "no file path to write to; use :w <path>",
+        Locale::Ja => "書き込み先のパスが未指定です。:w <path> を使ってください",
+    }
+}
+
+/// Prompt shown while [`crate::app::App::pending_overwrite`] is set, asking
+/// whether to replace an existing file.
+pub fn hint_confirm_overwrite(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => "file exists, overwrite? [y/n]",
+        Locale::Ja => "ファイルが既に存在します。上書きしますか? [y/n]",
+    }
+}
+
+/// Prompt shown while [`crate::app::App::pending_revert`] is set, asking
+/// whether to discard unsaved changes and reload from disk.
+pub fn hint_confirm_revert(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => "discard unsaved changes and reload from disk? [y/n]",
+        Locale::Ja => "未保存の変更を破棄してディスクから再読み込みしますか? [y/n]",
+    }
+}
+
+/// Placeholder used in the Canvas title when the image hasn't been saved
+/// to, or opened from, a file yet.
+pub fn no_name_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => "[No Name]",
+        Locale::Ja => "[無題]",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locales_have_distinct_messages() {
+        assert_ne!(
+            hint_confirm_overwrite(Locale::En),
+            hint_confirm_overwrite(Locale::Ja)
+        );
+        assert_ne!(
+            hint_confirm_revert(Locale::En),
+            hint_confirm_revert(Locale::Ja)
+        );
+        assert_ne!(no_name_label(Locale::En), no_name_label(Locale::Ja));
+    }
+}
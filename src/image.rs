@@ -0,0 +1,1861 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+/// An RGBA pixel as exposed to the editor UI and commands: four 8-bit
+/// channels. Stored internally at [`HighPixel`] precision, so pixels that
+/// started at 16 bits per channel aren't truncated until something actually
+/// edits them through this 8-bit surface.
+pub type Pixel = [u8; 4];
+
+/// The same four RGBA channels at full 16-bit precision, as decoded from or
+/// encoded to a 16-bit PNG.
+pub type HighPixel = [u16; 4];
+
+/// Expands an 8-bit channel to 16 bits by replicating it (`0xAB` -> `0xABAB`),
+/// matching how `image` widens 8-bit sources internally.
+fn widen(pixel: Pixel) -> HighPixel {
+    pixel.map(|c| c as u16 * 257)
+}
+
+/// Narrows a 16-bit channel back to 8 bits for display and editing.
+fn narrow(pixel: HighPixel) -> Pixel {
+    pixel.map(|c| (c >> 8) as u8)
+}
+
+/// Turns an `image`-crate decode failure into [`ImageError::Unsupported`]
+/// when it's classifiable as an unsupported color type or format, so
+/// `Image::load` can suggest a fix instead of a generic decode error.
+fn classify_unsupported(path: &Path, err: image::ImageError) -> ImageError {
+    let kind = match &err {
+        image::ImageError::Unsupported(unsupported) => match unsupported.kind() {
+            image::error::UnsupportedErrorKind::Color(color) => {
+                Some(UnsupportedKind::ColorType(format!("{color:?}")))
+            }
+            image::error::UnsupportedErrorKind::Format(format) => {
+                Some(UnsupportedKind::Format(format.to_string()))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    match kind {
+        Some(kind) => ImageError::Unsupported {
+            path: path.to_path_buf(),
+            kind,
+        },
+        None => ImageError::Decode(err),
+    }
+}
+
+/// Rejects a decoded width/height of 0 before [`Image::load`] or
+/// [`Image::load_aseprite`] builds an [`Image`] from it - a corrupt or
+/// truncated header can make the underlying decoder hand back a degenerate
+/// size instead of erroring itself, and an `Image` with no pixels would
+/// make every cursor/paint path that assumes at least one pixel (e.g.
+/// [`App::export_selection`](crate::app::App::export_selection)) unsound.
+fn check_nonempty(path: &Path, width: u32, height: u32) -> Result<(), ImageError> {
+    if width == 0 || height == 0 {
+        return Err(ImageError::Empty {
+            path: path.to_path_buf(),
+            width,
+            height,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("failed to decode image: {0}")]
+    Decode(#[from] image::ImageError),
+    #[error(
+        "cannot open {}: unsupported {kind} - convert it to a format tie supports first (png, webp, qoi, gif, bmp, ico, ase/aseprite), e.g. with ImageMagick's `convert {} fixed.png`",
+        path.display(),
+        path.display()
+    )]
+    Unsupported {
+        path: std::path::PathBuf,
+        kind: UnsupportedKind,
+    },
+    #[error("failed to read aseprite file: {0}")]
+    Aseprite(#[from] asefile::AsepriteParseError),
+    #[error("Game Boy tiles require dimensions that are multiples of 8, got {width}x{height}")]
+    NotTileAligned { width: u32, height: u32 },
+    #[error("Game Boy tiles support at most 4 colors, but this image uses at least {0}")]
+    TooManyColors(usize),
+    #[error("coordinates ({x}, {y}) are out of bounds for a {width}x{height} image")]
+    OutOfBounds {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    #[error("cannot remove the last row/column of a {width}x{height} image")]
+    TooSmallToShrink { width: u32, height: u32 },
+    #[error("montage needs at least one image")]
+    MontageEmpty,
+    #[error(
+        "montage images must all be the same size; expected {expected_width}x{expected_height}, got {width}x{height} at index {index}"
+    )]
+    MontageSizeMismatch {
+        index: usize,
+        width: u32,
+        height: u32,
+        expected_width: u32,
+        expected_height: u32,
+    },
+    #[error(
+        "cannot split a {width}x{height} image into {tile_width}x{tile_height} tiles; its dimensions must be a multiple of the tile size"
+    )]
+    NotDivisibleByTileSize {
+        width: u32,
+        height: u32,
+        tile_width: u32,
+        tile_height: u32,
+    },
+    #[error("{path}: image has no pixels ({width}x{height}); it may be corrupt")]
+    Empty {
+        path: std::path::PathBuf,
+        width: u32,
+        height: u32,
+    },
+    #[error("an APNG needs at least one frame")]
+    ApngEmpty,
+    #[error(
+        "APNG frames must all be the same size; expected {expected_width}x{expected_height}, got {width}x{height} at frame {index}"
+    )]
+    ApngSizeMismatch {
+        index: usize,
+        width: u32,
+        height: u32,
+        expected_width: u32,
+        expected_height: u32,
+    },
+    #[error("failed to write APNG: {0}")]
+    Apng(#[from] png::EncodingError),
+}
+
+/// What about a source image [`Image::load`] could not handle, as classified
+/// by the underlying `image` crate - used to give format-specific advice
+/// instead of a generic decode error.
+#[derive(Debug)]
+pub enum UnsupportedKind {
+    ColorType(String),
+    Format(String),
+}
+
+impl std::fmt::Display for UnsupportedKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnsupportedKind::ColorType(color) => write!(f, "color type {color}"),
+            UnsupportedKind::Format(format) => write!(f, "format {format}"),
+        }
+    }
+}
+
+/// How many bits per channel an image's pixels were decoded at, and
+/// therefore how it round-trips through disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    Sixteen,
+}
+
+/// Whether an image's pixels carry full color or are constrained to shades
+/// of gray, and therefore whether it round-trips through disk as an RGBA or
+/// a grayscale PNG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    #[default]
+    Rgba,
+    Grayscale,
+}
+
+impl ColorMode {
+    /// Clamps a pixel to what this mode allows - a no-op for `Rgba`, an
+    /// averaging of the RGB channels for `Grayscale`.
+    fn constrain(self, [r, g, b, a]: Pixel) -> Pixel {
+        match self {
+            ColorMode::Rgba => [r, g, b, a],
+            ColorMode::Grayscale => {
+                let luma = ((r as u32 + g as u32 + b as u32) / 3) as u8;
+                [luma, luma, luma, a]
+            }
+        }
+    }
+}
+
+/// Which edge(s) [`Image::padded`] grows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    All,
+}
+
+/// An integer pixel position, carrying its own bounds-checking and
+/// neighbor-offset math so callers juggling cursor, viewport, and
+/// multi-cursor coordinates together don't have to re-derive it ad hoc at
+/// each call site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Coord {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Coord {
+    pub fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    /// Moves by `(dx, dy)`, or `None` if that would cross below zero in
+    /// either axis - callers that also need to clamp to an image's far
+    /// edge can follow up with [`Coord::clamp`].
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Self> {
+        let x = self.x as i32 + dx;
+        let y = self.y as i32 + dy;
+        if x < 0 || y < 0 {
+            return None;
+        }
+        Some(Self::new(x as u32, y as u32))
+    }
+
+    /// Pulls this position back inside a `width` x `height` image, same as
+    /// clamping each axis to its last valid index.
+    pub fn clamp(self, width: u32, height: u32) -> Self {
+        Self::new(
+            self.x.min(width.saturating_sub(1)),
+            self.y.min(height.saturating_sub(1)),
+        )
+    }
+
+    /// Whether this position is inside a `width` x `height` image.
+    pub fn within(self, width: u32, height: u32) -> bool {
+        self.x < width && self.y < height
+    }
+}
+
+impl From<(u32, u32)> for Coord {
+    fn from((x, y): (u32, u32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Coord> for (u32, u32) {
+    fn from(coord: Coord) -> Self {
+        (coord.x, coord.y)
+    }
+}
+
+/// Which axis (or both) [`Image::centered`] re-centers the drawing along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Both,
+}
+
+impl Axis {
+    fn affects_x(self) -> bool {
+        matches!(self, Axis::X | Axis::Both)
+    }
+
+    fn affects_y(self) -> bool {
+        matches!(self, Axis::Y | Axis::Both)
+    }
+}
+
+/// An in-memory raster image, the core data structure edited by tie. Pixels
+/// are always stored at [`HighPixel`] (16-bit) precision; [`ColorMode`] and
+/// [`BitDepth`] only constrain what gets written into them through the
+/// 8-bit editing surface and how the image round-trips through disk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<HighPixel>,
+    color_mode: ColorMode,
+    bit_depth: BitDepth,
+}
+
+/// Pixel dimensions and 8-bit get/set access, pulled out of [`Image`]'s
+/// inherent methods as a trait so a different storage strategy - a tiled
+/// buffer for images too large to hold as one `Vec`, a memory-mapped file,
+/// a buffer shared over the network for collaborative editing - could
+/// stand in for [`Image`] against the same interface. [`Image`] is the
+/// only implementation for now; nothing in [`crate::canvas`] or
+/// [`crate::app`] takes `impl ImageProvider` yet, since there's no second
+/// backend today to justify threading a type parameter through the editor
+/// - this is the seam a future one would implement against.
+pub trait ImageProvider {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    /// Reads a pixel, narrowed to 8 bits per channel for display and editing.
+    fn get_pixel(&self, x: u32, y: u32) -> Result<Pixel, ImageError>;
+    /// Sets a pixel from an 8-bit color.
+    fn set_pixel(&mut self, x: u32, y: u32, color: Pixel) -> Result<(), ImageError>;
+}
+
+impl ImageProvider for Image {
+    fn width(&self) -> u32 {
+        self.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.height()
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Result<Pixel, ImageError> {
+        self.get_pixel(x, y)
+    }
+
+    fn set_pixel(&mut self, x: u32, y: u32, color: Pixel) -> Result<(), ImageError> {
+        self.set_pixel(x, y, color)
+    }
+}
+
+impl Image {
+    /// Creates a new RGBA image of the given size, filled with transparent black.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0, 0, 0, 0]; (width as usize) * (height as usize)],
+            color_mode: ColorMode::default(),
+            bit_depth: BitDepth::default(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    pub fn bit_depth(&self) -> BitDepth {
+        self.bit_depth
+    }
+
+    fn index(&self, x: u32, y: u32) -> Result<usize, ImageError> {
+        if x >= self.width || y >= self.height {
+            return Err(ImageError::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        Ok(y as usize * self.width as usize + x as usize)
+    }
+
+    /// Whether `coord` falls inside this image, e.g. before committing a
+    /// cursor move or an extra-cursor placement computed via [`Coord::offset`].
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.within(self.width, self.height)
+    }
+
+    /// Reads a pixel, narrowed to 8 bits per channel for display and editing.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Result<Pixel, ImageError> {
+        Ok(narrow(self.pixels[self.index(x, y)?]))
+    }
+
+    /// Reads a pixel at full stored precision, e.g. for saving back to disk
+    /// without losing bits a narrower edit never touched.
+    pub fn get_pixel16(&self, x: u32, y: u32) -> Result<HighPixel, ImageError> {
+        Ok(self.pixels[self.index(x, y)?])
+    }
+
+    /// Sets a pixel from an 8-bit color, constraining it to what
+    /// [`ColorMode`] allows - e.g. averaged to gray while editing a
+    /// grayscale image - then widening it to the internal 16-bit precision.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Pixel) -> Result<(), ImageError> {
+        let i = self.index(x, y)?;
+        self.pixels[i] = widen(self.color_mode.constrain(color));
+        Ok(())
+    }
+
+    /// Same as [`Image::get_pixel`], but indexed by a [`Coord`] instead of a
+    /// loose `x, y` pair - for callers (cursor/selection/viewport code) that
+    /// are already carrying positions as `Coord` and would otherwise have
+    /// to destructure and re-pack them just to call into `Image`.
+    pub fn get_pixel_at(&self, coord: Coord) -> Result<Pixel, ImageError> {
+        self.get_pixel(coord.x, coord.y)
+    }
+
+    /// Same as [`Image::set_pixel`], but indexed by a [`Coord`].
+    pub fn set_pixel_at(&mut self, coord: Coord, color: Pixel) -> Result<(), ImageError> {
+        self.set_pixel(coord.x, coord.y, color)
+    }
+
+    /// Replaces every pixel with `f(x, y, pixel)`, same as calling
+    /// [`Image::set_pixel`] in a loop over every coordinate (so `f`'s
+    /// result still passes through [`ColorMode::constrain`]), but built
+    /// with the `parallel` feature, rows are processed concurrently via
+    /// rayon instead of one at a time. This is the hot path for filters
+    /// that touch every pixel - convolution kernels, palette quantization,
+    /// full-image recolor - where a serial pass over a large canvas is the
+    /// most visible source of UI lag.
+    pub fn map_pixels(&mut self, f: impl Fn(u32, u32, Pixel) -> Pixel + Sync) {
+        let width = self.width as usize;
+        if width == 0 {
+            return;
+        }
+        let color_mode = self.color_mode;
+        let apply_row = move |y: usize, row: &mut [HighPixel]| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let next = f(x as u32, y as u32, narrow(*pixel));
+                *pixel = widen(color_mode.constrain(next));
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.pixels
+                .par_chunks_mut(width)
+                .enumerate()
+                .for_each(|(y, row)| apply_row(y, row));
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.pixels
+                .chunks_mut(width)
+                .enumerate()
+                .for_each(|(y, row)| apply_row(y, row));
+        }
+    }
+
+    /// Loads an image from disk. The format is guessed from the path's
+    /// extension (PNG, WebP, QOI, and everything else `image` recognizes),
+    /// plus Aseprite's own `.ase`/`.aseprite` format. Grayscale sources are
+    /// kept in [`ColorMode::Grayscale`] rather than expanded to full color,
+    /// and 16-bit-per-channel sources are kept at full precision rather than
+    /// truncated to 8 bits. Adam7-interlaced PNGs decode transparently - the
+    /// underlying `png` crate always hands back a fully deinterlaced image,
+    /// regardless of how the source file stored its scanlines. An animated
+    /// PNG is handled the same way a multi-frame Aseprite file already is
+    /// (see [`Image::load_aseprite`]): only its first frame is kept, since
+    /// tie has no multi-frame model to load the rest into, and a warning is
+    /// logged when frames are dropped.
+    pub fn load(path: &Path) -> Result<Self, ImageError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ase") | Some("aseprite") => return Self::load_aseprite(path),
+            Some("png") => {
+                if let Some(image) = Self::load_apng_first_frame(path)? {
+                    return Ok(image);
+                }
+            }
+            _ => {}
+        }
+
+        let decoded = image::open(path).map_err(|err| classify_unsupported(path, err))?;
+        let color_mode = match &decoded {
+            image::DynamicImage::ImageLuma8(_)
+            | image::DynamicImage::ImageLuma16(_)
+            | image::DynamicImage::ImageLumaA8(_)
+            | image::DynamicImage::ImageLumaA16(_) => ColorMode::Grayscale,
+            _ => ColorMode::Rgba,
+        };
+        let bit_depth = match &decoded {
+            image::DynamicImage::ImageLuma16(_)
+            | image::DynamicImage::ImageLumaA16(_)
+            | image::DynamicImage::ImageRgb16(_)
+            | image::DynamicImage::ImageRgba16(_) => BitDepth::Sixteen,
+            _ => BitDepth::Eight,
+        };
+        let decoded = decoded.into_rgba16();
+        let width = decoded.width();
+        let height = decoded.height();
+        check_nonempty(path, width, height)?;
+        let pixels = decoded.pixels().map(|p| p.0).collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            color_mode,
+            bit_depth,
+        })
+    }
+
+    /// Reads an Aseprite file, flattening its first frame - every visible
+    /// layer merged, exactly as Aseprite itself would composite it - into an
+    /// 8-bit RGBA image. Animation frames beyond the first, tags, and
+    /// tilemaps are not representable in tie's single-image model and are
+    /// discarded; a warning is logged when frames are dropped.
+    fn load_aseprite(path: &Path) -> Result<Self, ImageError> {
+        let ase = asefile::AsepriteFile::read_file(path)?;
+        if ase.num_frames() > 1 {
+            log::warn!(
+                "{} has {} frames; only the first is loaded",
+                path.display(),
+                ase.num_frames()
+            );
+        }
+        let frame = ase.frame(0).image();
+        let width = frame.width();
+        let height = frame.height();
+        check_nonempty(path, width, height)?;
+        let pixels = frame.pixels().map(|p| widen(p.0)).collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            color_mode: ColorMode::default(),
+            bit_depth: BitDepth::default(),
+        })
+    }
+
+    /// Reads the first frame of an animated PNG, or `None` if `path` isn't
+    /// one - the caller falls back to [`Image::load`]'s regular decode path
+    /// in that case, which handles grayscale and 16-bit PNGs this one
+    /// doesn't (`image`'s APNG frame iterator only ever hands back 8-bit
+    /// RGBA). Mirrors [`Image::load_aseprite`]'s handling of a multi-frame
+    /// source tie can't represent: keep the first frame, warn about the
+    /// rest.
+    fn load_apng_first_frame(path: &Path) -> Result<Option<Self>, ImageError> {
+        use image::AnimationDecoder;
+
+        let file = std::fs::File::open(path).map_err(|e| classify_unsupported(path, e.into()))?;
+        let decoder = image::codecs::png::PngDecoder::new(std::io::BufReader::new(file))
+            .map_err(|err| classify_unsupported(path, err))?;
+        if !decoder.is_apng() {
+            return Ok(None);
+        }
+
+        let mut frames = decoder.apng().into_frames();
+        let first = frames
+            .next()
+            .ok_or(ImageError::Empty {
+                path: path.to_path_buf(),
+                width: 0,
+                height: 0,
+            })?
+            .map_err(|err| classify_unsupported(path, err))?;
+        let remaining = frames.count();
+        if remaining > 0 {
+            log::warn!(
+                "{} has {} frames; only the first is loaded",
+                path.display(),
+                remaining + 1
+            );
+        }
+
+        let buffer = first.into_buffer();
+        let width = buffer.width();
+        let height = buffer.height();
+        check_nonempty(path, width, height)?;
+        let pixels = buffer.pixels().map(|p| widen(p.0)).collect();
+        Ok(Some(Self {
+            width,
+            height,
+            pixels,
+            color_mode: ColorMode::default(),
+            bit_depth: BitDepth::default(),
+        }))
+    }
+
+    /// Saves the image to disk, writing it back at the color mode and bit
+    /// depth it was decoded at rather than always expanding to 8-bit RGBA.
+    /// The format is chosen from the path's extension - `.png` by
+    /// convention, but `.webp` (lossless) and `.qoi` work too, since
+    /// `image`'s encoders dispatch on extension the same way its decoders
+    /// do. Both are 8-bit-RGB(A)-only formats: saving a 16-bit or grayscale
+    /// image to either errors out rather than silently losing precision or
+    /// color. PNGs are always written non-interlaced - neither `image` nor
+    /// the `png` crate it wraps can encode Adam7 passes, and an icon-sized
+    /// image has nothing to gain from progressive loading anyway.
+    pub fn save(&self, path: &Path) -> Result<(), ImageError> {
+        match (self.color_mode, self.bit_depth) {
+            (ColorMode::Rgba, BitDepth::Eight) => {
+                let mut buf = image::RgbaImage::new(self.width, self.height);
+                for (i, pixel) in self.pixels.iter().enumerate() {
+                    let (x, y) = self.coords_of(i);
+                    buf.put_pixel(x, y, image::Rgba(narrow(*pixel)));
+                }
+                buf.save(path)?;
+            }
+            (ColorMode::Rgba, BitDepth::Sixteen) => {
+                let mut buf = image::ImageBuffer::new(self.width, self.height);
+                for (i, pixel) in self.pixels.iter().enumerate() {
+                    let (x, y) = self.coords_of(i);
+                    buf.put_pixel(x, y, image::Rgba(*pixel));
+                }
+                image::DynamicImage::ImageRgba16(buf).save(path)?;
+            }
+            (ColorMode::Grayscale, BitDepth::Eight) => {
+                let mut buf = image::GrayImage::new(self.width, self.height);
+                for (i, pixel) in self.pixels.iter().enumerate() {
+                    let (x, y) = self.coords_of(i);
+                    buf.put_pixel(x, y, image::Luma([narrow(*pixel)[0]]));
+                }
+                buf.save(path)?;
+            }
+            (ColorMode::Grayscale, BitDepth::Sixteen) => {
+                let mut buf = image::ImageBuffer::new(self.width, self.height);
+                for (i, pixel) in self.pixels.iter().enumerate() {
+                    let (x, y) = self.coords_of(i);
+                    buf.put_pixel(x, y, image::Luma([pixel[0]]));
+                }
+                image::DynamicImage::ImageLuma16(buf).save(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn coords_of(&self, index: usize) -> (u32, u32) {
+        let index = index as u32;
+        (index % self.width, index / self.width)
+    }
+
+    /// Writes `frames` as a single animated PNG, each shown for `delay_ms`
+    /// milliseconds - `image`'s own PNG encoder can only write a single
+    /// image, so this talks to the lower-level `png` crate directly to set
+    /// the APNG animation chunks it needs. Always 8-bit RGBA, the only
+    /// pixel format `image`'s APNG *decoder* round-trips losslessly.
+    /// Errors if `frames` is empty, or if they don't all share the same
+    /// dimensions, since APNG frames can't be resized mid-animation here.
+    pub fn save_apng(frames: &[Image], path: &Path, delay_ms: u32) -> Result<(), ImageError> {
+        let first = frames.first().ok_or(ImageError::ApngEmpty)?;
+        let (width, height) = (first.width, first.height);
+        for (index, frame) in frames.iter().enumerate() {
+            if frame.width != width || frame.height != height {
+                return Err(ImageError::ApngSizeMismatch {
+                    index,
+                    width: frame.width,
+                    height: frame.height,
+                    expected_width: width,
+                    expected_height: height,
+                });
+            }
+        }
+
+        let file = std::fs::File::create(path).map_err(image::ImageError::IoError)?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(frames.len() as u32, 0)?;
+        encoder.set_frame_delay(delay_ms.clamp(1, u16::MAX as u32) as u16, 1000)?;
+        let mut writer = encoder.write_header()?;
+        for frame in frames {
+            let data: Vec<u8> = frame.pixels.iter().flat_map(|p| narrow(*p)).collect();
+            writer.write_image_data(&data)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Resamples the image to `width`x`height` using nearest-neighbor, so
+    /// pixel edges stay crisp rather than blurring - the resizing a pixel
+    /// art editor wants, as opposed to a photo editor's smoothing filters.
+    /// The result is always 8-bit RGBA, since that's what icon formats expect.
+    fn resize_nearest(&self, width: u32, height: u32) -> image::RgbaImage {
+        let mut src = image::RgbaImage::new(self.width, self.height);
+        for (i, pixel) in self.pixels.iter().enumerate() {
+            let (x, y) = self.coords_of(i);
+            src.put_pixel(x, y, image::Rgba(narrow(*pixel)));
+        }
+        image::imageops::resize(&src, width, height, image::imageops::FilterType::Nearest)
+    }
+
+    /// Downscales the image to fit within `max_dim` on its longer side,
+    /// preserving aspect ratio and never upscaling - e.g. for a file
+    /// browser's preview panel, where a full-resolution sprite would waste
+    /// space. Always 8-bit RGBA, since that's all a preview needs.
+    pub fn thumbnail(&self, max_dim: u32) -> Image {
+        let max_dim = max_dim.max(1) as f64;
+        let scale = (max_dim / self.width.max(1) as f64)
+            .min(max_dim / self.height.max(1) as f64)
+            .min(1.0);
+        let width = ((self.width as f64 * scale).round() as u32).max(1);
+        let height = ((self.height as f64 * scale).round() as u32).max(1);
+        let buf = self.resize_nearest(width, height);
+        Image {
+            width,
+            height,
+            pixels: buf.pixels().map(|p| widen(p.0)).collect(),
+            color_mode: ColorMode::Rgba,
+            bit_depth: BitDepth::Eight,
+        }
+    }
+
+    /// The smallest `(top, bottom, left, right)` box, inclusive, containing
+    /// every non-transparent pixel - shared by [`Image::autocropped`] and
+    /// [`Image::centered`]. `None` if every pixel is transparent.
+    fn opaque_bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+        let opaque_at = |x: u32, y: u32| self.get_pixel16(x, y).expect("in bounds")[3] != 0;
+        let row_has_content = |y: u32| (0..self.width).any(|x| opaque_at(x, y));
+        let col_has_content = |x: u32| (0..self.height).any(|y| opaque_at(x, y));
+
+        let top = (0..self.height).find(|&y| row_has_content(y))?;
+        let bottom = (0..self.height)
+            .rev()
+            .find(|&y| row_has_content(y))
+            .expect("top exists, so some row has content");
+        let left = (0..self.width)
+            .find(|&x| col_has_content(x))
+            .expect("top exists, so some column has content");
+        let right = (0..self.width)
+            .rev()
+            .find(|&x| col_has_content(x))
+            .expect("top exists, so some column has content");
+        Some((top, bottom, left, right))
+    }
+
+    /// Trims rows and columns that are entirely transparent from every
+    /// edge, shrinking the canvas to fit the opaque drawing - handy when a
+    /// sprite was drawn in the middle of an oversized canvas. Returns the
+    /// image unchanged if every pixel is transparent, rather than cropping
+    /// to nothing.
+    pub fn autocropped(&self) -> Image {
+        let Some((top, bottom, left, right)) = self.opaque_bounding_box() else {
+            return self.clone();
+        };
+
+        let width = right - left + 1;
+        let height = bottom - top + 1;
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in top..=bottom {
+            for x in left..=right {
+                pixels.push(self.get_pixel16(x, y).expect("in bounds"));
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        }
+    }
+
+    /// Translates the opaque drawing so its bounding box is centered on the
+    /// canvas along `axis`, leaving transparent padding where the content
+    /// moved away from and clipping anything pushed past an edge. Returns
+    /// the image unchanged if every pixel is transparent.
+    pub fn centered(&self, axis: Axis) -> Image {
+        let Some((top, bottom, left, right)) = self.opaque_bounding_box() else {
+            return self.clone();
+        };
+
+        let dx = if axis.affects_x() {
+            (self.width as i64 - (right - left + 1) as i64) / 2 - left as i64
+        } else {
+            0
+        };
+        let dy = if axis.affects_y() {
+            (self.height as i64 - (bottom - top + 1) as i64) / 2 - top as i64
+        } else {
+            0
+        };
+
+        let mut pixels = vec![[0u16; 4]; (self.width as usize) * (self.height as usize)];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                if nx >= 0 && nx < self.width as i64 && ny >= 0 && ny < self.height as i64 {
+                    let dst = ny as usize * self.width as usize + nx as usize;
+                    pixels[dst] = self.get_pixel16(x, y).expect("in bounds");
+                }
+            }
+        }
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        }
+    }
+
+    /// Grows the canvas by `amount` pixels along `edge`, filling the new
+    /// pixels with `fill` - handy when a sprite outgrows its original
+    /// bounds. An `amount` of `0` leaves the image unchanged.
+    pub fn padded(&self, edge: Edge, amount: u32, fill: Pixel) -> Image {
+        let (left, right, top, bottom) = match edge {
+            Edge::Left => (amount, 0, 0, 0),
+            Edge::Right => (0, amount, 0, 0),
+            Edge::Top => (0, 0, amount, 0),
+            Edge::Bottom => (0, 0, 0, amount),
+            Edge::All => (amount, amount, amount, amount),
+        };
+        let width = self.width + left + right;
+        let height = self.height + top + bottom;
+        let fill = widen(self.color_mode.constrain(fill));
+        let mut pixels = vec![fill; (width as usize) * (height as usize)];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let dst = (y + top) as usize * width as usize + (x + left) as usize;
+                pixels[dst] = self.get_pixel16(x, y).expect("in bounds");
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        }
+    }
+
+    /// Deletes the row at `y`, shrinking the image's height by one - for
+    /// trimming a stray row out of a sprite sheet. Errors if this is the
+    /// image's only row, since an image can't be zero pixels tall.
+    pub fn without_row(&self, y: u32) -> Result<Image, ImageError> {
+        self.index(0, y)?;
+        if self.height == 1 {
+            return Err(ImageError::TooSmallToShrink {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        let height = self.height - 1;
+        let mut pixels = Vec::with_capacity((self.width * height) as usize);
+        for row in 0..self.height {
+            if row == y {
+                continue;
+            }
+            for x in 0..self.width {
+                pixels.push(self.get_pixel16(x, row).expect("in bounds"));
+            }
+        }
+        Ok(Image {
+            width: self.width,
+            height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        })
+    }
+
+    /// Deletes the column at `x`, shrinking the image's width by one - the
+    /// column counterpart to [`Image::without_row`].
+    pub fn without_column(&self, x: u32) -> Result<Image, ImageError> {
+        self.index(x, 0)?;
+        if self.width == 1 {
+            return Err(ImageError::TooSmallToShrink {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        let width = self.width - 1;
+        let mut pixels = Vec::with_capacity((width * self.height) as usize);
+        for y in 0..self.height {
+            for col in 0..self.width {
+                if col == x {
+                    continue;
+                }
+                pixels.push(self.get_pixel16(col, y).expect("in bounds"));
+            }
+        }
+        Ok(Image {
+            width,
+            height: self.height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        })
+    }
+
+    /// Inserts a blank (transparent) row before `y`, growing the image's
+    /// height by one and shifting `y` and every row after it down - the
+    /// complement of [`Image::without_row`], for opening up a line of
+    /// spacing in a sprite.
+    pub fn with_inserted_row(&self, y: u32) -> Result<Image, ImageError> {
+        self.index(0, y)?;
+        let height = self.height + 1;
+        let mut pixels = Vec::with_capacity((self.width * height) as usize);
+        for row in 0..height {
+            match row.cmp(&y) {
+                std::cmp::Ordering::Less => {
+                    for x in 0..self.width {
+                        pixels.push(self.get_pixel16(x, row).expect("in bounds"));
+                    }
+                }
+                std::cmp::Ordering::Equal => {
+                    pixels.extend(std::iter::repeat_n([0u16; 4], self.width as usize))
+                }
+                std::cmp::Ordering::Greater => {
+                    for x in 0..self.width {
+                        pixels.push(self.get_pixel16(x, row - 1).expect("in bounds"));
+                    }
+                }
+            }
+        }
+        Ok(Image {
+            width: self.width,
+            height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        })
+    }
+
+    /// Inserts a blank column before `x`, growing the image's width by
+    /// one - the column counterpart to [`Image::with_inserted_row`].
+    pub fn with_inserted_column(&self, x: u32) -> Result<Image, ImageError> {
+        self.index(x, 0)?;
+        let width = self.width + 1;
+        let mut pixels = Vec::with_capacity((width * self.height) as usize);
+        for y in 0..self.height {
+            for col in 0..width {
+                match col.cmp(&x) {
+                    std::cmp::Ordering::Less => {
+                        pixels.push(self.get_pixel16(col, y).expect("in bounds"))
+                    }
+                    std::cmp::Ordering::Equal => pixels.push([0u16; 4]),
+                    std::cmp::Ordering::Greater => {
+                        pixels.push(self.get_pixel16(col - 1, y).expect("in bounds"))
+                    }
+                }
+            }
+        }
+        Ok(Image {
+            width,
+            height: self.height,
+            pixels,
+            color_mode: self.color_mode,
+            bit_depth: self.bit_depth,
+        })
+    }
+
+    /// Tiles `images` into a grid `cols` wide (as many rows as needed to
+    /// fit them all), for packing animation frames into one sprite sheet -
+    /// `tie montage`. Errors if `images` is empty, or if they don't all
+    /// share the same dimensions, since a ragged grid wouldn't line up.
+    pub fn montage(images: &[Image], cols: u32) -> Result<Image, ImageError> {
+        let first = images.first().ok_or(ImageError::MontageEmpty)?;
+        let (tile_width, tile_height) = (first.width, first.height);
+        for (index, image) in images.iter().enumerate() {
+            if image.width != tile_width || image.height != tile_height {
+                return Err(ImageError::MontageSizeMismatch {
+                    index,
+                    width: image.width,
+                    height: image.height,
+                    expected_width: tile_width,
+                    expected_height: tile_height,
+                });
+            }
+        }
+
+        let cols = cols.max(1);
+        let rows = (images.len() as u32).div_ceil(cols);
+        let width = tile_width * cols;
+        let height = tile_height * rows;
+        let mut pixels = vec![[0u16; 4]; (width as usize) * (height as usize)];
+        for (i, image) in images.iter().enumerate() {
+            let (col, row) = (i as u32 % cols, i as u32 / cols);
+            let (ox, oy) = (col * tile_width, row * tile_height);
+            for y in 0..tile_height {
+                for x in 0..tile_width {
+                    let dst = (oy + y) as usize * width as usize + (ox + x) as usize;
+                    pixels[dst] = image.get_pixel16(x, y).expect("in bounds");
+                }
+            }
+        }
+        Ok(Image {
+            width,
+            height,
+            pixels,
+            color_mode: first.color_mode,
+            bit_depth: first.bit_depth,
+        })
+    }
+
+    /// Cuts the image into `tile_width`x`tile_height` tiles, reading left
+    /// to right then top to bottom - the inverse of [`Image::montage`], for
+    /// pulling a sprite sheet apart into per-frame images. Errors unless
+    /// both dimensions evenly divide the image's.
+    pub fn split(&self, tile_width: u32, tile_height: u32) -> Result<Vec<Image>, ImageError> {
+        if tile_width == 0
+            || tile_height == 0
+            || !self.width.is_multiple_of(tile_width)
+            || !self.height.is_multiple_of(tile_height)
+        {
+            return Err(ImageError::NotDivisibleByTileSize {
+                width: self.width,
+                height: self.height,
+                tile_width,
+                tile_height,
+            });
+        }
+
+        let cols = self.width / tile_width;
+        let rows = self.height / tile_height;
+        let mut tiles = Vec::with_capacity((cols * rows) as usize);
+        for row in 0..rows {
+            for col in 0..cols {
+                let mut pixels = Vec::with_capacity((tile_width * tile_height) as usize);
+                for y in 0..tile_height {
+                    for x in 0..tile_width {
+                        pixels.push(
+                            self.get_pixel16(col * tile_width + x, row * tile_height + y)
+                                .expect("in bounds"),
+                        );
+                    }
+                }
+                tiles.push(Image {
+                    width: tile_width,
+                    height: tile_height,
+                    pixels,
+                    color_mode: self.color_mode,
+                    bit_depth: self.bit_depth,
+                });
+            }
+        }
+        Ok(tiles)
+    }
+
+    /// Resizes the image by an integer `factor`, multiplying both width and
+    /// height, for upscaling pixel art without blurring. A `factor` of `0`
+    /// is treated as `1`. Always 8-bit RGBA, like [`Image::thumbnail`].
+    pub fn scaled(&self, factor: u32) -> Image {
+        let factor = factor.max(1);
+        let width = self.width * factor;
+        let height = self.height * factor;
+        let buf = self.resize_nearest(width, height);
+        Image {
+            width,
+            height,
+            pixels: buf.pixels().map(|p| widen(p.0)).collect(),
+            color_mode: ColorMode::Rgba,
+            bit_depth: BitDepth::Eight,
+        }
+    }
+
+    /// Writes a multi-resolution ICO/favicon: `sizes` squares resampled from
+    /// this image, e.g. `[16, 32, 48]`.
+    pub fn export_ico(&self, path: &Path, sizes: &[u32]) -> Result<(), ImageError> {
+        let mut frames = Vec::with_capacity(sizes.len());
+        for &size in sizes {
+            let buf = self.resize_nearest(size, size);
+            frames.push(image::codecs::ico::IcoFrame::as_png(
+                buf.as_raw(),
+                size,
+                size,
+                image::ColorType::Rgba8,
+            )?);
+        }
+        let file = std::fs::File::create(path).map_err(image::ImageError::IoError)?;
+        image::codecs::ico::IcoEncoder::new(file).encode_images(&frames)?;
+        Ok(())
+    }
+
+    /// Writes the image as raw Game Boy 2bpp tile data: 16 bytes per 8x8
+    /// tile (low and high bit planes interleaved per row), tiles visited in
+    /// raster order. The image must use at most 4 distinct colors and have
+    /// dimensions that are multiples of 8; colors are assigned palette
+    /// indices 0 (lightest) to 3 (darkest) by total RGB brightness.
+    pub fn export_gameboy_tiles(&self, path: &Path) -> Result<(), ImageError> {
+        if !self.width.is_multiple_of(8) || !self.height.is_multiple_of(8) {
+            return Err(ImageError::NotTileAligned {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        let mut colors: Vec<[u8; 3]> = Vec::new();
+        for pixel in &self.pixels {
+            let [r, g, b, _] = narrow(*pixel);
+            let rgb = [r, g, b];
+            if !colors.contains(&rgb) {
+                colors.push(rgb);
+                if colors.len() > 4 {
+                    return Err(ImageError::TooManyColors(colors.len()));
+                }
+            }
+        }
+        colors.sort_by_key(|&[r, g, b]| std::cmp::Reverse(r as u32 + g as u32 + b as u32));
+
+        let mut out = Vec::new();
+        for tile_y in 0..(self.height / 8) {
+            for tile_x in 0..(self.width / 8) {
+                for row in 0..8 {
+                    let mut low = 0u8;
+                    let mut high = 0u8;
+                    for col in 0..8 {
+                        let [r, g, b, _] = self
+                            .get_pixel(tile_x * 8 + col, tile_y * 8 + row)
+                            .expect("in bounds");
+                        let index = colors.iter().position(|&c| c == [r, g, b]).unwrap() as u8;
+                        let bit = 7 - col;
+                        low |= (index & 1) << bit;
+                        high |= ((index >> 1) & 1) << bit;
+                    }
+                    out.push(low);
+                    out.push(high);
+                }
+            }
+        }
+        std::fs::write(path, out).map_err(image::ImageError::IoError)?;
+        Ok(())
+    }
+
+    /// Lists the distinct 8-bit RGBA colors used in the image, in the order
+    /// they're first encountered - e.g. for `tie info` to report a color
+    /// count and palette for CI asset validation.
+    pub fn palette(&self) -> Vec<Pixel> {
+        let mut colors: Vec<Pixel> = Vec::new();
+        for pixel in &self.pixels {
+            let color = narrow(*pixel);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        colors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coord_offset_rejects_going_below_zero_in_either_axis() {
+        let origin = Coord::new(0, 0);
+        assert_eq!(origin.offset(1, 1), Some(Coord::new(1, 1)));
+        assert_eq!(origin.offset(-1, 0), None);
+        assert_eq!(origin.offset(0, -1), None);
+    }
+
+    #[test]
+    fn coord_clamp_pulls_back_to_the_last_valid_index() {
+        assert_eq!(Coord::new(9, 9).clamp(4, 3), Coord::new(3, 2));
+        assert_eq!(Coord::new(1, 1).clamp(4, 3), Coord::new(1, 1));
+    }
+
+    #[test]
+    fn coord_within_matches_image_contains() {
+        let img = Image::new(4, 3);
+        assert!(Coord::new(3, 2).within(4, 3));
+        assert!(img.contains(Coord::new(3, 2)));
+        assert!(!Coord::new(4, 2).within(4, 3));
+        assert!(!img.contains(Coord::new(0, 3)));
+    }
+
+    #[test]
+    fn new_image_is_transparent() {
+        let img = Image::new(4, 3);
+        assert_eq!(img.width(), 4);
+        assert_eq!(img.height(), 3);
+        assert_eq!(img.get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn set_and_get_pixel_round_trips() {
+        let mut img = Image::new(2, 2);
+        img.set_pixel(1, 1, [255, 0, 0, 255]).unwrap();
+        assert_eq!(img.get_pixel(1, 1).unwrap(), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn set_and_get_pixel_at_a_coord_round_trips() {
+        let mut img = Image::new(2, 2);
+        img.set_pixel_at(Coord::new(1, 1), [255, 0, 0, 255])
+            .unwrap();
+        assert_eq!(
+            img.get_pixel_at(Coord::new(1, 1)).unwrap(),
+            [255, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn get_pixel_at_rejects_an_out_of_bounds_coord() {
+        let img = Image::new(2, 2);
+        assert!(img.get_pixel_at(Coord::new(2, 0)).is_err());
+    }
+
+    #[test]
+    fn map_pixels_applies_the_given_function_to_every_coordinate() {
+        let mut img = Image::new(2, 2);
+        img.map_pixels(|x, y, _| [x as u8, y as u8, 0, 255]);
+        assert_eq!(img.get_pixel(0, 0).unwrap(), [0, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(img.get_pixel(0, 1).unwrap(), [0, 1, 0, 255]);
+        assert_eq!(img.get_pixel(1, 1).unwrap(), [1, 1, 0, 255]);
+    }
+
+    #[test]
+    fn map_pixels_on_a_zero_width_image_does_not_panic() {
+        let mut img = Image::new(0, 3);
+        img.map_pixels(|_, _, pixel| pixel);
+    }
+
+    #[test]
+    fn map_pixels_constrains_through_the_color_mode_like_set_pixel_does() {
+        let mut img = Image::new(1, 1);
+        img.color_mode = ColorMode::Grayscale;
+        img.map_pixels(|_, _, _| [30, 60, 90, 255]);
+        assert_eq!(img.get_pixel(0, 0).unwrap(), [60, 60, 60, 255]);
+    }
+
+    #[test]
+    fn image_is_usable_through_the_image_provider_trait() {
+        fn paint_corner(provider: &mut impl ImageProvider) {
+            let (x, y) = (provider.width() - 1, provider.height() - 1);
+            provider.set_pixel(x, y, [1, 2, 3, 4]).unwrap();
+        }
+
+        let mut img = Image::new(2, 2);
+        paint_corner(&mut img);
+        assert_eq!(img.get_pixel(1, 1).unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn classify_unsupported_reports_the_rejected_color_type() {
+        let inner = image::error::UnsupportedError::from_format_and_kind(
+            image::error::ImageFormatHint::Exact(image::ImageFormat::Png),
+            image::error::UnsupportedErrorKind::Color(image::ColorType::Rgba32F.into()),
+        );
+        let err = classify_unsupported(
+            Path::new("sprite.png"),
+            image::ImageError::Unsupported(inner),
+        );
+        assert!(matches!(
+            err,
+            ImageError::Unsupported {
+                kind: UnsupportedKind::ColorType(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn classify_unsupported_falls_back_to_decode_for_other_errors() {
+        let err = classify_unsupported(
+            Path::new("sprite.png"),
+            image::ImageError::IoError(std::io::Error::new(std::io::ErrorKind::NotFound, "x")),
+        );
+        assert!(matches!(err, ImageError::Decode(_)));
+    }
+
+    #[test]
+    fn check_nonempty_rejects_a_zero_width_or_height() {
+        let path = Path::new("broken.png");
+        assert!(matches!(
+            check_nonempty(path, 0, 4),
+            Err(ImageError::Empty {
+                width: 0,
+                height: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            check_nonempty(path, 4, 0),
+            Err(ImageError::Empty {
+                width: 4,
+                height: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn check_nonempty_accepts_a_one_by_one_image() {
+        assert!(check_nonempty(Path::new("tiny.png"), 1, 1).is_ok());
+    }
+
+    #[test]
+    fn out_of_bounds_access_errors() {
+        let img = Image::new(2, 2);
+        assert!(matches!(
+            img.get_pixel(2, 0),
+            Err(ImageError::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn grayscale_mode_constrains_set_pixels_to_gray() {
+        let mut img = Image::new(1, 1);
+        img.color_mode = ColorMode::Grayscale;
+        img.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        assert_eq!(img.get_pixel(0, 0).unwrap(), [85, 85, 85, 255]);
+    }
+
+    #[test]
+    fn grayscale_png_round_trips_through_load_and_save() {
+        let mut gray = image::GrayImage::new(2, 1);
+        gray.put_pixel(0, 0, image::Luma([10]));
+        gray.put_pixel(1, 0, image::Luma([200]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gray.png");
+        gray.save(&path).unwrap();
+
+        let img = Image::load(&path).unwrap();
+        assert_eq!(img.color_mode(), ColorMode::Grayscale);
+        assert_eq!(img.get_pixel(0, 0).unwrap(), [10, 10, 10, 255]);
+        assert_eq!(img.get_pixel(1, 0).unwrap(), [200, 200, 200, 255]);
+
+        let roundtrip_path = dir.path().join("gray_out.png");
+        img.save(&roundtrip_path).unwrap();
+        let reloaded = Image::load(&roundtrip_path).unwrap();
+        assert_eq!(reloaded.color_mode(), ColorMode::Grayscale);
+        assert_eq!(reloaded.get_pixel(0, 0).unwrap(), [10, 10, 10, 255]);
+    }
+
+    // Adam7-interlaced PNG bytes (PngSuite's `basi2c08.png`, public domain) -
+    // `Image::save` can't produce this layout itself (see its doc comment),
+    // so this is embedded rather than round-tripped like the other PNG
+    // tests, just to prove `Image::load` already deinterlaces it correctly.
+    const INTERLACED_PNG: &[u8] = &[
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 32, 0, 0, 0, 32, 8,
+        2, 0, 0, 1, 139, 31, 221, 53, 0, 0, 0, 4, 103, 65, 77, 65, 0, 1, 134, 160, 49, 232, 150,
+        95, 0, 0, 0, 242, 73, 68, 65, 84, 120, 156, 213, 147, 65, 170, 4, 33, 12, 68, 171, 192,
+        123, 120, 19, 61, 89, 211, 115, 51, 189, 137, 215, 104, 104, 181, 102, 209, 12, 244, 103,
+        90, 248, 89, 100, 49, 161, 22, 98, 124, 86, 136, 145, 146, 128, 227, 18, 37, 125, 214, 160,
+        164, 207, 26, 1, 0, 142, 227, 18, 165, 243, 198, 156, 55, 230, 252, 195, 244, 126, 103,
+        118, 160, 127, 139, 218, 245, 180, 15, 238, 210, 211, 62, 2, 94, 47, 244, 254, 45, 74, 99,
+        225, 161, 109, 145, 24, 11, 115, 109, 139, 196, 88, 84, 197, 109, 149, 24, 99, 244, 167,
+        224, 182, 109, 207, 9, 169, 0, 243, 255, 162, 148, 141, 64, 145, 229, 60, 168, 108, 4, 138,
+        100, 57, 15, 102, 43, 80, 74, 153, 150, 96, 206, 217, 6, 24, 43, 154, 1, 168, 190, 0, 165,
+        100, 4, 166, 237, 225, 130, 177, 34, 59, 64, 37, 227, 44, 77, 227, 67, 4, 99, 69, 118, 128,
+        201, 58, 173, 166, 201, 155, 115, 134, 90, 171, 47, 192, 148, 146, 13, 144, 26, 32, 63, 93,
+        223, 225, 151, 13, 40, 69, 103, 131, 38, 207, 251, 175, 239, 252, 211, 6, 84, 244, 109, 17,
+        155, 228, 121, 63, 130, 115, 135, 252, 13, 24, 157, 91, 196, 214, 154, 60, 35, 212, 90,
+        127, 219, 128, 49, 70, 87, 131, 55, 223, 77, 10, 49, 33, 252, 61, 51, 0, 0, 0, 0, 73, 69,
+        78, 68, 174, 66, 96, 130,
+    ];
+
+    #[test]
+    fn load_deinterlaces_an_adam7_png_transparently() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("interlaced.png");
+        std::fs::write(&path, INTERLACED_PNG).unwrap();
+
+        let img = Image::load(&path).unwrap();
+        assert_eq!((img.width(), img.height()), (32, 32));
+        // Just needs to decode into a full, addressable 32x32 image rather
+        // than erroring or leaving gaps from undecoded passes.
+        assert!(img.get_pixel(31, 31).is_ok());
+    }
+
+    #[test]
+    fn save_apng_rejects_an_empty_frame_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.png");
+        assert!(matches!(
+            Image::save_apng(&[], &path, 100),
+            Err(ImageError::ApngEmpty)
+        ));
+    }
+
+    #[test]
+    fn save_apng_rejects_frames_of_mismatched_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mismatch.png");
+        let frames = [Image::new(2, 2), Image::new(3, 3)];
+        assert!(matches!(
+            Image::save_apng(&frames, &path, 100),
+            Err(ImageError::ApngSizeMismatch { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn save_apng_round_trips_through_load_as_its_first_frame() {
+        let mut first = Image::new(2, 2);
+        first.set_pixel(0, 0, [10, 20, 30, 255]).unwrap();
+        let mut second = Image::new(2, 2);
+        second.set_pixel(0, 0, [200, 100, 50, 255]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anim.png");
+        Image::save_apng(&[first.clone(), second], &path, 100).unwrap();
+
+        let loaded = Image::load(&path).unwrap();
+        assert_eq!(loaded.get_pixel(0, 0).unwrap(), [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn sixteen_bit_png_round_trips_without_losing_precision() {
+        let mut rgba16 = image::ImageBuffer::new(1, 1);
+        rgba16.put_pixel(0, 0, image::Rgba([0x1234, 0x5678, 0x9abc, 0xffff]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hi.png");
+        image::DynamicImage::ImageRgba16(rgba16)
+            .save(&path)
+            .unwrap();
+
+        let img = Image::load(&path).unwrap();
+        assert_eq!(img.bit_depth(), BitDepth::Sixteen);
+        assert_eq!(
+            img.get_pixel16(0, 0).unwrap(),
+            [0x1234, 0x5678, 0x9abc, 0xffff]
+        );
+
+        let roundtrip_path = dir.path().join("hi_out.png");
+        img.save(&roundtrip_path).unwrap();
+        let reloaded = Image::load(&roundtrip_path).unwrap();
+        assert_eq!(reloaded.bit_depth(), BitDepth::Sixteen);
+        assert_eq!(
+            reloaded.get_pixel16(0, 0).unwrap(),
+            [0x1234, 0x5678, 0x9abc, 0xffff]
+        );
+    }
+
+    #[test]
+    fn webp_round_trips_an_rgba_image() {
+        let mut img = Image::new(2, 1);
+        img.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        img.set_pixel(1, 0, [0, 255, 0, 128]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roundtrip.webp");
+        img.save(&path).unwrap();
+
+        let reloaded = Image::load(&path).unwrap();
+        assert_eq!(reloaded.get_pixel(0, 0).unwrap(), [255, 0, 0, 255]);
+        assert_eq!(reloaded.get_pixel(1, 0).unwrap(), [0, 255, 0, 128]);
+    }
+
+    #[test]
+    fn qoi_round_trips_an_rgba_image() {
+        let mut img = Image::new(2, 1);
+        img.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        img.set_pixel(1, 0, [0, 255, 0, 128]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roundtrip.qoi");
+        img.save(&path).unwrap();
+
+        let reloaded = Image::load(&path).unwrap();
+        assert_eq!(reloaded.get_pixel(0, 0).unwrap(), [255, 0, 0, 255]);
+        assert_eq!(reloaded.get_pixel(1, 0).unwrap(), [0, 255, 0, 128]);
+    }
+
+    #[test]
+    fn saving_a_grayscale_image_as_qoi_errors_instead_of_expanding_to_color() {
+        let mut img = Image::new(1, 1);
+        img.color_mode = ColorMode::Grayscale;
+        img.set_pixel(0, 0, [10, 10, 10, 255]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gray.qoi");
+        assert!(img.save(&path).is_err());
+    }
+
+    #[test]
+    fn export_ico_writes_a_frame_per_size() {
+        let mut img = Image::new(4, 4);
+        img.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("favicon.ico");
+        img.export_ico(&path, &[16, 32, 48]).unwrap();
+
+        let decoded = image::open(&path).unwrap();
+        assert_eq!(
+            (
+                image::GenericImageView::width(&decoded),
+                image::GenericImageView::height(&decoded)
+            ),
+            (48, 48)
+        );
+    }
+
+    #[test]
+    fn thumbnail_preserves_aspect_ratio_and_clamps_the_longer_side() {
+        let img = Image::new(32, 16);
+        let thumb = img.thumbnail(8);
+        assert_eq!((thumb.width(), thumb.height()), (8, 4));
+    }
+
+    #[test]
+    fn thumbnail_never_upscales_a_smaller_image() {
+        let img = Image::new(4, 2);
+        let thumb = img.thumbnail(64);
+        assert_eq!((thumb.width(), thumb.height()), (4, 2));
+    }
+
+    #[test]
+    fn autocropped_trims_transparent_padding_to_the_opaque_content() {
+        let mut img = Image::new(5, 5);
+        img.set_pixel(2, 1, [255, 0, 0, 255]).unwrap();
+        img.set_pixel(3, 3, [0, 255, 0, 255]).unwrap();
+        let cropped = img.autocropped();
+        assert_eq!((cropped.width(), cropped.height()), (2, 3));
+        assert_eq!(cropped.get_pixel(0, 0).unwrap(), [255, 0, 0, 255]);
+        assert_eq!(cropped.get_pixel(1, 2).unwrap(), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn autocropped_leaves_a_fully_transparent_image_unchanged() {
+        let img = Image::new(3, 3);
+        let cropped = img.autocropped();
+        assert_eq!((cropped.width(), cropped.height()), (3, 3));
+    }
+
+    #[test]
+    fn autocropped_leaves_an_already_tight_image_unchanged() {
+        let mut img = Image::new(2, 2);
+        for y in 0..2 {
+            for x in 0..2 {
+                img.set_pixel(x, y, [1, 2, 3, 255]).unwrap();
+            }
+        }
+        assert_eq!(img.autocropped(), img);
+    }
+
+    #[test]
+    fn padded_grows_only_the_given_edge() {
+        let img = Image::new(2, 2);
+        let padded = img.padded(Edge::Left, 3, [0, 0, 0, 0]);
+        assert_eq!((padded.width(), padded.height()), (5, 2));
+    }
+
+    #[test]
+    fn padded_all_grows_every_edge_and_keeps_the_original_in_the_middle() {
+        let mut img = Image::new(1, 1);
+        img.set_pixel(0, 0, [9, 8, 7, 255]).unwrap();
+        let padded = img.padded(Edge::All, 1, [1, 2, 3, 255]);
+        assert_eq!((padded.width(), padded.height()), (3, 3));
+        assert_eq!(padded.get_pixel(1, 1).unwrap(), [9, 8, 7, 255]);
+        assert_eq!(padded.get_pixel(0, 0).unwrap(), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn padded_by_zero_leaves_the_image_unchanged() {
+        let mut img = Image::new(2, 2);
+        img.set_pixel(0, 0, [9, 8, 7, 255]).unwrap();
+        assert_eq!(img.padded(Edge::Top, 0, [0, 0, 0, 0]), img);
+    }
+
+    #[test]
+    fn centered_moves_a_corner_dot_to_the_middle_of_the_canvas() {
+        let mut img = Image::new(5, 5);
+        img.set_pixel(0, 0, [9, 8, 7, 255]).unwrap();
+        let centered = img.centered(Axis::Both);
+        assert_eq!(centered.get_pixel(2, 2).unwrap(), [9, 8, 7, 255]);
+        assert_eq!(centered.get_pixel(0, 0).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn centered_on_one_axis_leaves_the_other_untouched() {
+        let mut img = Image::new(5, 5);
+        img.set_pixel(0, 0, [9, 8, 7, 255]).unwrap();
+        let centered = img.centered(Axis::X);
+        assert_eq!(centered.get_pixel(2, 0).unwrap(), [9, 8, 7, 255]);
+    }
+
+    #[test]
+    fn centered_leaves_a_fully_transparent_image_unchanged() {
+        let img = Image::new(3, 3);
+        assert_eq!(img.centered(Axis::Both), img);
+    }
+
+    #[test]
+    fn without_row_shrinks_height_and_shifts_later_rows_up() {
+        let mut img = Image::new(2, 3);
+        img.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        img.set_pixel(0, 1, [2, 0, 0, 255]).unwrap();
+        img.set_pixel(0, 2, [3, 0, 0, 255]).unwrap();
+
+        let result = img.without_row(1).unwrap();
+
+        assert_eq!((result.width, result.height), (2, 2));
+        assert_eq!(result.get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(result.get_pixel(0, 1).unwrap(), [3, 0, 0, 255]);
+    }
+
+    #[test]
+    fn without_row_errors_on_a_one_row_image() {
+        let img = Image::new(2, 1);
+        assert!(matches!(
+            img.without_row(0),
+            Err(ImageError::TooSmallToShrink { .. })
+        ));
+    }
+
+    #[test]
+    fn without_column_shrinks_width_and_shifts_later_columns_left() {
+        let mut img = Image::new(3, 2);
+        img.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        img.set_pixel(1, 0, [2, 0, 0, 255]).unwrap();
+        img.set_pixel(2, 0, [3, 0, 0, 255]).unwrap();
+
+        let result = img.without_column(1).unwrap();
+
+        assert_eq!((result.width, result.height), (2, 2));
+        assert_eq!(result.get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(result.get_pixel(1, 0).unwrap(), [3, 0, 0, 255]);
+    }
+
+    #[test]
+    fn without_column_errors_on_a_one_column_image() {
+        let img = Image::new(1, 2);
+        assert!(matches!(
+            img.without_column(0),
+            Err(ImageError::TooSmallToShrink { .. })
+        ));
+    }
+
+    #[test]
+    fn with_inserted_row_grows_height_and_shifts_later_rows_down() {
+        let mut img = Image::new(2, 2);
+        img.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        img.set_pixel(0, 1, [2, 0, 0, 255]).unwrap();
+
+        let result = img.with_inserted_row(1).unwrap();
+
+        assert_eq!((result.width, result.height), (2, 3));
+        assert_eq!(result.get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(result.get_pixel(0, 1).unwrap(), [0, 0, 0, 0]);
+        assert_eq!(result.get_pixel(0, 2).unwrap(), [2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn with_inserted_row_rejects_an_out_of_bounds_position() {
+        let img = Image::new(2, 2);
+        assert!(img.with_inserted_row(2).is_err());
+    }
+
+    #[test]
+    fn with_inserted_column_grows_width_and_shifts_later_columns_right() {
+        let mut img = Image::new(2, 1);
+        img.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        img.set_pixel(1, 0, [2, 0, 0, 255]).unwrap();
+
+        let result = img.with_inserted_column(1).unwrap();
+
+        assert_eq!((result.width, result.height), (3, 1));
+        assert_eq!(result.get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(result.get_pixel(1, 0).unwrap(), [0, 0, 0, 0]);
+        assert_eq!(result.get_pixel(2, 0).unwrap(), [2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn with_inserted_column_rejects_an_out_of_bounds_position() {
+        let img = Image::new(2, 2);
+        assert!(img.with_inserted_column(2).is_err());
+    }
+
+    #[test]
+    fn montage_tiles_images_into_a_grid_n_columns_wide() {
+        let mut a = Image::new(1, 1);
+        a.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        let mut b = Image::new(1, 1);
+        b.set_pixel(0, 0, [2, 0, 0, 255]).unwrap();
+        let mut c = Image::new(1, 1);
+        c.set_pixel(0, 0, [3, 0, 0, 255]).unwrap();
+
+        let result = Image::montage(&[a, b, c], 2).unwrap();
+
+        assert_eq!((result.width, result.height), (2, 2));
+        assert_eq!(result.get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(result.get_pixel(1, 0).unwrap(), [2, 0, 0, 255]);
+        assert_eq!(result.get_pixel(0, 1).unwrap(), [3, 0, 0, 255]);
+        assert_eq!(
+            result.get_pixel(1, 1).unwrap(),
+            [0, 0, 0, 0],
+            "leftover cell in a ragged last row is transparent"
+        );
+    }
+
+    #[test]
+    fn montage_rejects_images_of_differing_sizes() {
+        let a = Image::new(1, 1);
+        let b = Image::new(2, 2);
+        assert!(matches!(
+            Image::montage(&[a, b], 2),
+            Err(ImageError::MontageSizeMismatch { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn montage_rejects_an_empty_list() {
+        assert!(matches!(
+            Image::montage(&[], 2),
+            Err(ImageError::MontageEmpty)
+        ));
+    }
+
+    #[test]
+    fn split_cuts_a_sheet_into_tiles_left_to_right_then_top_to_bottom() {
+        let mut sheet = Image::new(2, 2);
+        sheet.set_pixel(0, 0, [1, 0, 0, 255]).unwrap();
+        sheet.set_pixel(1, 0, [2, 0, 0, 255]).unwrap();
+        sheet.set_pixel(0, 1, [3, 0, 0, 255]).unwrap();
+        sheet.set_pixel(1, 1, [4, 0, 0, 255]).unwrap();
+
+        let tiles = sheet.split(1, 1).unwrap();
+
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles[0].get_pixel(0, 0).unwrap(), [1, 0, 0, 255]);
+        assert_eq!(tiles[1].get_pixel(0, 0).unwrap(), [2, 0, 0, 255]);
+        assert_eq!(tiles[2].get_pixel(0, 0).unwrap(), [3, 0, 0, 255]);
+        assert_eq!(tiles[3].get_pixel(0, 0).unwrap(), [4, 0, 0, 255]);
+    }
+
+    #[test]
+    fn split_round_trips_through_montage() {
+        let mut sheet = Image::new(4, 2);
+        for x in 0..4 {
+            sheet.set_pixel(x, 0, [x as u8, 0, 0, 255]).unwrap();
+            sheet.set_pixel(x, 1, [x as u8, 1, 0, 255]).unwrap();
+        }
+
+        let tiles = sheet.split(2, 1).unwrap();
+        let rebuilt = Image::montage(&tiles, 2).unwrap();
+
+        for y in 0..2 {
+            for x in 0..4 {
+                assert_eq!(
+                    rebuilt.get_pixel(x, y).unwrap(),
+                    sheet.get_pixel(x, y).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn split_rejects_a_tile_size_that_doesnt_evenly_divide_the_image() {
+        let sheet = Image::new(5, 4);
+        assert!(matches!(
+            sheet.split(2, 2),
+            Err(ImageError::NotDivisibleByTileSize { .. })
+        ));
+    }
+
+    #[test]
+    fn palette_lists_each_distinct_color_once_in_first_seen_order() {
+        let mut img = Image::new(2, 2);
+        img.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+        img.set_pixel(1, 0, [0, 255, 0, 255]).unwrap();
+        img.set_pixel(0, 1, [255, 0, 0, 255]).unwrap();
+        assert_eq!(
+            img.palette(),
+            vec![[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn scaled_multiplies_both_axes_by_the_factor() {
+        let img = Image::new(4, 2);
+        let scaled = img.scaled(3);
+        assert_eq!((scaled.width(), scaled.height()), (12, 6));
+    }
+
+    #[test]
+    fn scaled_treats_a_zero_factor_as_one() {
+        let img = Image::new(4, 2);
+        let scaled = img.scaled(0);
+        assert_eq!((scaled.width(), scaled.height()), (4, 2));
+    }
+
+    #[test]
+    fn export_gameboy_tiles_rejects_dimensions_not_a_multiple_of_8() {
+        let img = Image::new(7, 8);
+        assert!(matches!(
+            img.export_gameboy_tiles(Path::new("/dev/null")),
+            Err(ImageError::NotTileAligned {
+                width: 7,
+                height: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn export_gameboy_tiles_rejects_more_than_four_colors() {
+        let mut img = Image::new(8, 8);
+        for (i, shade) in [0u8, 64, 128, 192, 255].into_iter().enumerate() {
+            img.set_pixel(i as u32, 0, [shade, shade, shade, 255])
+                .unwrap();
+        }
+        assert!(matches!(
+            img.export_gameboy_tiles(Path::new("/dev/null")),
+            Err(ImageError::TooManyColors(5))
+        ));
+    }
+
+    #[test]
+    fn export_gameboy_tiles_packs_a_single_tile_into_sixteen_bytes() {
+        let mut img = Image::new(8, 8);
+        // A single top row alternating black/white should set every other
+        // bit of the low-plane byte for row 0, and nothing in the high plane.
+        for x in 0..8u32 {
+            let shade = if x % 2 == 0 { 0 } else { 255 };
+            img.set_pixel(x, 0, [shade, shade, shade, 255]).unwrap();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tile.2bpp");
+        img.export_gameboy_tiles(&path).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), 16);
+        // With only 2 colors present, indices are 0 (white) and 1 (black),
+        // so every bit lands in the low plane and the high plane stays 0.
+        assert_eq!(bytes[0], 0b1010_1010); // low plane, row 0 (alternating)
+        assert_eq!(bytes[1], 0b0000_0000); // high plane, row 0
+                                           // Rows 1-7 are untouched, default-transparent-black -> solid index 1.
+        for row in 1..8 {
+            assert_eq!(bytes[row * 2], 0b1111_1111, "row {row} low plane");
+            assert_eq!(bytes[row * 2 + 1], 0b0000_0000, "row {row} high plane");
+        }
+    }
+
+    #[test]
+    fn editing_a_sixteen_bit_pixel_narrows_just_that_pixel() {
+        let mut rgba16 = image::ImageBuffer::new(1, 1);
+        rgba16.put_pixel(0, 0, image::Rgba([0x1234, 0x5678, 0x9abc, 0xffff]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hi.png");
+        image::DynamicImage::ImageRgba16(rgba16)
+            .save(&path)
+            .unwrap();
+
+        let mut img = Image::load(&path).unwrap();
+        img.set_pixel(0, 0, [0x12, 0x56, 0x9a, 0xff]).unwrap();
+        assert_eq!(
+            img.get_pixel16(0, 0).unwrap(),
+            [0x1212, 0x5656, 0x9a9a, 0xffff]
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+    use proptest::strategy::Strategy;
+    use tempfile::tempdir;
+
+    use super::Image;
+
+    /// Random images, sized small (including the 1x1 and 1xN edge cases)
+    /// to keep each case's PNG round trip fast.
+    fn arb_image() -> impl Strategy<Value = Image> {
+        (1u32..6, 1u32..6).prop_flat_map(|(width, height)| {
+            let len = (width * height) as usize;
+            proptest::collection::vec(proptest::array::uniform4(any::<u8>()), len).prop_map(
+                move |pixels| {
+                    let mut image = Image::new(width, height);
+                    for (i, color) in pixels.into_iter().enumerate() {
+                        let x = i as u32 % width;
+                        let y = i as u32 / width;
+                        image.set_pixel(x, y, color).unwrap();
+                    }
+                    image
+                },
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn save_then_load_round_trips(image in arb_image()) {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("roundtrip.png");
+            image.save(&path).unwrap();
+            let loaded = Image::load(&path).unwrap();
+            prop_assert_eq!(loaded, image);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! An `:inspect` overlay: a magnified neighborhood of pixels centered on
+//! the cursor, each cell labelled with its exact hex color, for precise
+//! color comparisons without zooming the whole canvas.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::color::Color;
+use crate::image::{Coord, Image};
+
+/// How many pixels out from the cursor the inspected neighborhood extends,
+/// in each direction.
+const RADIUS: i32 = 2;
+
+/// How many character cells one pixel's hex label takes, including the gap
+/// before the next one.
+const CELL_WIDTH: u16 = 8;
+
+/// How many rows [`Inspector`] renders - one per row of the inspected
+/// neighborhood.
+pub const HEIGHT: u16 = (2 * RADIUS + 1) as u16;
+
+/// A magnified view of the pixels around `cursor`, each labelled with its
+/// exact hex color. Out-of-bounds and fully transparent cells are shown as
+/// `-`; the cursor's own cell is highlighted.
+pub struct Inspector<'a> {
+    image: &'a Image,
+    cursor: Coord,
+}
+
+impl<'a> Inspector<'a> {
+    pub fn new(image: &'a Image, cursor: Coord) -> Self {
+        Self { image, cursor }
+    }
+
+    fn label(&self, x: i32, y: i32) -> String {
+        if x < 0 || y < 0 {
+            return "-".to_string();
+        }
+        match self.image.get_pixel(x as u32, y as u32) {
+            Ok(pixel) if pixel[3] > 0 => Color::from(pixel).hex(),
+            _ => "-".to_string(),
+        }
+    }
+}
+
+impl Widget for Inspector<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (cx, cy) = (self.cursor.x as i32, self.cursor.y as i32);
+        for row in -RADIUS..=RADIUS {
+            let y = area.y + (row + RADIUS) as u16;
+            if y >= area.y + area.height {
+                break;
+            }
+            for col in -RADIUS..=RADIUS {
+                let x = area.x + (col + RADIUS) as u16 * CELL_WIDTH;
+                if x + CELL_WIDTH > area.x + area.width {
+                    break;
+                }
+                let label = self.label(cx + col, cy + row);
+                let style = if row == 0 && col == 0 {
+                    Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+                buf.set_string(x, y, &label, style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_reads_the_hex_color_of_an_opaque_pixel() {
+        let mut image = Image::new(2, 2);
+        image.set_pixel(1, 1, [18, 52, 86, 255]).unwrap();
+        let inspector = Inspector::new(&image, Coord::new(1, 1));
+        assert_eq!(inspector.label(1, 1), "#123456");
+    }
+
+    #[test]
+    fn label_is_a_dash_for_transparent_or_out_of_bounds_pixels() {
+        let image = Image::new(2, 2);
+        let inspector = Inspector::new(&image, Coord::new(0, 0));
+        assert_eq!(inspector.label(0, 0), "-");
+        assert_eq!(inspector.label(-1, 0), "-");
+        assert_eq!(inspector.label(5, 5), "-");
+    }
+}
@@ -0,0 +1,485 @@
+//! A single source of truth for which keys do what in each input context,
+//! so the status bar's hint ([`hint_line`]) can't drift out of sync with
+//! what `main.rs` actually dispatches. [`Context`] is narrower than
+//! [`crate::app::Mode`] - "select" isn't its own `Mode`, but having an
+//! active selection changes which bindings are worth showing.
+
+use std::time::{Duration, Instant};
+
+use crate::i18n::Locale;
+
+/// Which input context a hint describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// No selection is active.
+    Normal,
+    /// [`crate::app::App::selection`] is active.
+    Select,
+    /// [`crate::app::Mode::Command`].
+    Command,
+}
+
+/// One key binding's label and what it does, localized to English and
+/// Japanese like the rest of [`crate::i18n`]'s messages.
+pub struct Binding {
+    pub key: &'static str,
+    action_en: &'static str,
+    action_ja: &'static str,
+}
+
+impl Binding {
+    fn action(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => self.action_en,
+            Locale::Ja => self.action_ja,
+        }
+    }
+}
+
+const NORMAL_BINDINGS: &[Binding] = &[
+    Binding {
+        key: ":",
+        action_en: "command",
+        action_ja: "コマンド",
+    },
+    Binding {
+        key: "hjkl",
+        action_en: "move",
+        action_ja: "移動",
+    },
+    Binding {
+        key: "x",
+        action_en: "paint",
+        action_ja: "ペイント",
+    },
+    Binding {
+        key: "p",
+        action_en: "pen",
+        action_ja: "ペン切替",
+    },
+    Binding {
+        key: "u",
+        action_en: "undo",
+        action_ja: "取り消し",
+    },
+    Binding {
+        key: "d d",
+        action_en: "del row",
+        action_ja: "行削除",
+    },
+    Binding {
+        key: "d c",
+        action_en: "del col",
+        action_ja: "列削除",
+    },
+    Binding {
+        key: "i r",
+        action_en: "ins row",
+        action_ja: "行挿入",
+    },
+    Binding {
+        key: "i c",
+        action_en: "ins col",
+        action_ja: "列挿入",
+    },
+];
+
+const SELECT_BINDINGS: &[Binding] = &[
+    Binding {
+        key: ":",
+        action_en: "command",
+        action_ja: "コマンド",
+    },
+    Binding {
+        key: "hjkl",
+        action_en: "move",
+        action_ja: "移動",
+    },
+    Binding {
+        key: "g",
+        action_en: "grab",
+        action_ja: "つかむ",
+    },
+    Binding {
+        key: "w",
+        action_en: "wand select",
+        action_ja: "自動選択",
+    },
+    Binding {
+        key: "y",
+        action_en: "copy color",
+        action_ja: "色をコピー",
+    },
+    Binding {
+        key: "'",
+        action_en: "jump to mark",
+        action_ja: "マークへ移動",
+    },
+];
+
+const COMMAND_BINDINGS: &[Binding] = &[
+    Binding {
+        key: "Enter",
+        action_en: "run",
+        action_ja: "実行",
+    },
+    Binding {
+        key: "Esc",
+        action_en: "cancel",
+        action_ja: "キャンセル",
+    },
+];
+
+/// The bindings shown as the hint bar for `context`.
+pub fn bindings(context: Context) -> &'static [Binding] {
+    match context {
+        Context::Normal => NORMAL_BINDINGS,
+        Context::Select => SELECT_BINDINGS,
+        Context::Command => COMMAND_BINDINGS,
+    }
+}
+
+/// Renders `bindings(context)` as a single status-bar line, e.g.
+/// `": command | hjkl move | x paint | p pen | u undo | d d del row | ..."`.
+pub fn hint_line(context: Context, locale: Locale) -> String {
+    bindings(context)
+        .iter()
+        .map(|binding| format!("{} {}", binding.key, binding.action(locale)))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// An action a key can be bound to via `:map` - deliberately a small,
+/// fixed vocabulary covering the most-used normal-mode actions, rather
+/// than every key binding the editor has (most of those take no argument
+/// and aren't meaningfully "remappable" beyond picking a different key,
+/// which `:map` doesn't need a vocabulary entry to express once it does).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappableAction {
+    Left,
+    Right,
+    Up,
+    Down,
+    Undo,
+    Paint,
+    Pen,
+}
+
+impl MappableAction {
+    /// The name `:map`/`:unmap` read and print this action as, e.g. `:map h
+    /// left`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MappableAction::Left => "left",
+            MappableAction::Right => "right",
+            MappableAction::Up => "up",
+            MappableAction::Down => "down",
+            MappableAction::Undo => "undo",
+            MappableAction::Paint => "paint",
+            MappableAction::Pen => "pen",
+        }
+    }
+
+    /// Parses `:map`'s action argument, e.g. `"left"` - `None` if it isn't
+    /// one of the actions `:map` supports.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(MappableAction::Left),
+            "right" => Some(MappableAction::Right),
+            "up" => Some(MappableAction::Up),
+            "down" => Some(MappableAction::Down),
+            "undo" => Some(MappableAction::Undo),
+            "paint" => Some(MappableAction::Paint),
+            "pen" => Some(MappableAction::Pen),
+            _ => None,
+        }
+    }
+}
+
+/// A single keypress, decoupled from any terminal backend's event type -
+/// `crossterm::event::KeyEvent` is gated behind the `tui` feature, and
+/// this module is part of the always-compiled library surface - so chord
+/// matching stays usable, and testable, without it. `main.rs` is
+/// responsible for translating real key events into this shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyPress {
+    pub char: char,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+/// A multi-key binding: typing `keys` in order, within [`CHORD_TIMEOUT`]
+/// of each other, triggers `action`. A single-key binding with a
+/// modifier (e.g. Ctrl+z) is just a one-element chord.
+pub struct Chord {
+    pub keys: &'static [KeyPress],
+    pub action: &'static str,
+}
+
+/// How long [`KeyInput`] waits for the next key of a chord before giving
+/// up and discarding what's been typed so far.
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(600);
+
+/// What [`KeyInput::feed`] learned from the latest keypress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feed {
+    /// No chord in the table starts with the keys typed so far; the
+    /// caller should fall back to handling this keypress on its own.
+    NoMatch,
+    /// At least one chord starts with the keys typed so far; wait for
+    /// the next keypress instead of acting yet.
+    Pending,
+    /// The keys typed so far exactly match a chord's full sequence.
+    Matched(&'static str),
+}
+
+/// Tracks keys typed toward a multi-key chord (e.g. `Z Z`), so a caller
+/// can tell a lone keypress from the first half of a longer one. Keys
+/// older than [`CHORD_TIMEOUT`] are dropped before the next is added, so
+/// an abandoned chord can't linger and hijack an unrelated keypress.
+#[derive(Debug, Default)]
+pub struct KeyInput {
+    pending: Vec<KeyPress>,
+    last_at: Option<Instant>,
+}
+
+impl KeyInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds `key`, matching it against `chords`. `now` is taken as a
+    /// parameter (rather than read internally) so tests can simulate a
+    /// timeout without actually sleeping.
+    pub fn feed(&mut self, key: KeyPress, chords: &[Chord], now: Instant) -> Feed {
+        if let Some(last) = self.last_at {
+            if now.duration_since(last) > CHORD_TIMEOUT {
+                self.pending.clear();
+            }
+        }
+        self.pending.push(key);
+        self.last_at = Some(now);
+
+        if let Some(chord) = chords.iter().find(|c| c.keys == self.pending.as_slice()) {
+            self.pending.clear();
+            return Feed::Matched(chord.action);
+        }
+        if chords.iter().any(|c| c.keys.starts_with(&self.pending)) {
+            Feed::Pending
+        } else {
+            self.pending.clear();
+            Feed::NoMatch
+        }
+    }
+}
+
+/// Consecutive-press counts at which [`RepeatAccelerator::step`] bumps the
+/// movement distance: 3 repeats of the same direction key ramps to 2
+/// cells per press, 6 repeats to 4.
+pub const REPEAT_THRESHOLDS: [(u32, u32); 2] = [(3, 2), (6, 4)];
+
+/// How soon the next press of the same direction key must follow the last
+/// one to count as the same held streak, rather than starting over at 1
+/// cell. Past this, [`RepeatAccelerator::step`] treats it as a fresh tap.
+pub const REPEAT_WINDOW: Duration = Duration::from_millis(150);
+
+/// Tracks repeated presses of the same movement key, so a caller can ramp
+/// up how many cells a press moves the cursor by the longer a direction
+/// is held - 1 cell for a single tap, accelerating per [`REPEAT_THRESHOLDS`]
+/// as the same key keeps firing within [`REPEAT_WINDOW`] of the last press.
+/// A different key, or a pause longer than the window, resets the streak.
+#[derive(Debug, Default)]
+pub struct RepeatAccelerator {
+    key: Option<char>,
+    streak: u32,
+    last_at: Option<Instant>,
+}
+
+impl RepeatAccelerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a press of `key` at `now` and returns how many cells this
+    /// press should move the cursor.
+    pub fn step(&mut self, key: char, now: Instant) -> u32 {
+        let continues = self.key == Some(key)
+            && self
+                .last_at
+                .is_some_and(|last| now.duration_since(last) <= REPEAT_WINDOW);
+        self.streak = if continues { self.streak + 1 } else { 0 };
+        self.key = Some(key);
+        self.last_at = Some(now);
+
+        REPEAT_THRESHOLDS
+            .iter()
+            .rev()
+            .find(|&&(count, _)| self.streak >= count)
+            .map_or(1, |&(_, step)| step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Z: KeyPress = KeyPress {
+        char: 'Z',
+        ctrl: false,
+        alt: false,
+    };
+    const TEST_CHORDS: &[Chord] = &[
+        Chord {
+            keys: &[Z, Z],
+            action: "quit",
+        },
+        Chord {
+            keys: &[KeyPress {
+                char: 'z',
+                ctrl: true,
+                alt: false,
+            }],
+            action: "undo",
+        },
+    ];
+
+    #[test]
+    fn feed_matches_a_two_key_chord_typed_within_the_timeout() {
+        let mut input = KeyInput::new();
+        let t0 = Instant::now();
+        assert_eq!(input.feed(Z, TEST_CHORDS, t0), Feed::Pending);
+        assert_eq!(
+            input.feed(Z, TEST_CHORDS, t0 + Duration::from_millis(100)),
+            Feed::Matched("quit")
+        );
+    }
+
+    #[test]
+    fn feed_matches_a_modifier_chord_in_one_keypress() {
+        let mut input = KeyInput::new();
+        let ctrl_z = KeyPress {
+            char: 'z',
+            ctrl: true,
+            alt: false,
+        };
+        assert_eq!(
+            input.feed(ctrl_z, TEST_CHORDS, Instant::now()),
+            Feed::Matched("undo")
+        );
+    }
+
+    #[test]
+    fn feed_drops_a_pending_key_once_the_timeout_elapses() {
+        let mut input = KeyInput::new();
+        let t0 = Instant::now();
+        assert_eq!(input.feed(Z, TEST_CHORDS, t0), Feed::Pending);
+        // The second `Z` arrives too late, so it starts a fresh chord
+        // instead of completing the first one.
+        assert_eq!(
+            input.feed(
+                Z,
+                TEST_CHORDS,
+                t0 + CHORD_TIMEOUT + Duration::from_millis(1)
+            ),
+            Feed::Pending
+        );
+    }
+
+    #[test]
+    fn feed_reports_no_match_for_a_key_that_starts_no_chord() {
+        let mut input = KeyInput::new();
+        let x = KeyPress {
+            char: 'x',
+            ctrl: false,
+            alt: false,
+        };
+        assert_eq!(input.feed(x, TEST_CHORDS, Instant::now()), Feed::NoMatch);
+    }
+
+    #[test]
+    fn repeat_accelerator_starts_at_one_cell_per_press() {
+        let mut accel = RepeatAccelerator::new();
+        assert_eq!(accel.step('h', Instant::now()), 1);
+    }
+
+    #[test]
+    fn repeat_accelerator_ramps_up_the_longer_the_same_key_is_held() {
+        let mut accel = RepeatAccelerator::new();
+        let t0 = Instant::now();
+        let mut steps = Vec::new();
+        for i in 0..7 {
+            steps.push(accel.step('h', t0 + i * Duration::from_millis(50)));
+        }
+        assert_eq!(steps, vec![1, 1, 1, 2, 2, 2, 4]);
+    }
+
+    #[test]
+    fn repeat_accelerator_resets_when_the_direction_key_changes() {
+        let mut accel = RepeatAccelerator::new();
+        let t0 = Instant::now();
+        for i in 0..4 {
+            accel.step('h', t0 + i * Duration::from_millis(50));
+        }
+        assert_eq!(accel.step('l', t0 + Duration::from_millis(200)), 1);
+    }
+
+    #[test]
+    fn repeat_accelerator_resets_after_a_pause_longer_than_the_window() {
+        let mut accel = RepeatAccelerator::new();
+        let t0 = Instant::now();
+        for i in 0..4 {
+            accel.step('h', t0 + i * Duration::from_millis(50));
+        }
+        assert_eq!(
+            accel.step('h', t0 + Duration::from_millis(150) + REPEAT_WINDOW * 2),
+            1
+        );
+    }
+
+    #[test]
+    fn hint_line_starts_with_the_command_binding_in_every_context() {
+        for context in [Context::Normal, Context::Select, Context::Command] {
+            let hint = hint_line(context, Locale::En);
+            assert!(
+                hint.contains("command") || hint.contains("run"),
+                "expected a way to act on input in the hint: {hint}"
+            );
+        }
+    }
+
+    #[test]
+    fn select_and_normal_hints_differ() {
+        assert_ne!(
+            hint_line(Context::Normal, Locale::En),
+            hint_line(Context::Select, Locale::En)
+        );
+    }
+
+    #[test]
+    fn mappable_action_name_round_trips_through_parse() {
+        for action in [
+            MappableAction::Left,
+            MappableAction::Right,
+            MappableAction::Up,
+            MappableAction::Down,
+            MappableAction::Undo,
+            MappableAction::Paint,
+            MappableAction::Pen,
+        ] {
+            assert_eq!(MappableAction::parse(action.name()), Some(action));
+        }
+    }
+
+    #[test]
+    fn mappable_action_parse_rejects_an_unknown_name() {
+        assert_eq!(MappableAction::parse("bogus"), None);
+    }
+
+    #[test]
+    fn locales_produce_distinct_hints() {
+        assert_ne!(
+            hint_line(Context::Normal, Locale::En),
+            hint_line(Context::Normal, Locale::Ja)
+        );
+    }
+}
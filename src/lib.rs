@@ -0,0 +1,43 @@
+//! Library API for `tie`.
+//!
+//! The TUI binary is a thin shell over this crate: everything that touches
+//! pixels, selections, undo history, or the command language lives here and
+//! has no dependency on a real terminal. Other Rust projects (build scripts,
+//! asset pipelines, tests) can depend on this crate to script the same
+//! operations headlessly via [`Editor`], without pulling in `crossterm`.
+
+pub mod app;
+pub mod canvas;
+pub mod color;
+pub mod colorspace;
+pub mod command;
+pub mod config;
+pub mod crash;
+pub mod display;
+pub mod filter;
+pub mod floating;
+pub mod generate;
+pub mod histogram;
+pub mod history;
+pub mod i18n;
+pub mod image;
+pub mod inspector;
+pub mod keyconfig;
+pub mod logging;
+#[cfg(feature = "network")]
+pub mod lospec;
+pub mod messages;
+pub mod palette;
+pub mod palette_state;
+pub mod palette_widget;
+pub mod selection;
+pub mod session;
+pub mod theme;
+pub mod tool;
+pub mod toolbar;
+pub mod ui;
+
+pub use app::{App as Editor, AppError, CommandOutcome};
+pub use command::Command;
+pub use image::Image;
+pub use ui::Renderer;
@@ -0,0 +1,97 @@
+//! A file-backed logger for diagnosing issues without writing to stderr,
+//! which would corrupt the TUI's alternate screen. Installed once at
+//! startup via [`init`]; everything else just uses the `log` crate's
+//! `info!`/`warn!`/etc. macros as normal.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LoggingError {
+    #[error("failed to open log file {0}: {1}")]
+    OpenFile(PathBuf, std::io::Error),
+    #[error("a logger is already installed")]
+    AlreadyInstalled(#[from] log::SetLoggerError),
+}
+
+struct FileLogger {
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        let _ = writeln!(
+            file,
+            "[{}] {}: {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Installs a logger that appends to `path` at `level`, per `--log-file`
+/// and `--log-level`. Until this is called, `log`'s macros are no-ops.
+pub fn init(path: &Path, level: LevelFilter) -> Result<(), LoggingError> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| LoggingError::OpenFile(path.to_path_buf(), e))?;
+    log::set_boxed_logger(Box::new(FileLogger {
+        file: Mutex::new(file),
+    }))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    #[test]
+    fn init_writes_records_to_the_log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tie.log");
+        // `log` only allows one global logger per process, so this can't
+        // assert via `log::info!` without risking cross-test interference;
+        // it drives the `Log` impl directly instead.
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .unwrap();
+        let logger = FileLogger {
+            file: Mutex::new(file),
+        };
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("tie::test")
+                .args(format_args!("hello"))
+                .build(),
+        );
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("WARN"));
+        assert!(contents.contains("hello"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Fetches named palettes from the Lospec palette list, for `:palette
+//! lospec <slug>` to pull in a community palette without leaving the
+//! editor. Gated behind the `network` feature, so the default build has no
+//! HTTP dependency. Successful fetches are cached under the platform cache
+//! directory (alongside [`crate::crash`]'s crash reports), so a later
+//! fetch can fall back to the last known copy if the network is down.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Why a Lospec palette lookup failed.
+#[derive(Debug, Error)]
+pub enum LospecError {
+    #[error("failed to fetch palette '{0}': {1}")]
+    Request(String, Box<ureq::Error>),
+    #[error("failed to parse palette '{0}': {1}")]
+    Parse(String, std::io::Error),
+    #[error("palette '{0}' has no colors")]
+    Empty(String),
+}
+
+/// The subset of Lospec's JSON palette export this needs: a flat list of
+/// hex colors, without the leading `#`.
+#[derive(Debug, Deserialize)]
+struct LospecResponse {
+    colors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Cached {
+    colors: Vec<[u8; 3]>,
+}
+
+/// Fetches `slug`'s palette from the Lospec API and caches it locally, or
+/// falls back to a previously cached copy of `slug` if the request fails.
+pub fn fetch(slug: &str) -> Result<Vec<[u8; 3]>, LospecError> {
+    match fetch_remote(slug) {
+        Ok(colors) => {
+            cache_write(slug, &colors);
+            Ok(colors)
+        }
+        Err(err) => cache_read(slug).ok_or(err),
+    }
+}
+
+fn fetch_remote(slug: &str) -> Result<Vec<[u8; 3]>, LospecError> {
+    let url = format!("https://lospec.com/palette-list/{slug}.json");
+    let response: LospecResponse = ureq::get(&url)
+        .call()
+        .map_err(|err| LospecError::Request(slug.to_string(), Box::new(err)))?
+        .into_json()
+        .map_err(|err| LospecError::Parse(slug.to_string(), err))?;
+    let colors: Vec<[u8; 3]> = response
+        .colors
+        .iter()
+        .filter_map(|hex| parse_hex(hex))
+        .collect();
+    if colors.is_empty() {
+        return Err(LospecError::Empty(slug.to_string()));
+    }
+    Ok(colors)
+}
+
+/// Parses a `RRGGBB` hex color, with or without a leading `#`.
+fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some([r, g, b])
+}
+
+fn cache_path(slug: &str) -> Option<PathBuf> {
+    Some(
+        dirs::cache_dir()?
+            .join("tie")
+            .join("lospec")
+            .join(format!("{slug}.toml")),
+    )
+}
+
+/// Caches `colors` for `slug`, best-effort - a failure to write here only
+/// means the next offline fetch won't have a fallback, not data loss.
+fn cache_write(slug: &str, colors: &[[u8; 3]]) {
+    let Some(path) = cache_path(slug) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let cached = Cached {
+        colors: colors.to_vec(),
+    };
+    if let Ok(contents) = toml::to_string(&cached) {
+        let _ = std::fs::write(path, contents);
+    }
+}
+
+fn cache_read(slug: &str) -> Option<Vec<[u8; 3]>> {
+    let contents = std::fs::read_to_string(cache_path(slug)?).ok()?;
+    let cached: Cached = toml::from_str(&contents).ok()?;
+    Some(cached.colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_accepts_a_leading_hash() {
+        assert_eq!(parse_hex("#ff00aa"), Some([0xff, 0x00, 0xaa]));
+        assert_eq!(parse_hex("ff00aa"), Some([0xff, 0x00, 0xaa]));
+    }
+
+    #[test]
+    fn parse_hex_rejects_the_wrong_length() {
+        assert_eq!(parse_hex("fff"), None);
+    }
+
+    #[test]
+    fn cached_colors_round_trip_through_toml() {
+        let cached = Cached {
+            colors: vec![[1, 2, 3], [4, 5, 6]],
+        };
+        let toml = toml::to_string(&cached).unwrap();
+        let reloaded: Cached = toml::from_str(&toml).unwrap();
+        assert_eq!(reloaded.colors, cached.colors);
+    }
+}
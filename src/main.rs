@@ -0,0 +1,951 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use clap::{Parser, ValueEnum};
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers,
+};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::ExecutableCommand;
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+
+use tie::app::{App, Direction, Focus, Mode, DRAG_STEPS};
+use tie::image::Image as TieImage;
+use tie::keyconfig::{Chord, Feed, KeyInput, KeyPress, MappableAction, RepeatAccelerator};
+use tie::tool::ToolKind;
+use tie::Renderer;
+
+/// Multi-key and modifier bindings recognized in [`Mode::Normal`], on top
+/// of the single-key bindings matched below. Checked first, via
+/// [`KeyInput::feed`], so a chord's keys don't also fire as individual
+/// ones - e.g. the first `Z` of `Z Z` waits for its partner instead of
+/// falling through immediately.
+const NORMAL_CHORDS: &[Chord] = &[
+    Chord {
+        keys: &[
+            KeyPress {
+                char: 'Z',
+                ctrl: false,
+                alt: false,
+            },
+            KeyPress {
+                char: 'Z',
+                ctrl: false,
+                alt: false,
+            },
+        ],
+        action: "quit",
+    },
+    Chord {
+        keys: &[KeyPress {
+            char: 'z',
+            ctrl: true,
+            alt: false,
+        }],
+        action: "undo",
+    },
+    Chord {
+        keys: &[
+            KeyPress {
+                char: 'd',
+                ctrl: false,
+                alt: false,
+            },
+            KeyPress {
+                char: 'd',
+                ctrl: false,
+                alt: false,
+            },
+        ],
+        action: "delete_row",
+    },
+    Chord {
+        keys: &[
+            KeyPress {
+                char: 'd',
+                ctrl: false,
+                alt: false,
+            },
+            KeyPress {
+                char: 'c',
+                ctrl: false,
+                alt: false,
+            },
+        ],
+        action: "delete_column",
+    },
+    Chord {
+        keys: &[
+            KeyPress {
+                char: 'i',
+                ctrl: false,
+                alt: false,
+            },
+            KeyPress {
+                char: 'r',
+                ctrl: false,
+                alt: false,
+            },
+        ],
+        action: "insert_row",
+    },
+    Chord {
+        keys: &[
+            KeyPress {
+                char: 'i',
+                ctrl: false,
+                alt: false,
+            },
+            KeyPress {
+                char: 'c',
+                ctrl: false,
+                alt: false,
+            },
+        ],
+        action: "insert_column",
+    },
+];
+
+/// Translates a real key event into the backend-agnostic shape
+/// [`KeyInput`] matches against, or `None` for keys no chord can start
+/// with (arrows, Enter, etc. - those are handled by `handle_key` itself).
+fn key_press(key: &KeyEvent) -> Option<KeyPress> {
+    match key.code {
+        KeyCode::Char(char) => Some(KeyPress {
+            char,
+            ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+            alt: key.modifiers.contains(KeyModifiers::ALT),
+        }),
+        _ => None,
+    }
+}
+
+/// How verbose `--log-file` logging is, mirroring `log::LevelFilter`.
+#[derive(Clone, Copy, ValueEnum)]
+enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// tie - a TUI icon editor.
+#[derive(Parser)]
+struct Args {
+    /// Run a non-interactive subcommand instead of opening the TUI.
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+    /// Image file to open. A blank 16x16 canvas is created if omitted.
+    file: Option<PathBuf>,
+    /// Reopen the most recently edited file instead of `file`, at its last
+    /// cursor position.
+    #[arg(long = "continue")]
+    continue_: bool,
+    /// Append diagnostic logs to this file, since stderr would corrupt the
+    /// alternate screen. Logging is disabled unless this is set.
+    #[arg(long)]
+    log_file: Option<PathBuf>,
+    /// How verbose `--log-file` logging is.
+    #[arg(long, default_value = "info")]
+    log_level: LogLevel,
+    /// Suppress interactive hints - the status-bar "begin input command"
+    /// hint in the TUI, and the per-file progress lines `convert` prints -
+    /// so wrapper scripts only see meaningful output.
+    #[arg(long, global = true)]
+    quiet: bool,
+}
+
+/// Failure modes `main` reports with a distinct process exit code, so
+/// wrapper scripts can tell them apart without parsing stderr.
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    #[error("{0}")]
+    Unsupported(tie::image::ImageError),
+    #[error("{0}")]
+    Decode(tie::image::ImageError),
+    #[error("{0}")]
+    Save(tie::image::ImageError),
+    #[error("{} already exists; pass --force to overwrite it", .0.display())]
+    Aborted(PathBuf),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl CliError {
+    /// `0` is reserved for success; everything else distinguishes one
+    /// failure mode from another for scripts inspecting `$?`.
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Unsupported(_) => 2,
+            CliError::Decode(_) => 3,
+            CliError::Save(_) => 4,
+            CliError::Aborted(_) => 5,
+            CliError::Other(_) => 1,
+        }
+    }
+}
+
+/// Classifies an [`Image::load`] failure as [`CliError::Unsupported`] (a
+/// format `tie` doesn't understand) or a generic [`CliError::Decode`].
+fn classify_load_error(err: tie::image::ImageError) -> CliError {
+    match err {
+        tie::image::ImageError::Unsupported { .. } => CliError::Unsupported(err),
+        other => CliError::Decode(other),
+    }
+}
+
+/// Non-interactive subcommands that reuse the editor's image code without
+/// opening a terminal UI.
+#[derive(clap::Subcommand)]
+enum Subcommand {
+    /// Decode and re-encode image(s), e.g. `tie convert in.jpg out.png`,
+    /// turning tie into a lightweight asset-pipeline tool.
+    Convert(ConvertArgs),
+    /// Print an image's dimensions, color type, bit depth, and palette,
+    /// for scripting and asset validation in CI.
+    Info(InfoArgs),
+    /// Run a script of `:`-commands against an image headlessly, e.g. from
+    /// a Makefile.
+    Apply(ApplyArgs),
+    /// Tile input images into a grid on a new canvas, e.g. `tie montage
+    /// --cols 4 frames/*.png sheet.png`, for packing animation frames.
+    Montage(MontageArgs),
+    /// Cut a sprite sheet into per-tile images, e.g. `tie split sheet.png
+    /// 16x16 outdir/` - the inverse of `montage`.
+    Split(SplitArgs),
+}
+
+/// `tie convert [--scale N] <input>... <output>` - the output is the last
+/// path; it's a file when there's one input, or an existing directory
+/// (each input keeping its own file name) when there are several.
+#[derive(clap::Args)]
+struct ConvertArgs {
+    /// Resize each image by this integer factor (nearest-neighbor) before
+    /// writing it out.
+    #[arg(long)]
+    scale: Option<u32>,
+    /// Overwrite an output path that already exists.
+    #[arg(long)]
+    force: bool,
+    /// Input image(s), followed by the output file or directory.
+    #[arg(required = true, num_args = 2..)]
+    paths: Vec<PathBuf>,
+}
+
+/// Runs `tie convert`: loads each input, optionally scales it, and saves it
+/// to the output path (or, with multiple inputs, into the output
+/// directory under its original file name). Refuses to overwrite an
+/// existing output path unless `--force` is given, since there's no
+/// terminal to ask "overwrite? y/n" headlessly.
+fn run_convert(args: ConvertArgs, quiet: bool) -> Result<(), CliError> {
+    let split = args.paths.len() - 1;
+    let (inputs, output) = args.paths.split_at(split);
+    let output = &output[0];
+
+    if inputs.len() > 1 {
+        std::fs::create_dir_all(output).map_err(anyhow::Error::from)?;
+    }
+
+    for input in inputs {
+        let mut image = TieImage::load(input).map_err(classify_load_error)?;
+        if let Some(factor) = args.scale {
+            image = image.scaled(factor);
+        }
+
+        let out_path = if inputs.len() > 1 {
+            let name = input
+                .file_name()
+                .ok_or_else(|| anyhow::anyhow!("{}: has no file name", input.display()))?;
+            output.join(name)
+        } else {
+            output.clone()
+        };
+        if out_path.exists() && !args.force {
+            return Err(CliError::Aborted(out_path));
+        }
+        image.save(&out_path).map_err(CliError::Save)?;
+        if !quiet {
+            println!("{} -> {}", input.display(), out_path.display());
+        }
+    }
+    Ok(())
+}
+
+/// `tie montage --cols N <input>... <output>` - the output is the last
+/// path, same convention as [`ConvertArgs`].
+#[derive(clap::Args)]
+struct MontageArgs {
+    /// How many tiles wide the output grid is.
+    #[arg(long)]
+    cols: u32,
+    /// Overwrite an output path that already exists.
+    #[arg(long)]
+    force: bool,
+    /// Input image(s), followed by the output file.
+    #[arg(required = true, num_args = 2..)]
+    paths: Vec<PathBuf>,
+}
+
+/// Runs `tie montage`: loads every input image, validates they're all the
+/// same size, and writes a single `--cols`-wide grid of them to the output
+/// path. Refuses to overwrite an existing output path unless `--force` is
+/// given, since there's no terminal to ask "overwrite? y/n" headlessly.
+fn run_montage(args: MontageArgs) -> Result<(), CliError> {
+    let split = args.paths.len() - 1;
+    let (inputs, output) = args.paths.split_at(split);
+    let output = &output[0];
+
+    if output.exists() && !args.force {
+        return Err(CliError::Aborted(output.clone()));
+    }
+
+    let images = inputs
+        .iter()
+        .map(|path| TieImage::load(path).map_err(classify_load_error))
+        .collect::<Result<Vec<_>, _>>()?;
+    let sheet = TieImage::montage(&images, args.cols).map_err(anyhow::Error::from)?;
+    sheet.save(output).map_err(CliError::Save)?;
+    Ok(())
+}
+
+/// `tie split <sheet> <tile_size> <outdir>` - `tile_size` is `WxH`, e.g.
+/// `16x16`.
+#[derive(clap::Args)]
+struct SplitArgs {
+    /// Sprite sheet to split.
+    sheet: PathBuf,
+    /// Tile size as `<width>x<height>`, e.g. `16x16`.
+    tile_size: String,
+    /// Directory to write the numbered tile images into, created if needed.
+    outdir: PathBuf,
+}
+
+/// Parses a `WxH` tile size, e.g. `16x16`.
+fn parse_tile_size(s: &str) -> anyhow::Result<(u32, u32)> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| anyhow::anyhow!("tile size must be WxH, e.g. 16x16, got {s}"))?;
+    let width = width
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid tile width: {width}"))?;
+    let height = height
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid tile height: {height}"))?;
+    Ok((width, height))
+}
+
+/// Runs `tie split`: loads the sheet, cuts it into `tile_size` tiles, and
+/// writes each one as a numbered PNG into `outdir` (created if it doesn't
+/// exist yet).
+fn run_split(args: SplitArgs) -> Result<(), CliError> {
+    let (tile_width, tile_height) = parse_tile_size(&args.tile_size)?;
+    let sheet = TieImage::load(&args.sheet).map_err(classify_load_error)?;
+    let tiles = sheet
+        .split(tile_width, tile_height)
+        .map_err(anyhow::Error::from)?;
+
+    std::fs::create_dir_all(&args.outdir).map_err(anyhow::Error::from)?;
+    for (i, tile) in tiles.iter().enumerate() {
+        let path = args.outdir.join(format!("{i:05}.png"));
+        tile.save(&path).map_err(CliError::Save)?;
+    }
+    Ok(())
+}
+
+/// `tie info [--json] <file>` - dimensions, color mode, bit depth, and
+/// palette of a single image file.
+#[derive(clap::Args)]
+struct InfoArgs {
+    /// Image file to inspect.
+    path: PathBuf,
+    /// Print the info as a single line of JSON instead of human-readable text.
+    #[arg(long)]
+    json: bool,
+}
+
+/// An image's dimensions, color mode, bit depth, and palette, as reported
+/// by `tie info`.
+#[derive(serde::Serialize)]
+struct ImageInfo {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    color_mode: String,
+    bit_depth: String,
+    unique_colors: usize,
+    palette: Vec<[u8; 4]>,
+}
+
+/// Runs `tie info`: loads the image and prints its dimensions, color mode,
+/// bit depth, and palette, as JSON (`--json`) or human-readable text.
+fn run_info(args: InfoArgs) -> Result<(), CliError> {
+    let image = TieImage::load(&args.path).map_err(classify_load_error)?;
+    let palette = image.palette();
+    let info = ImageInfo {
+        path: args.path,
+        width: image.width(),
+        height: image.height(),
+        color_mode: format!("{:?}", image.color_mode()).to_lowercase(),
+        bit_depth: format!("{:?}", image.bit_depth()).to_lowercase(),
+        unique_colors: palette.len(),
+        palette,
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string(&info).map_err(anyhow::Error::from)?
+        );
+    } else {
+        println!(
+            "{}: {}x{}, {} {}-bit, {} unique color(s)",
+            info.path.display(),
+            info.width,
+            info.height,
+            info.color_mode,
+            info.bit_depth,
+            info.unique_colors
+        );
+        for [r, g, b, a] in &info.palette {
+            println!("  {r:3} {g:3} {b:3} {a:3}");
+        }
+    }
+    Ok(())
+}
+
+/// `tie apply [--cmd CMD]... [file]` - run each command exactly as it would
+/// be typed at the `:` prompt, e.g. `--cmd ":fill 0 0 0 0" --cmd ":w
+/// out.png"`. With no `--cmd`, commands are read one per line from stdin
+/// instead, so a whole script can be piped in.
+#[derive(clap::Args)]
+struct ApplyArgs {
+    /// Image file to load. A blank 16x16 canvas is created if omitted.
+    file: Option<PathBuf>,
+    /// A command to run, as typed at the `:` prompt (the leading `:` is
+    /// optional). May be given multiple times, in order.
+    #[arg(long = "cmd")]
+    cmds: Vec<String>,
+}
+
+/// Runs `tie apply`: executes each command against the loaded (or blank)
+/// image in order, the same way the editor would. Nothing is saved unless
+/// one of the commands is a `:w`.
+fn run_apply(args: ApplyArgs) -> Result<(), CliError> {
+    let (image, path) = match &args.file {
+        Some(path) => (
+            TieImage::load(path).map_err(classify_load_error)?,
+            Some(path.clone()),
+        ),
+        None => (TieImage::new(16, 16), None),
+    };
+    let mut app = tie::Editor::new(image, path);
+
+    let cmds = if args.cmds.is_empty() {
+        io::stdin()
+            .lines()
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(anyhow::Error::from)?
+    } else {
+        args.cmds
+    };
+
+    for cmd in &cmds {
+        let cmd = cmd.trim().trim_start_matches(':');
+        if cmd.is_empty() {
+            continue;
+        }
+        let outcome = app.execute_outcome(cmd);
+        if let Some(error) = outcome.error {
+            return Err(anyhow::anyhow!("{cmd}: {error}").into());
+        }
+    }
+    Ok(())
+}
+
+/// Where crash reports are written, alongside other ephemeral app data.
+fn crash_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("tie")
+        .join("crashes")
+}
+
+/// A one-line summary of the terminal a crash happened in.
+fn terminal_info() -> String {
+    let term = std::env::var("TERM").unwrap_or_else(|_| "unknown".to_string());
+    let size = crossterm::terminal::size()
+        .map(|(w, h)| format!("{w}x{h}"))
+        .unwrap_or_else(|_| "unknown size".to_string());
+    format!("{term} {size}")
+}
+
+/// Restores the terminal and writes a crash report before letting the
+/// default panic hook print its usual message - a panic unwinds straight
+/// past the normal `disable_raw_mode`/`LeaveAlternateScreen` cleanup at the
+/// end of `main`, so this has to do it itself.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = io::stdout().execute(LeaveAlternateScreen);
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        report_crash(&backtrace.to_string());
+        default_hook(info);
+    }));
+}
+
+/// Writes a crash report and prints its path to stderr, best-effort.
+fn report_crash(backtrace: &str) {
+    let context = tie::crash::current_context();
+    match tie::crash::write_report(&crash_dir(), &context, backtrace, &terminal_info()) {
+        Ok(path) => eprintln!("tie: crashed - report written to {}", path.display()),
+        Err(e) => eprintln!("tie: crashed, and failed to write a crash report: {e}"),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    if let Some(command) = args.command {
+        let result = match command {
+            Subcommand::Convert(convert_args) => run_convert(convert_args, args.quiet),
+            Subcommand::Info(info_args) => run_info(info_args),
+            Subcommand::Apply(apply_args) => run_apply(apply_args),
+            Subcommand::Montage(montage_args) => run_montage(montage_args),
+            Subcommand::Split(split_args) => run_split(split_args),
+        };
+        if let Err(err) = result {
+            eprintln!("tie: {err}");
+            std::process::exit(err.exit_code());
+        }
+        return Ok(());
+    }
+
+    install_panic_hook();
+
+    if let Some(log_file) = &args.log_file {
+        tie::logging::init(log_file, args.log_level.into())?;
+    }
+
+    let file = if args.continue_ {
+        let last = tie::session::SessionState::load()
+            .last_file()
+            .map(|p| p.to_path_buf());
+        if last.is_none() {
+            eprintln!("tie: --continue given, but no file has been edited yet");
+        }
+        last
+    } else {
+        args.file.clone()
+    };
+
+    let (image, path) = match &file {
+        Some(path) => (TieImage::load(path)?, Some(path.clone())),
+        None => (TieImage::new(16, 16), None),
+    };
+    let mut app = App::new(image, path);
+    app.quiet = args.quiet;
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    stdout.execute(EnterAlternateScreen)?;
+    stdout.execute(EnableBracketedPaste)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let result = run(&mut terminal, &mut app);
+
+    disable_raw_mode()?;
+    terminal.backend_mut().execute(DisableBracketedPaste)?;
+    terminal.backend_mut().execute(LeaveAlternateScreen)?;
+
+    if result.is_err() {
+        report_crash("(no backtrace - this was a returned error, not a panic)");
+    }
+
+    result
+}
+
+/// Upper bound on redraw frequency - roughly 60Hz - so holding down a
+/// movement or paint key doesn't flood the terminal with one `draw` call
+/// per repeated key event; events still get handled as fast as they
+/// arrive, only the (comparatively expensive) redraw is throttled.
+const MIN_FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+fn run<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+) -> anyhow::Result<()> {
+    let mut key_input = KeyInput::new();
+    let mut repeat = RepeatAccelerator::new();
+    let mut needs_redraw = true;
+    let mut last_draw = Instant::now() - MIN_FRAME_INTERVAL;
+    while !app.should_quit {
+        if needs_redraw {
+            let since_last_draw = last_draw.elapsed();
+            if since_last_draw < MIN_FRAME_INTERVAL {
+                std::thread::sleep(MIN_FRAME_INTERVAL - since_last_draw);
+            }
+            terminal.render_app(app)?;
+            last_draw = Instant::now();
+        }
+
+        needs_redraw = match event::read()? {
+            Event::Key(key) => {
+                key.kind == KeyEventKind::Press && handle_key(app, key, &mut key_input, &mut repeat)
+            }
+            Event::Paste(text) if app.mode == Mode::Command => {
+                app.command_paste(&text);
+                true
+            }
+            Event::Resize(_, _) => true,
+            _ => false,
+        };
+    }
+    Ok(())
+}
+
+/// Runs a key bound via `:map`, mirroring the matching built-in normal-mode
+/// binding the action is named after (e.g. `left` does what `h` does by
+/// default).
+fn apply_mapped_action(app: &mut App, action: MappableAction) {
+    match action {
+        MappableAction::Left => app.move_cursor(Direction::Left),
+        MappableAction::Right => app.move_cursor(Direction::Right),
+        MappableAction::Up => app.move_cursor(Direction::Up),
+        MappableAction::Down => app.move_cursor(Direction::Down),
+        MappableAction::Undo => app.undo(),
+        MappableAction::Paint => {
+            let color = app.active_color;
+            let _ = app.paint_at_cursors(color);
+        }
+        MappableAction::Pen => app.toggle_pen(),
+    }
+}
+
+/// Dispatches a keypress and reports whether it changed anything a redraw
+/// would show, so [`run`] can skip the draw call entirely on a keypress
+/// that turned out to be a no-op (an unbound key, an unrecognized key
+/// during a confirmation prompt, a chord still waiting on more input).
+/// Ratatui's own `Terminal::draw` already diffs the previous and next
+/// frame at the cell level and writes only the changed cells, but that
+/// diff still costs a full rebuild of the widget tree - this skips paying
+/// that cost at all when nothing could possibly have changed.
+fn handle_key(
+    app: &mut App,
+    key: KeyEvent,
+    key_input: &mut KeyInput,
+    repeat: &mut RepeatAccelerator,
+) -> bool {
+    let code = key.code;
+    match app.mode {
+        Mode::Normal if app.pending_overwrite.is_some() => match code {
+            KeyCode::Char('y') => {
+                let _ = app.confirm_overwrite();
+                true
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.cancel_overwrite();
+                true
+            }
+            _ => false,
+        },
+        Mode::Normal if app.pending_revert => match code {
+            KeyCode::Char('y') => {
+                let _ = app.confirm_revert();
+                true
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.cancel_revert();
+                true
+            }
+            _ => false,
+        },
+        Mode::Normal if app.pending_mark_jump => {
+            app.pending_mark_jump = false;
+            if let KeyCode::Char(letter) = code {
+                let _ = app.jump_to_mark(letter);
+            }
+            true
+        }
+        Mode::Normal if app.floating.is_some() => match code {
+            KeyCode::Char('h') => {
+                app.nudge_floating(-1, 0);
+                true
+            }
+            KeyCode::Char('l') => {
+                app.nudge_floating(1, 0);
+                true
+            }
+            KeyCode::Char('k') => {
+                app.nudge_floating(0, -1);
+                true
+            }
+            KeyCode::Char('j') => {
+                app.nudge_floating(0, 1);
+                true
+            }
+            KeyCode::Enter => {
+                app.drop_floating();
+                true
+            }
+            KeyCode::Esc => {
+                app.cancel_floating();
+                true
+            }
+            _ => false,
+        },
+        Mode::Normal => {
+            if let Some(press) = key_press(&key) {
+                match key_input.feed(press, NORMAL_CHORDS, Instant::now()) {
+                    Feed::Matched("quit") => {
+                        app.should_quit = true;
+                        return true;
+                    }
+                    Feed::Matched("undo") => {
+                        app.undo();
+                        return true;
+                    }
+                    Feed::Matched("delete_row") => {
+                        let _ = app.execute("delrow");
+                        return true;
+                    }
+                    Feed::Matched("delete_column") => {
+                        let _ = app.execute("delcol");
+                        return true;
+                    }
+                    Feed::Matched("insert_row") => {
+                        let _ = app.execute("insrow");
+                        return true;
+                    }
+                    Feed::Matched("insert_column") => {
+                        let _ = app.execute("inscol");
+                        return true;
+                    }
+                    Feed::Matched(_) | Feed::Pending => return true,
+                    Feed::NoMatch => {}
+                }
+            }
+            if let KeyCode::Char(c) = code {
+                if let Some(action) = app.key_map.get(&c).copied() {
+                    apply_mapped_action(app, action);
+                    return true;
+                }
+            }
+            match code {
+                KeyCode::Char(':') => {
+                    app.mode = Mode::Command;
+                    app.command_clear();
+                    true
+                }
+                KeyCode::Tab => {
+                    app.toggle_focus();
+                    true
+                }
+                KeyCode::Char('h') if app.focus == Focus::Palette => {
+                    app.cycle_palette_selection(false);
+                    true
+                }
+                KeyCode::Char('l') if app.focus == Focus::Palette => {
+                    app.cycle_palette_selection(true);
+                    true
+                }
+                KeyCode::Char('k') if app.focus == Focus::Palette => {
+                    app.step_palette_page(false);
+                    true
+                }
+                KeyCode::Char('j') if app.focus == Focus::Palette => {
+                    app.step_palette_page(true);
+                    true
+                }
+                KeyCode::Char('h') => {
+                    let steps = repeat.step('h', Instant::now());
+                    app.move_cursor_by(Direction::Left, steps);
+                    true
+                }
+                KeyCode::Char('l') => {
+                    let steps = repeat.step('l', Instant::now());
+                    app.move_cursor_by(Direction::Right, steps);
+                    true
+                }
+                KeyCode::Char('k') => {
+                    let steps = repeat.step('k', Instant::now());
+                    app.move_cursor_by(Direction::Up, steps);
+                    true
+                }
+                KeyCode::Char('j') => {
+                    let steps = repeat.step('j', Instant::now());
+                    app.move_cursor_by(Direction::Down, steps);
+                    true
+                }
+                KeyCode::Char('H') => {
+                    app.drag(Direction::Left, DRAG_STEPS);
+                    true
+                }
+                KeyCode::Char('L') => {
+                    app.drag(Direction::Right, DRAG_STEPS);
+                    true
+                }
+                KeyCode::Char('K') => {
+                    app.drag(Direction::Up, DRAG_STEPS);
+                    true
+                }
+                KeyCode::Char('J') => {
+                    app.drag(Direction::Down, DRAG_STEPS);
+                    true
+                }
+                KeyCode::Char('p') if app.focus == Focus::Palette => {
+                    let _ = app.paint_with_selected_cell();
+                    true
+                }
+                KeyCode::Char('p') => {
+                    app.toggle_pen();
+                    true
+                }
+                KeyCode::Char('G') => {
+                    app.toggle_grid_snap();
+                    true
+                }
+                KeyCode::Char('A') => {
+                    app.toggle_axis_lock();
+                    true
+                }
+                KeyCode::Char(c @ '0'..='9') => {
+                    app.select_palette_cell(c as u8 - b'0');
+                    true
+                }
+                KeyCode::F(1) => {
+                    app.set_palette_page(0);
+                    true
+                }
+                KeyCode::F(2) => {
+                    app.set_palette_page(1);
+                    true
+                }
+                KeyCode::F(3) => {
+                    app.set_palette_page(2);
+                    true
+                }
+                KeyCode::F(4) => {
+                    app.set_palette_page(3);
+                    true
+                }
+                KeyCode::F(5) => {
+                    app.set_tool(ToolKind::Pencil);
+                    true
+                }
+                KeyCode::F(6) => {
+                    app.set_tool(ToolKind::Eyedropper);
+                    true
+                }
+                KeyCode::F(7) => {
+                    app.set_tool(ToolKind::Select);
+                    true
+                }
+                KeyCode::Char('[') => {
+                    app.cycle_palette_selection(false);
+                    true
+                }
+                KeyCode::Char(']') => {
+                    app.cycle_palette_selection(true);
+                    true
+                }
+                KeyCode::Char('P') => {
+                    let _ = app.paint_with_selected_cell();
+                    true
+                }
+                KeyCode::Char('x') => {
+                    let color = app.active_color;
+                    let _ = app.paint_at_cursors(color);
+                    true
+                }
+                KeyCode::Char('u') => {
+                    app.undo();
+                    true
+                }
+                KeyCode::Char('w') => {
+                    let _ = app.select_wand(false);
+                    true
+                }
+                KeyCode::Char('g') => {
+                    let _ = app.grab();
+                    true
+                }
+                KeyCode::Char('y') => {
+                    let _ = app.copy_color_under_cursor();
+                    true
+                }
+                KeyCode::Char('\'') => {
+                    app.pending_mark_jump = true;
+                    true
+                }
+                _ => false,
+            }
+        }
+        Mode::Command => match code {
+            KeyCode::Enter => {
+                let line = app.command_line.clone();
+                if app.execute(&line).is_ok() {
+                    app.mode = Mode::Normal;
+                }
+                true
+            }
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.command_delete_word_backward();
+                true
+            }
+            KeyCode::Char(c) => {
+                app.command_insert(c);
+                true
+            }
+            KeyCode::Backspace => {
+                app.command_backspace();
+                true
+            }
+            KeyCode::Left => {
+                app.command_move_left();
+                true
+            }
+            KeyCode::Right => {
+                app.command_move_right();
+                true
+            }
+            KeyCode::Home => {
+                app.command_move_home();
+                true
+            }
+            KeyCode::End => {
+                app.command_move_end();
+                true
+            }
+            KeyCode::Esc => {
+                app.command_clear();
+                app.last_error = None;
+                app.invalid_token = None;
+                app.mode = Mode::Normal;
+                true
+            }
+            _ => false,
+        },
+    }
+}
@@ -0,0 +1,77 @@
+//! A `:messages` overlay: the tail of [`crate::app::App::message_log`],
+//! one line per executed command - mirrors vim's `:messages` window, and
+//! lets a command's result be checked after the status bar has already
+//! moved on to something else.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::app::Message;
+
+/// How many rows [`Messages`] renders by default - callers with a taller
+/// area (or `:messages`'s own layout row) may show fewer if the log is
+/// shorter.
+pub const HEIGHT: u16 = 6;
+
+/// The tail of the command message log, oldest shown line first so the
+/// most recent command ends up at the bottom, closest to the status bar
+/// it came from.
+pub struct Messages<'a> {
+    log: &'a [Message],
+}
+
+impl<'a> Messages<'a> {
+    pub fn new(log: &'a [Message]) -> Self {
+        Self { log }
+    }
+}
+
+impl Widget for Messages<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (row, message) in tail(self.log, area.height as usize).iter().enumerate() {
+            let y = area.y + row as u16;
+            let (suffix, color) = match &message.error {
+                Some(error) => (error.as_str(), Color::Red),
+                None => ("ok", Color::Green),
+            };
+            let line = format!(":{}  {suffix}", message.command);
+            buf.set_string(area.x, y, line, Style::new().fg(color));
+        }
+    }
+}
+
+/// The last `rows` entries of `log`, oldest first - the whole log if it's
+/// shorter than `rows`.
+fn tail(log: &[Message], rows: usize) -> &[Message] {
+    &log[log.len().saturating_sub(rows)..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(command: &str, error: Option<&str>) -> Message {
+        Message {
+            command: command.to_string(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn tail_returns_the_whole_log_when_shorter_than_rows() {
+        let log = vec![message("u", None)];
+        assert_eq!(tail(&log, 6), &log[..]);
+    }
+
+    #[test]
+    fn tail_keeps_only_the_most_recent_rows() {
+        let log = vec![
+            message("u", None),
+            message("redo", None),
+            message("x", None),
+        ];
+        assert_eq!(tail(&log, 2), &log[1..]);
+    }
+}
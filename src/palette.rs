@@ -0,0 +1,694 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::color::Color;
+use crate::colorspace::ColorSpace;
+
+/// A built-in fixed color palette for a fantasy console. When set via
+/// `:palette use <name>`, every paint operation snaps to the closest color
+/// in the preset rather than the exact color requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaletteName {
+    /// No constraint - colors are used exactly as given.
+    #[default]
+    None,
+    Pico8,
+    Tic80,
+}
+
+/// PICO-8's 16-color default palette, in its official index order.
+const PICO8: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00],
+    [0x1D, 0x2B, 0x53],
+    [0x7E, 0x25, 0x53],
+    [0x00, 0x87, 0x51],
+    [0xAB, 0x52, 0x36],
+    [0x5F, 0x57, 0x4F],
+    [0xC2, 0xC3, 0xC7],
+    [0xFF, 0xF1, 0xE8],
+    [0xFF, 0x00, 0x4D],
+    [0xFF, 0xA3, 0x00],
+    [0xFF, 0xEC, 0x27],
+    [0x00, 0xE4, 0x36],
+    [0x29, 0xAD, 0xFF],
+    [0x83, 0x76, 0x9C],
+    [0xFF, 0x77, 0xA8],
+    [0xFF, 0xCC, 0xAA],
+];
+
+/// TIC-80's default "sweetie-16" palette, in its official index order.
+const TIC80: [[u8; 3]; 16] = [
+    [0x1A, 0x1C, 0x2C],
+    [0x5D, 0x27, 0x5D],
+    [0xB1, 0x3E, 0x53],
+    [0xEF, 0x7D, 0x57],
+    [0xFF, 0xCD, 0x75],
+    [0xA7, 0xF0, 0x70],
+    [0x38, 0xB7, 0x64],
+    [0x25, 0x71, 0x79],
+    [0x29, 0x36, 0x6F],
+    [0x3B, 0x5D, 0xC9],
+    [0x41, 0xA6, 0xF6],
+    [0x73, 0xEF, 0xF7],
+    [0xF4, 0xF4, 0xF4],
+    [0x94, 0xB0, 0xC2],
+    [0x56, 0x6C, 0x86],
+    [0x33, 0x3C, 0x57],
+];
+
+impl PaletteName {
+    /// The fixed RGB colors this preset constrains editing to, in index
+    /// order - empty for `None`.
+    pub fn colors(self) -> &'static [[u8; 3]] {
+        match self {
+            PaletteName::None => &[],
+            PaletteName::Pico8 => &PICO8,
+            PaletteName::Tic80 => &TIC80,
+        }
+    }
+
+    /// Snaps a color to the closest entry in this preset by squared
+    /// Euclidean distance in RGB space, leaving alpha untouched. A no-op
+    /// for `None`.
+    pub fn constrain(self, [r, g, b, a]: [u8; 4]) -> [u8; 4] {
+        match self.nearest(r, g, b) {
+            Some([nr, ng, nb]) => [nr, ng, nb, a],
+            None => [r, g, b, a],
+        }
+    }
+
+    /// The index of the palette entry closest to `color`, ignoring alpha -
+    /// for sprite export, where every pixel becomes a palette index. Always
+    /// `0` for `None`, since there is no palette to index into.
+    pub fn index_of(self, [r, g, b, _a]: [u8; 4]) -> usize {
+        let Some(nearest) = self.nearest(r, g, b) else {
+            return 0;
+        };
+        self.colors()
+            .iter()
+            .position(|&color| color == nearest)
+            .unwrap_or(0)
+    }
+
+    fn nearest(self, r: u8, g: u8, b: u8) -> Option<[u8; 3]> {
+        nearest_in(self.colors(), r, g, b, DistanceMetric::Rgb)
+    }
+}
+
+/// The entry in `colors` closest to `(r, g, b)` by `metric`, or `None` if
+/// `colors` is empty.
+fn nearest_in(colors: &[[u8; 3]], r: u8, g: u8, b: u8, metric: DistanceMetric) -> Option<[u8; 3]> {
+    colors.iter().copied().min_by(|&a, &candidate| {
+        metric
+            .distance([r, g, b], a)
+            .total_cmp(&metric.distance([r, g, b], candidate))
+    })
+}
+
+/// How two colors' closeness is measured when snapping paint to the
+/// nearest palette cell - set with `:set palettemetric`. `Rgb` is cheap and
+/// matches what most pixel art expects; `Ciede2000` trades speed for
+/// matching human perception more closely, useful when a palette has
+/// colors that are close in RGB but clearly distinct to the eye.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DistanceMetric {
+    #[default]
+    Rgb,
+    Ciede2000,
+}
+
+impl DistanceMetric {
+    /// A distance between `a` and `b` in this metric's own units - only
+    /// relative order matters, since this is used to pick the closest
+    /// cell, never shown to the user.
+    fn distance(self, a: [u8; 3], b: [u8; 3]) -> f32 {
+        match self {
+            DistanceMetric::Rgb => {
+                let [ar, ag, ab] = a.map(f32::from);
+                let [br, bg, bb] = b.map(f32::from);
+                let (dr, dg, db) = (ar - br, ag - bg, ab - bb);
+                dr * dr + dg * dg + db * db
+            }
+            DistanceMetric::Ciede2000 => ciede2000(a, b),
+        }
+    }
+}
+
+/// The CIEDE2000 color difference between two 8-bit sRGB colors, via CIE
+/// L*a*b*. Lower is closer; the result isn't in any particular unit scale
+/// beyond "smaller means more similar", which is all [`nearest_in`] needs.
+fn ciede2000(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let (l1, a1, b1) = rgb_to_lab(a);
+    let (l2, a2, b2) = rgb_to_lab(b);
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let c_bar = (c1 + c2) / 2.0;
+
+    let c_bar7 = c_bar.powi(7);
+    let g = 0.5 * (1.0 - (c_bar7 / (c_bar7 + 25.0f32.powi(7))).sqrt());
+    let a1p = a1 * (1.0 + g);
+    let a2p = a2 * (1.0 + g);
+
+    let c1p = (a1p * a1p + b1 * b1).sqrt();
+    let c2p = (a2p * a2p + b2 * b2).sqrt();
+
+    let h1p = hue_angle(b1, a1p);
+    let h2p = hue_angle(b2, a2p);
+
+    let delta_l = l2 - l1;
+    let delta_cp = c2p - c1p;
+    let delta_hp_raw = h2p - h1p;
+    let delta_hp = if c1p * c2p == 0.0 {
+        0.0
+    } else if delta_hp_raw.abs() <= 180.0 {
+        delta_hp_raw
+    } else if delta_hp_raw > 180.0 {
+        delta_hp_raw - 360.0
+    } else {
+        delta_hp_raw + 360.0
+    };
+    let delta_big_hp = 2.0 * (c1p * c2p).sqrt() * (delta_hp.to_radians() / 2.0).sin();
+
+    let l_bar_p = (l1 + l2) / 2.0;
+    let c_bar_p = (c1p + c2p) / 2.0;
+    let h_sum = h1p + h2p;
+    let h_bar_p = if c1p * c2p == 0.0 {
+        h_sum
+    } else if (h1p - h2p).abs() <= 180.0 {
+        h_sum / 2.0
+    } else if h_sum < 360.0 {
+        (h_sum + 360.0) / 2.0
+    } else {
+        (h_sum - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (h_bar_p - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_bar_p).to_radians().cos()
+        + 0.32 * (3.0 * h_bar_p + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_bar_p - 63.0).to_radians().cos();
+
+    let delta_theta = 30.0 * (-(((h_bar_p - 275.0) / 25.0).powi(2))).exp();
+    let r_c = 2.0 * (c_bar_p.powi(7) / (c_bar_p.powi(7) + 25.0f32.powi(7))).sqrt();
+    let r_t = -r_c * (2.0 * delta_theta.to_radians()).sin();
+
+    let s_l = 1.0 + (0.015 * (l_bar_p - 50.0).powi(2)) / (20.0 + (l_bar_p - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_bar_p;
+    let s_h = 1.0 + 0.015 * c_bar_p * t;
+
+    let term_l = delta_l / s_l;
+    let term_c = delta_cp / s_c;
+    let term_h = delta_big_hp / s_h;
+
+    term_l * term_l + term_c * term_c + term_h * term_h + r_t * term_c * term_h
+}
+
+/// `atan2(b, a)` in degrees, wrapped to `0.0..360.0` - `0.0` when both `a`
+/// and `b` are `0.0` (a neutral gray has no hue).
+fn hue_angle(b: f32, a: f32) -> f32 {
+    if a == 0.0 && b == 0.0 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    }
+}
+
+/// Converts an 8-bit sRGB color to CIE L*a*b*, via CIE XYZ under the D65
+/// illuminant.
+fn rgb_to_lab([r, g, b]: [u8; 3]) -> (f32, f32, f32) {
+    fn to_linear(channel: u8) -> f32 {
+        let c = channel as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+    let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+
+    let x = r * 0.4124564 + g * 0.3575761 + b * 0.1804375;
+    let y = r * 0.2126729 + g * 0.7151522 + b * 0.0721750;
+    let z = r * 0.0193339 + g * 0.119_192 + b * 0.9503041;
+
+    // D65 reference white.
+    const XN: f32 = 0.95047;
+    const YN: f32 = 1.0;
+    const ZN: f32 = 1.08883;
+
+    fn f(t: f32) -> f32 {
+        const DELTA: f32 = 6.0 / 29.0;
+        if t > DELTA.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    }
+
+    let (fx, fy, fz) = (f(x / XN), f(y / YN), f(z / ZN));
+    let l = 116.0 * fy - 16.0;
+    let a = 500.0 * (fx - fy);
+    let b = 200.0 * (fy - fz);
+    (l, a, b)
+}
+
+/// How many cells the digit keys `0`-`9` can address at once - palettes
+/// with more cells than this are split into pages, switched with
+/// `F1`-`F4`, so the same ten keys can reach every cell a page at a time.
+pub const PAGE_SIZE: u8 = 10;
+
+/// Why a [`PaletteSlots`] operation was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PaletteError {
+    #[error("palette index {0} is out of range")]
+    IndexOutOfRange(u8),
+    #[error("no palette cell is named '{0}'")]
+    UnknownName(String),
+    #[error("no palette cell named '{0}' on page {1}")]
+    UnknownNameOnPage(String, u8),
+}
+
+/// A mutable, per-session working copy of a palette's cells, seeded from a
+/// [`PaletteName`] preset. Lets cells be named (`:palette name <index>
+/// <name>`), swapped (`:palette swap <a> <b>`), and recolored by name
+/// (`:set <name> r g b`) without touching the underlying preset's fixed
+/// colors. Reset to the preset's original colors whenever `:palette use`
+/// switches presets.
+#[derive(Debug, Clone, Default)]
+pub struct PaletteSlots {
+    colors: Vec<[u8; 3]>,
+    names: HashMap<u8, String>,
+}
+
+impl PaletteSlots {
+    /// Seeds a fresh working copy from `preset`'s colors, with no names.
+    pub fn from_preset(preset: PaletteName) -> Self {
+        Self {
+            colors: preset.colors().to_vec(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// The current working colors, in index order.
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    /// Replaces the working colors wholesale, clearing every cell's name
+    /// since they belonged to the old colors at those indices - e.g. for
+    /// `:palette from-file` to adopt another image's colors.
+    pub fn set_colors(&mut self, colors: Vec<[u8; 3]>) {
+        self.colors = colors;
+        self.names.clear();
+    }
+
+    /// The name given to cell `index`, if any.
+    pub fn name(&self, index: u8) -> Option<&str> {
+        self.names.get(&index).map(String::as_str)
+    }
+
+    /// Labels cell `index` as `name`, so it can be addressed as `:set
+    /// <name> r g b` instead of by its numeric index.
+    pub fn set_name(&mut self, index: u8, name: String) -> Result<(), PaletteError> {
+        self.check_index(index)?;
+        self.names.insert(index, name);
+        Ok(())
+    }
+
+    /// Swaps two cells' colors. Names stay attached to their label rather
+    /// than their position, so a swap moves names along with their colors.
+    pub fn swap(&mut self, a: u8, b: u8) -> Result<(), PaletteError> {
+        self.check_index(a)?;
+        self.check_index(b)?;
+        self.colors.swap(a as usize, b as usize);
+        let name_a = self.names.remove(&a);
+        let name_b = self.names.remove(&b);
+        if let Some(name) = name_a {
+            self.names.insert(b, name);
+        }
+        if let Some(name) = name_b {
+            self.names.insert(a, name);
+        }
+        Ok(())
+    }
+
+    /// Overrides the color of the cell named `name`. When `page` is given
+    /// (the `@<page>` suffix of `:set <name>@<page> r g b`), only matches
+    /// a cell within that page - two pages may reuse the same name once
+    /// their digit-key ranges no longer overlap.
+    pub fn set_color_by_name(
+        &mut self,
+        name: &str,
+        page: Option<u8>,
+        color: [u8; 3],
+    ) -> Result<(), PaletteError> {
+        let index = self
+            .names
+            .iter()
+            .find(|&(&index, cell_name)| {
+                cell_name == name && page.is_none_or(|page| index / PAGE_SIZE == page)
+            })
+            .map(|(&index, _)| index)
+            .ok_or_else(|| match page {
+                Some(page) => PaletteError::UnknownNameOnPage(name.to_string(), page),
+                None => PaletteError::UnknownName(name.to_string()),
+            })?;
+        self.colors[index as usize] = color;
+        Ok(())
+    }
+
+    /// The number of pages the digit keys `0`-`9` need to reach every
+    /// cell, `PAGE_SIZE` cells at a time - always at least `1`, even for
+    /// an empty palette, so page `0` is always valid to select.
+    pub fn page_count(&self) -> u8 {
+        let pages = self.colors.len().div_ceil(PAGE_SIZE as usize);
+        pages.max(1) as u8
+    }
+
+    /// Snaps a color to the closest cell by `metric`, leaving alpha
+    /// untouched - a no-op when there are no cells (the `None` preset).
+    pub fn constrain(&self, [r, g, b, a]: [u8; 4], metric: DistanceMetric) -> [u8; 4] {
+        match nearest_in(&self.colors, r, g, b, metric) {
+            Some([nr, ng, nb]) => [nr, ng, nb, a],
+            None => [r, g, b, a],
+        }
+    }
+
+    /// Whether `color`'s RGB exactly matches one of the cells, ignoring
+    /// alpha - used by `:set lockpalette` to tell an exact match from one
+    /// that would merely snap to the nearest cell.
+    pub fn contains(&self, [r, g, b, _a]: [u8; 4]) -> bool {
+        self.colors.contains(&[r, g, b])
+    }
+
+    /// The index of the cell closest to `color` by `metric`, ignoring
+    /// alpha - `0` when there are no cells.
+    pub fn index_of(&self, [r, g, b, _a]: [u8; 4], metric: DistanceMetric) -> usize {
+        let Some(nearest) = nearest_in(&self.colors, r, g, b, metric) else {
+            return 0;
+        };
+        self.colors
+            .iter()
+            .position(|&color| color == nearest)
+            .unwrap_or(0)
+    }
+
+    /// Writes a smooth `steps`-step gradient from cell `start`'s color to
+    /// cell `end`'s color into `steps` consecutive cells beginning at
+    /// `start` - the bread-and-butter shading ramp of pixel art. Blends
+    /// through `colorspace` the same way [`crate::app::App::adjust`] does,
+    /// so a `Gamma` ramp's midpoint looks like the midpoint brightness
+    /// rather than the midpoint byte value. A no-op for `steps == 0`.
+    pub fn ramp(
+        &mut self,
+        start: u8,
+        end: u8,
+        steps: u32,
+        colorspace: ColorSpace,
+    ) -> Result<(), PaletteError> {
+        self.check_index(start)?;
+        self.check_index(end)?;
+        if steps == 0 {
+            return Ok(());
+        }
+        let last = start as u32 + steps - 1;
+        if last as usize >= self.colors.len() {
+            return Err(PaletteError::IndexOutOfRange(last.min(u8::MAX as u32) as u8));
+        }
+        let from = self.colors[start as usize];
+        let to = self.colors[end as usize];
+        for step in 0..steps {
+            let t = if steps == 1 {
+                0.0
+            } else {
+                step as f32 / (steps - 1) as f32
+            };
+            self.colors[start as usize + step as usize] = [
+                colorspace.lerp_channel(from[0], to[0], t),
+                colorspace.lerp_channel(from[1], to[1], t),
+                colorspace.lerp_channel(from[2], to[2], t),
+            ];
+        }
+        Ok(())
+    }
+
+    /// Fills every cell except `seed` with colors derived from `seed`'s
+    /// hue via `harmony`, keeping `seed`'s saturation and value. A
+    /// harmony's hue offsets repeat in order to cover palettes with more
+    /// cells than offsets.
+    pub fn harmonize(&mut self, seed: u8, harmony: Harmony) -> Result<(), PaletteError> {
+        self.check_index(seed)?;
+        let (hue, saturation, value) = Color::from(self.colors[seed as usize]).to_hsv();
+        let offsets = harmony.hue_offsets();
+        let mut next = 0;
+        for i in 0..self.colors.len() {
+            if i == seed as usize {
+                continue;
+            }
+            self.colors[i] =
+                Color::from_hsv(hue + offsets[next % offsets.len()], saturation, value).into();
+            next += 1;
+        }
+        Ok(())
+    }
+
+    fn check_index(&self, index: u8) -> Result<(), PaletteError> {
+        if (index as usize) < self.colors.len() {
+            Ok(())
+        } else {
+            Err(PaletteError::IndexOutOfRange(index))
+        }
+    }
+}
+
+/// A standard color-harmony rule for `:palette harmonize`, generating
+/// colors derived from a seed cell's hue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Harmony {
+    /// The seed's hue plus its opposite, 180° around the color wheel.
+    Complementary,
+    /// The seed's hue plus two more, evenly spaced 120° apart.
+    Triadic,
+    /// The seed's hue plus its neighbors, 30° to either side.
+    Analogous,
+}
+
+impl Harmony {
+    fn hue_offsets(self) -> &'static [f32] {
+        match self {
+            Harmony::Complementary => &[180.0],
+            Harmony::Triadic => &[120.0, 240.0],
+            Harmony::Analogous => &[30.0, -30.0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn none_leaves_colors_unchanged() {
+        assert_eq!(
+            PaletteName::None.constrain([12, 34, 56, 78]),
+            [12, 34, 56, 78]
+        );
+    }
+
+    #[test]
+    fn pico8_snaps_to_the_closest_preset_color() {
+        assert_eq!(
+            PaletteName::Pico8.constrain([250, 10, 70, 255]),
+            [0xFF, 0x00, 0x4D, 255]
+        );
+    }
+
+    #[test]
+    fn index_of_matches_the_snapped_color_position() {
+        assert_eq!(PaletteName::Pico8.index_of([0, 0, 0, 255]), 0);
+        assert_eq!(PaletteName::Pico8.index_of([0xFF, 0xCC, 0xAA, 255]), 15);
+    }
+
+    #[test]
+    fn slots_start_out_unnamed_with_the_preset_colors() {
+        let slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        assert_eq!(slots.colors(), PaletteName::Pico8.colors());
+        assert_eq!(slots.name(0), None);
+    }
+
+    #[test]
+    fn naming_an_out_of_range_cell_fails() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        assert_eq!(
+            slots.set_name(99, "oops".to_string()),
+            Err(PaletteError::IndexOutOfRange(99))
+        );
+    }
+
+    #[test]
+    fn swap_exchanges_colors_and_keeps_names_with_their_label() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        slots.set_name(0, "skin".to_string()).unwrap();
+        let (c0, c1) = (slots.colors()[0], slots.colors()[1]);
+
+        slots.swap(0, 1).unwrap();
+
+        assert_eq!(slots.colors()[0], c1);
+        assert_eq!(slots.colors()[1], c0);
+        assert_eq!(slots.name(0), None);
+        assert_eq!(slots.name(1), Some("skin"));
+    }
+
+    #[test]
+    fn set_colors_replaces_the_palette_and_clears_names() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        slots.set_name(0, "skin".to_string()).unwrap();
+
+        slots.set_colors(vec![[10, 20, 30], [40, 50, 60]]);
+
+        assert_eq!(slots.colors(), &[[10, 20, 30], [40, 50, 60]]);
+        assert_eq!(slots.name(0), None);
+    }
+
+    #[test]
+    fn set_color_by_name_overrides_the_named_cell() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        slots.set_name(8, "skin".to_string()).unwrap();
+
+        slots
+            .set_color_by_name("skin", None, [240, 200, 180])
+            .unwrap();
+
+        assert_eq!(slots.colors()[8], [240, 200, 180]);
+    }
+
+    #[test]
+    fn set_color_by_name_with_a_page_only_matches_that_page() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        slots.set_name(3, "ink".to_string()).unwrap();
+        slots.set_name(13, "ink".to_string()).unwrap();
+
+        slots
+            .set_color_by_name("ink", Some(1), [240, 200, 180])
+            .unwrap();
+
+        assert_eq!(slots.colors()[3], PICO8[3]);
+        assert_eq!(slots.colors()[13], [240, 200, 180]);
+    }
+
+    #[test]
+    fn set_color_by_name_fails_for_a_name_not_on_the_given_page() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        slots.set_name(3, "ink".to_string()).unwrap();
+
+        assert_eq!(
+            slots.set_color_by_name("ink", Some(1), [240, 200, 180]),
+            Err(PaletteError::UnknownNameOnPage("ink".to_string(), 1))
+        );
+    }
+
+    #[test]
+    fn page_count_is_always_at_least_one() {
+        let slots = PaletteSlots::from_preset(PaletteName::None);
+        assert_eq!(slots.page_count(), 1);
+    }
+
+    #[test]
+    fn page_count_covers_every_cell_in_blocks_of_page_size() {
+        let slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        assert_eq!(slots.colors().len(), 16);
+        assert_eq!(slots.page_count(), 2);
+    }
+
+    #[test]
+    fn ramp_writes_an_evenly_spaced_gradient_starting_at_the_first_cell() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::None);
+        slots.colors = vec![[0, 0, 0], [0, 0, 0], [0, 0, 0], [100, 100, 100]];
+
+        slots.ramp(0, 3, 4, ColorSpace::Raw).unwrap();
+
+        assert_eq!(slots.colors[0], [0, 0, 0]);
+        assert_eq!(slots.colors[1], [33, 33, 33]);
+        assert_eq!(slots.colors[2], [67, 67, 67]);
+        assert_eq!(slots.colors[3], [100, 100, 100]);
+    }
+
+    #[test]
+    fn ramp_in_gamma_colorspace_brightens_the_midpoint_more_than_raw_does() {
+        let mut raw = PaletteSlots::from_preset(PaletteName::None);
+        raw.colors = vec![[0, 0, 0], [0, 0, 0], [255, 255, 255]];
+        raw.ramp(0, 2, 3, ColorSpace::Raw).unwrap();
+
+        let mut gamma = PaletteSlots::from_preset(PaletteName::None);
+        gamma.colors = vec![[0, 0, 0], [0, 0, 0], [255, 255, 255]];
+        gamma.ramp(0, 2, 3, ColorSpace::Gamma).unwrap();
+
+        assert!(gamma.colors[1][0] > raw.colors[1][0]);
+    }
+
+    #[test]
+    fn ramp_with_one_step_is_just_the_start_color() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::None);
+        slots.colors = vec![[10, 20, 30], [200, 200, 200]];
+
+        slots.ramp(0, 1, 1, ColorSpace::Raw).unwrap();
+
+        assert_eq!(slots.colors[0], [10, 20, 30]);
+    }
+
+    #[test]
+    fn ramp_that_would_overflow_the_palette_fails() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::None);
+        slots.colors = vec![[0, 0, 0], [255, 255, 255]];
+
+        assert_eq!(
+            slots.ramp(0, 1, 5, ColorSpace::Raw),
+            Err(PaletteError::IndexOutOfRange(4))
+        );
+    }
+
+    #[test]
+    fn complementary_harmony_opposes_the_seed_hue() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::None);
+        slots.colors = vec![[255, 0, 0], [0, 0, 0], [0, 0, 0]];
+
+        slots.harmonize(0, Harmony::Complementary).unwrap();
+
+        assert_eq!(slots.colors[0], [255, 0, 0]);
+        for &color in &slots.colors[1..] {
+            assert_eq!(color, [0, 255, 255]);
+        }
+    }
+
+    #[test]
+    fn triadic_harmony_spaces_two_hues_120_degrees_apart() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::None);
+        slots.colors = vec![[255, 0, 0], [0, 0, 0], [0, 0, 0]];
+
+        slots.harmonize(0, Harmony::Triadic).unwrap();
+
+        assert_eq!(slots.colors[1], [0, 255, 0]);
+        assert_eq!(slots.colors[2], [0, 0, 255]);
+    }
+
+    #[test]
+    fn harmonize_with_an_out_of_range_seed_fails() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        assert_eq!(
+            slots.harmonize(99, Harmony::Analogous),
+            Err(PaletteError::IndexOutOfRange(99))
+        );
+    }
+
+    #[test]
+    fn set_color_by_name_fails_for_an_unknown_name() {
+        let mut slots = PaletteSlots::from_preset(PaletteName::Pico8);
+        assert_eq!(
+            slots.set_color_by_name("skin", None, [240, 200, 180]),
+            Err(PaletteError::UnknownName("skin".to_string()))
+        );
+    }
+}
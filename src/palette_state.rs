@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::palette::PaletteName;
+
+/// Remembers which [`PaletteName`] was last active for each file, so
+/// reopening it restores the same palette instead of falling back to the
+/// config default - gated on `Config::remember_palette`. Stored as
+/// `tie/palette_state.toml` in the platform config directory, keyed by the
+/// file's path as given on the command line.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaletteState {
+    #[serde(default)]
+    by_path: HashMap<PathBuf, PaletteName>,
+}
+
+impl PaletteState {
+    fn file_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("tie").join("palette_state.toml"))
+    }
+
+    /// Loads the saved state, falling back to empty if missing, unreadable,
+    /// or fails to parse.
+    pub fn load() -> Self {
+        Self::file_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the state, best-effort - a failure to write here is a missed
+    /// convenience, not a data-loss risk, so it's not surfaced as an error.
+    pub fn save(&self) {
+        let Some(path) = Self::file_path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(contents) = toml::to_string(self) {
+            let _ = std::fs::write(path, contents);
+        }
+    }
+
+    /// The remembered palette for `path`, if any.
+    pub fn get(&self, path: &Path) -> Option<PaletteName> {
+        self.by_path.get(path).copied()
+    }
+
+    /// Remembers `palette` as the active one for `path`.
+    pub fn set(&mut self, path: PathBuf, palette: PaletteName) {
+        self.by_path.insert(path, palette);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_paths_have_no_remembered_palette() {
+        let state = PaletteState::default();
+        assert_eq!(state.get(Path::new("sprite.png")), None);
+    }
+
+    #[test]
+    fn set_then_get_round_trips() {
+        let mut state = PaletteState::default();
+        state.set(PathBuf::from("sprite.png"), PaletteName::Pico8);
+        assert_eq!(state.get(Path::new("sprite.png")), Some(PaletteName::Pico8));
+    }
+
+    #[test]
+    fn serializes_through_toml() {
+        let mut state = PaletteState::default();
+        state.set(PathBuf::from("sprite.png"), PaletteName::Tic80);
+        let toml = toml::to_string(&state).unwrap();
+        let reloaded: PaletteState = toml::from_str(&toml).unwrap();
+        assert_eq!(
+            reloaded.get(Path::new("sprite.png")),
+            Some(PaletteName::Tic80)
+        );
+    }
+}
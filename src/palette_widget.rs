@@ -0,0 +1,80 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::Widget;
+
+use crate::palette::PAGE_SIZE;
+
+/// A one-line strip of palette color swatches, each showing the digit key
+/// (`0`-`9`) that selects it as the active paint color via
+/// [`App::select_palette_cell`], so the mapping is visible without having
+/// to memorize it. Only the active [`App::palette_page`]'s cells get a
+/// digit label; the rest render without one, reachable instead by
+/// switching pages with `F1`-`F4` or cycling with `[`/`]`.
+/// [`App::selected_palette_cell`] - cycled with `[`/`]` and painted with
+/// by `P` - is marked with reversed video.
+///
+/// [`App::select_palette_cell`]: crate::app::App::select_palette_cell
+/// [`App::selected_palette_cell`]: crate::app::App::selected_palette_cell
+/// [`App::palette_page`]: crate::app::App::palette_page
+pub struct PaletteBar<'a> {
+    colors: &'a [[u8; 3]],
+    selected: Option<u8>,
+    page: u8,
+}
+
+impl<'a> PaletteBar<'a> {
+    pub fn new(colors: &'a [[u8; 3]]) -> Self {
+        Self {
+            colors,
+            selected: None,
+            page: 0,
+        }
+    }
+
+    /// Marks `selected`'s cell with reversed video, if it's in range.
+    pub fn with_selected(mut self, selected: Option<u8>) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Shows digit labels for `page`'s cells instead of page `0`'s.
+    pub fn with_page(mut self, page: u8) -> Self {
+        self.page = page;
+        self
+    }
+}
+
+impl Widget for PaletteBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let page_start = self.page as usize * PAGE_SIZE as usize;
+        for (i, &[r, g, b]) in self.colors.iter().enumerate() {
+            if i as u16 >= area.width {
+                break;
+            }
+            let cell = buf.get_mut(area.x + i as u16, area.y);
+            cell.set_bg(crate::color::Color::rgb(r, g, b).into());
+            cell.set_fg(readable_text_color(r, g, b));
+            let label = i
+                .checked_sub(page_start)
+                .and_then(|digit| char::from_digit(digit as u32, 10))
+                .unwrap_or(' ');
+            let mut encoded = [0u8; 4];
+            cell.set_symbol(label.encode_utf8(&mut encoded));
+            if self.selected == Some(i as u8) {
+                cell.modifier |= Modifier::REVERSED;
+            }
+        }
+    }
+}
+
+/// Black or white, whichever reads more clearly over `(r, g, b)`, by
+/// perceptual luminance.
+fn readable_text_color(r: u8, g: u8, b: u8) -> Color {
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luminance > 140.0 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
@@ -0,0 +1,109 @@
+/// A per-pixel mask over an image, used to scope editing operations (fill,
+/// adjust, replace-color, shift, ...) to a region the user has selected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    width: u32,
+    height: u32,
+    mask: Vec<bool>,
+}
+
+impl Selection {
+    fn new(width: u32, height: u32, value: bool) -> Self {
+        Self {
+            width,
+            height,
+            mask: vec![value; (width as usize) * (height as usize)],
+        }
+    }
+
+    /// A selection covering every pixel of a `width`x`height` image.
+    pub fn all(width: u32, height: u32) -> Self {
+        Self::new(width, height, true)
+    }
+
+    /// A selection covering no pixels of a `width`x`height` image.
+    pub fn empty(width: u32, height: u32) -> Self {
+        Self::new(width, height, false)
+    }
+
+    /// A selection covering the inclusive rectangle between `(x0, y0)` and
+    /// `(x1, y1)` of a `width`x`height` image - the corners may be given in
+    /// either order. Cells outside the image are silently ignored, the same
+    /// as [`Selection::set`].
+    pub fn rect(width: u32, height: u32, x0: u32, y0: u32, x1: u32, y1: u32) -> Self {
+        let mut selection = Self::empty(width, height);
+        let (left, right) = (x0.min(x1), x0.max(x1));
+        let (top, bottom) = (y0.min(y1), y0.max(y1));
+        for y in top..=bottom {
+            for x in left..=right {
+                selection.set(x, y, true);
+            }
+        }
+        selection
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        self.mask[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, selected: bool) {
+        if x < self.width && y < self.height {
+            self.mask[y as usize * self.width as usize + x as usize] = selected;
+        }
+    }
+
+    pub fn invert(&mut self) {
+        for cell in &mut self.mask {
+            *cell = !*cell;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.mask.iter().any(|&selected| selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_and_empty_selections_cover_as_named() {
+        let all = Selection::all(2, 2);
+        let empty = Selection::empty(2, 2);
+        assert!(all.contains(0, 0) && all.contains(1, 1));
+        assert!(!empty.contains(0, 0) && empty.is_empty());
+    }
+
+    #[test]
+    fn invert_flips_every_cell() {
+        let mut sel = Selection::empty(2, 1);
+        sel.set(0, 0, true);
+        sel.invert();
+        assert!(!sel.contains(0, 0));
+        assert!(sel.contains(1, 0));
+    }
+
+    #[test]
+    fn out_of_bounds_is_never_selected() {
+        let sel = Selection::all(2, 2);
+        assert!(!sel.contains(5, 5));
+    }
+
+    #[test]
+    fn rect_selects_only_the_given_inclusive_rectangle() {
+        let sel = Selection::rect(4, 4, 1, 1, 2, 2);
+        assert!(sel.contains(1, 1) && sel.contains(2, 2));
+        assert!(!sel.contains(0, 0) && !sel.contains(3, 3));
+    }
+
+    #[test]
+    fn rect_accepts_corners_in_either_order() {
+        let forward = Selection::rect(4, 4, 1, 1, 2, 2);
+        let reversed = Selection::rect(4, 4, 2, 2, 1, 1);
+        assert_eq!(forward, reversed);
+    }
+}
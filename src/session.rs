@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Remembers the cursor position for each file and which file was most
+/// recently open, so reopening a file restores where editing left off and
+/// `tie --continue` can jump straight back into it. Stored as
+/// `tie/session_state.toml` in the platform config directory, saved when the
+/// editor quits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionState {
+    #[serde(default)]
+    by_path: HashMap<PathBuf, (u32, u32)>,
+    #[serde(default)]
+    last_file: Option<PathBuf>,
+}
+
+impl SessionState {
+    fn file_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("tie").join("session_state.toml"))
+    }
+
+    /// Loads the saved state, falling back to empty if missing, unreadable,
+    /// or fails to parse.
+    pub fn load() -> Self {
+        Self::file_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the state, best-effort - a failure to write here is a missed
+    /// convenience, not a data-loss risk, so it's not surfaced as an error.
+    pub fn save(&self) {
+        let Some(path) = Self::file_path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(contents) = toml::to_string(self) {
+            let _ = std::fs::write(path, contents);
+        }
+    }
+
+    /// The remembered cursor position for `path`, if any.
+    pub fn cursor(&self, path: &Path) -> Option<(u32, u32)> {
+        self.by_path.get(path).copied()
+    }
+
+    /// The most recently opened file, if any file has ever been remembered.
+    pub fn last_file(&self) -> Option<&Path> {
+        self.last_file.as_deref()
+    }
+
+    /// Remembers `cursor` as the last position in `path`, and `path` as the
+    /// most recently opened file.
+    pub fn set_cursor(&mut self, path: PathBuf, cursor: (u32, u32)) {
+        self.last_file = Some(path.clone());
+        self.by_path.insert(path, cursor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_paths_have_no_remembered_cursor() {
+        let state = SessionState::default();
+        assert_eq!(state.cursor(Path::new("sprite.png")), None);
+    }
+
+    #[test]
+    fn set_cursor_remembers_both_the_position_and_the_last_file() {
+        let mut state = SessionState::default();
+        state.set_cursor(PathBuf::from("sprite.png"), (3, 5));
+        assert_eq!(state.cursor(Path::new("sprite.png")), Some((3, 5)));
+        assert_eq!(state.last_file(), Some(Path::new("sprite.png")));
+    }
+
+    #[test]
+    fn serializes_through_toml() {
+        let mut state = SessionState::default();
+        state.set_cursor(PathBuf::from("sprite.png"), (3, 5));
+        let toml = toml::to_string(&state).unwrap();
+        let reloaded: SessionState = toml::from_str(&toml).unwrap();
+        assert_eq!(reloaded.cursor(Path::new("sprite.png")), Some((3, 5)));
+        assert_eq!(reloaded.last_file(), Some(Path::new("sprite.png")));
+    }
+}
@@ -0,0 +1,76 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// Name of a built-in theme, as written in the config file or `:theme` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// Colors applied consistently across the canvas border, command line,
+/// status bar, and selection highlight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub border: Color,
+    pub command_line: Color,
+    pub status_bar: Color,
+    pub selection_highlight: Color,
+    pub guide: Color,
+}
+
+impl Theme {
+    pub fn named(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Theme {
+                border: Color::DarkGray,
+                command_line: Color::White,
+                status_bar: Color::Gray,
+                selection_highlight: Color::Yellow,
+                guide: Color::Cyan,
+            },
+            ThemeName::Light => Theme {
+                border: Color::Gray,
+                command_line: Color::Black,
+                status_bar: Color::DarkGray,
+                selection_highlight: Color::Blue,
+                guide: Color::Magenta,
+            },
+            ThemeName::HighContrast => Theme {
+                border: Color::White,
+                command_line: Color::Yellow,
+                status_bar: Color::White,
+                selection_highlight: Color::Red,
+                guide: Color::Cyan,
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::named(ThemeName::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_named_theme_is_distinct() {
+        let dark = Theme::named(ThemeName::Dark);
+        let light = Theme::named(ThemeName::Light);
+        let high_contrast = Theme::named(ThemeName::HighContrast);
+        assert_ne!(dark, light);
+        assert_ne!(light, high_contrast);
+    }
+
+    #[test]
+    fn default_theme_is_dark() {
+        assert_eq!(Theme::default(), Theme::named(ThemeName::Dark));
+    }
+}
@@ -0,0 +1,82 @@
+//! The active editing tool - what the cursor represents and how it should
+//! be drawn. A seam for the growing toolset: today [`App::active_tool`]
+//! only changes the canvas cursor's glyph, switched with `:tool`, but the
+//! per-tool identity (name, glyph) now lives behind [`Tool`] instead of
+//! being guessed from scattered `App` fields (`pen_down`, `selection`) at
+//! each render site.
+//!
+//! [`App::active_tool`]: crate::app::App::active_tool
+
+/// A selectable editing tool.
+pub trait Tool {
+    /// The name shown in `:tool` parse errors and used to select it.
+    fn name(&self) -> &'static str;
+
+    /// The character drawn at the cursor's cell on the canvas.
+    fn cursor_glyph(&self) -> char;
+}
+
+/// The active editing tool, shown as the canvas cursor's glyph. Keys like
+/// `P` or `y` act the same whichever tool is active.
+///
+/// Each variant names a capability this editor already has under a
+/// different key - the pencil is [`App::paint_at_cursors`]/[`App::pen_down`],
+/// the eyedropper is [`App::copy_color_under_cursor`], and select is
+/// [`App::select_wand`]/the `:select` family - grouped here so they share
+/// one name and cursor glyph instead of each being invisible until you
+/// read the status-bar hint. Eraser, fill, line, and rect aren't included:
+/// this editor has no point-and-drag flood fill, line, or rectangle
+/// drawing yet, and "erase" is just painting with whatever color is
+/// active, not a distinct operation.
+///
+/// [`App::paint_at_cursors`]: crate::app::App::paint_at_cursors
+/// [`App::pen_down`]: crate::app::App::pen_down
+/// [`App::copy_color_under_cursor`]: crate::app::App::copy_color_under_cursor
+/// [`App::select_wand`]: crate::app::App::select_wand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToolKind {
+    #[default]
+    Pencil,
+    Eyedropper,
+    Select,
+}
+
+impl Tool for ToolKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ToolKind::Pencil => "pencil",
+            ToolKind::Eyedropper => "eyedropper",
+            ToolKind::Select => "select",
+        }
+    }
+
+    fn cursor_glyph(&self) -> char {
+        match self {
+            ToolKind::Pencil => 'X',
+            ToolKind::Eyedropper => 'o',
+            ToolKind::Select => '+',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_tool_has_a_distinct_cursor_glyph() {
+        assert_ne!(
+            ToolKind::Pencil.cursor_glyph(),
+            ToolKind::Eyedropper.cursor_glyph()
+        );
+        assert_ne!(
+            ToolKind::Eyedropper.cursor_glyph(),
+            ToolKind::Select.cursor_glyph()
+        );
+    }
+
+    #[test]
+    fn default_tool_is_pencil() {
+        assert_eq!(ToolKind::default(), ToolKind::Pencil);
+    }
+}
@@ -0,0 +1,82 @@
+//! A `:toolbar` overlay: one row listing the available tools with their
+//! hotkeys, the active one picked out in reversed video - the visible
+//! counterpart to [`crate::tool::ToolKind`] switching, the same role
+//! [`crate::palette_widget::PaletteBar`] plays for palette cells.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::widgets::Widget;
+
+use crate::tool::{Tool, ToolKind};
+
+/// How many rows [`Toolbar`] renders.
+pub const HEIGHT: u16 = 1;
+
+/// Every tool in hotkey order, paired with the key that switches to it.
+const TOOLS: [(ToolKind, &str); 3] = [
+    (ToolKind::Pencil, "F5"),
+    (ToolKind::Eyedropper, "F6"),
+    (ToolKind::Select, "F7"),
+];
+
+/// Lists [`TOOLS`] as `<hotkey> <name>` entries, highlighting `active`'s.
+pub struct Toolbar {
+    active: ToolKind,
+}
+
+impl Toolbar {
+    pub fn new(active: ToolKind) -> Self {
+        Self { active }
+    }
+}
+
+impl Widget for Toolbar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut x = area.x;
+        let right_edge = area.x + area.width;
+        for (tool, hotkey) in TOOLS {
+            let label = format!(" {hotkey} {} ", tool.name());
+            let width = (label.chars().count() as u16).min(right_edge.saturating_sub(x));
+            if width == 0 {
+                break;
+            }
+            buf.set_stringn(
+                x,
+                area.y,
+                &label,
+                width as usize,
+                ratatui::style::Style::new(),
+            );
+            if tool == self.active {
+                for dx in 0..width {
+                    buf.get_mut(x + dx, area.y).modifier |= Modifier::REVERSED;
+                }
+            }
+            x += width;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn highlights_the_active_tool_with_reversed_video() {
+        let mut terminal = Terminal::new(TestBackend::new(30, 1)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(Toolbar::new(ToolKind::Eyedropper), frame.size()))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert!(!buffer.get(0, 0).modifier.contains(Modifier::REVERSED));
+        let eyedropper_start = " F5 pencil ".chars().count() as u16;
+        assert!(buffer
+            .get(eyedropper_start, 0)
+            .modifier
+            .contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,235 @@
+//! Draws [`App`] into a ratatui frame, and a small trait that lets anything
+//! backed by a ratatui [`Backend`] render it. Because `Backend` itself is
+//! already pluggable (`CrosstermBackend` for the real terminal,
+//! `TestBackend` for golden-buffer tests), the TUI binary and test code
+//! share this one rendering path without either depending on the other's
+//! backend.
+
+use ratatui::backend::Backend;
+use ratatui::layout::{Constraint, Direction as LayoutDirection, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Widget};
+use ratatui::{Frame, Terminal};
+
+use crate::app::{App, Mode};
+use crate::canvas::Canvas;
+use crate::histogram::{self, Histogram};
+use crate::i18n;
+use crate::inspector::{self, Inspector};
+use crate::keyconfig::{self, Context};
+use crate::messages::{self, Messages};
+use crate::palette_widget::PaletteBar;
+use crate::tool::Tool;
+use crate::toolbar::{self, Toolbar};
+
+/// Draws the current state of `app` into `frame`: a bordered canvas, an
+/// optional palette key-binding strip, histogram, pixel inspector, and
+/// message log below it, and a one-line status bar showing either the
+/// command line or a hint/error.
+pub fn draw(frame: &mut Frame, app: &App) {
+    let palette_colors = app.palette_slots.colors();
+    let palette_height = if palette_colors.is_empty() { 0 } else { 1 };
+    let toolbar_height = if app.show_toolbar { toolbar::HEIGHT } else { 0 };
+    let histogram_height = if app.show_histogram {
+        histogram::HEIGHT
+    } else {
+        0
+    };
+    let inspector_height = if app.show_inspector {
+        inspector::HEIGHT
+    } else {
+        0
+    };
+    let messages_height = if app.show_messages {
+        messages::HEIGHT
+    } else {
+        0
+    };
+    let layout = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(toolbar_height),
+            Constraint::Length(histogram_height),
+            Constraint::Length(inspector_height),
+            Constraint::Length(messages_height),
+            Constraint::Length(palette_height),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+
+    let border = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(canvas_border_color(app)))
+        .title(canvas_title(app));
+    let canvas_area = border.inner(layout[0]);
+    border.render(layout[0], frame.buffer_mut());
+
+    let canvas = Canvas::new(app.image(), app.cursor.into())
+        .with_extra_cursors(&app.extra_cursors)
+        .with_floating(app.floating.as_ref())
+        .with_display(app.display)
+        .with_selection_highlight(app.selection.as_ref(), app.theme.selection_highlight)
+        .with_guides(&app.x_guides, &app.y_guides, app.theme.guide)
+        .with_cursor_glyph(app.active_tool.cursor_glyph());
+    frame.render_widget(canvas, canvas_area);
+
+    if app.show_toolbar {
+        frame.render_widget(Toolbar::new(app.active_tool), layout[1]);
+    }
+
+    if app.show_histogram {
+        frame.render_widget(Histogram::new(app.image()), layout[2]);
+    }
+
+    if app.show_inspector {
+        frame.render_widget(Inspector::new(app.image(), app.cursor.into()), layout[3]);
+    }
+
+    if app.show_messages {
+        let log: Vec<_> = app.message_log.iter().cloned().collect();
+        frame.render_widget(Messages::new(&log), layout[4]);
+    }
+
+    if !palette_colors.is_empty() {
+        let palette_bar = PaletteBar::new(palette_colors)
+            .with_selected(app.selected_palette_cell)
+            .with_page(app.palette_page);
+        frame.render_widget(palette_bar, layout[5]);
+    }
+
+    let status_line = if let Some(path) = &app.pending_overwrite {
+        let status = format!(
+            "{} {}",
+            path.display(),
+            i18n::hint_confirm_overwrite(app.locale)
+        );
+        Line::from(Span::styled(
+            status,
+            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else if app.pending_revert {
+        Line::from(Span::styled(
+            i18n::hint_confirm_revert(app.locale).to_string(),
+            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        match app.mode {
+            Mode::Normal => {
+                let status = app
+                    .last_error
+                    .clone()
+                    .or_else(|| app.status_message.clone())
+                    .unwrap_or_else(|| {
+                        if app.quiet {
+                            String::new()
+                        } else {
+                            let context = if app.selection.is_some() {
+                                Context::Select
+                            } else {
+                                Context::Normal
+                            };
+                            keyconfig::hint_line(context, app.locale)
+                        }
+                    });
+                Line::from(Span::styled(status, Style::new().fg(app.theme.status_bar)))
+            }
+            Mode::Command => command_line_spans(app),
+        }
+    };
+    frame.render_widget(Paragraph::new(status_line), layout[6]);
+
+    if app.mode == Mode::Command {
+        let x = layout[6].x + 1 + app.command_cursor as u16;
+        frame.set_cursor(x, layout[6].y);
+    }
+}
+
+/// Colors the Canvas block's border by what the next keystroke would do,
+/// so the active state is glanceable without reading the status bar:
+/// green while the pen is down and movement paints, yellow while a
+/// selection is active, the theme's border color otherwise. Checked in that order since a
+/// pen-down stroke is the more urgent thing to notice even with a
+/// selection still in place.
+fn canvas_border_color(app: &App) -> Color {
+    if app.pen_down {
+        Color::Green
+    } else if app.selection.is_some() {
+        Color::Yellow
+    } else {
+        app.theme.border
+    }
+}
+
+/// Builds the Canvas block's title: the working file name (or a
+/// placeholder if none has been set yet), its dimensions, and a trailing
+/// `[+]` when there are unsaved changes.
+fn canvas_title(app: &App) -> String {
+    let name = app
+        .path
+        .as_deref()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| i18n::no_name_label(app.locale).to_string());
+    let image = app.image();
+    let dirty = if app.is_dirty() { " [+]" } else { "" };
+    format!(
+        "Canvas — {name} {}×{}{dirty}",
+        image.width(),
+        image.height()
+    )
+}
+
+/// Builds the command-line status line, highlighting
+/// [`App::invalid_token`] in red and appending [`App::last_error`] inline
+/// instead of clearing the line when a typed command is rejected. Borrows
+/// straight from `app` rather than `Line<'static>`, so the common case -
+/// typing with no rejected token and no error, which is every frame of a
+/// fast-typed command - builds its spans without allocating a `String` at
+/// all; the `:` prefix only needs `format!`-ing into its own owned string
+/// when an invalid token has to be located within it.
+fn command_line_spans(app: &App) -> Line<'_> {
+    let style = Style::new().fg(app.theme.command_line);
+    let error_style = Style::new().fg(Color::Red).add_modifier(Modifier::BOLD);
+
+    let mut spans = match app.invalid_token.as_deref().filter(|t| !t.is_empty()) {
+        Some(token) => {
+            let text = format!(":{}", app.command_line);
+            match text.find(token) {
+                Some(pos) => {
+                    let (before, rest) = text.split_at(pos);
+                    let (bad, after) = rest.split_at(token.len());
+                    vec![
+                        Span::styled(before.to_string(), style),
+                        Span::styled(bad.to_string(), error_style),
+                        Span::styled(after.to_string(), style),
+                    ]
+                }
+                None => vec![Span::styled(text, style)],
+            }
+        }
+        None => vec![Span::raw(":"), Span::styled(&app.command_line, style)],
+    };
+
+    if let Some(message) = &app.last_error {
+        spans.push(Span::styled(format!("  {message}"), error_style));
+    }
+    Line::from(spans)
+}
+
+/// Renders an [`App`] one frame at a time. Implemented for any ratatui
+/// `Terminal`, so the interactive binary (a real terminal, via
+/// `CrosstermBackend`) and tests (an in-memory `TestBackend`) drive the
+/// exact same rendering code through whichever backend they plug in.
+pub trait Renderer {
+    fn render_app(&mut self, app: &App) -> std::io::Result<()>;
+}
+
+impl<B: Backend> Renderer for Terminal<B> {
+    fn render_app(&mut self, app: &App) -> std::io::Result<()> {
+        self.draw(|frame| draw(frame, app))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+//! Golden-buffer tests of the rendered UI. Drives the same `draw`/`Renderer`
+//! path the real terminal uses, but against ratatui's in-memory
+//! `TestBackend`, so rendering regressions in the canvas border, cursor, and
+//! status bar show up as plain assertion failures instead of needing eyes
+//! on a real terminal.
+
+use ratatui::backend::TestBackend;
+use ratatui::style::{Color, Modifier};
+use ratatui::Terminal;
+
+use tie::image::Image;
+use tie::selection::Selection;
+use tie::{Editor, Renderer};
+
+fn sample_editor() -> Editor {
+    let mut image = Image::new(3, 2);
+    image.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+    Editor::new(image, None)
+}
+
+/// The border+canvas region for a 3x2 image: a 1-cell border around it, so
+/// 5 cells wide, 4 tall.
+fn canvas_snapshot(terminal: &Terminal<TestBackend>) -> Vec<String> {
+    let buffer = terminal.backend().buffer();
+    (0..4)
+        .map(|y| (0..5).map(|x| buffer.get(x, y).symbol()).collect())
+        .collect()
+}
+
+#[test]
+fn renders_border_and_cursor() {
+    let mut terminal = Terminal::new(TestBackend::new(5, 5)).unwrap();
+    let app = sample_editor();
+
+    terminal.render_app(&app).unwrap();
+
+    // The top border is truncated by the Canvas title ("Canvas — [No Name] 3×2").
+    assert_eq!(
+        canvas_snapshot(&terminal),
+        vec!["┌Can┐", "│X  │", "│   │", "└───┘"]
+    );
+}
+
+#[test]
+fn renders_status_hint_in_normal_mode() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+    let app = sample_editor();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let status: String = (0..30).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert!(status.trim_end().starts_with(": command"));
+}
+
+#[test]
+fn quiet_mode_blanks_the_begin_command_hint() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+    let mut app = sample_editor();
+    app.quiet = true;
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let status: String = (0..30).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert_eq!(status.trim_end(), "");
+}
+
+#[test]
+fn renders_a_palette_bar_with_digit_keys_when_a_palette_is_active() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 6)).unwrap();
+    let mut app = sample_editor();
+    app.execute("palette use pico8").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    // Row 4 (after the 4-row bordered canvas) is the palette bar; its first
+    // cells are labeled with the digit keys 0-9.
+    let labels: String = (0..10).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert_eq!(labels, "0123456789");
+}
+
+#[test]
+fn renders_a_palette_bar_with_digit_keys_relative_to_the_active_page() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 6)).unwrap();
+    let mut app = sample_editor();
+    app.execute("palette use pico8").unwrap();
+    app.set_palette_page(1);
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    // Pico-8 has 16 cells; on page 1, cells 0-9 have no digit label and
+    // cells 10-15 get labels 0-5.
+    let unlabeled: String = (0..10).map(|x| buffer.get(x, 4).symbol()).collect();
+    let labeled: String = (10..16).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert_eq!(unlabeled, " ".repeat(10));
+    assert_eq!(labeled, "012345");
+}
+
+#[test]
+fn renders_a_toolbar_below_the_canvas_when_toggled_on() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 6)).unwrap();
+    let mut app = sample_editor();
+    app.execute("toolbar").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    // Row 4 (after the 4-row bordered canvas) is the toolbar's one row.
+    let row: String = (0..30).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert!(row.trim_end().starts_with(" F5 pencil "));
+}
+
+#[test]
+fn renders_a_histogram_below_the_canvas_when_toggled_on() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 9)).unwrap();
+    let mut app = sample_editor();
+    app.execute("histogram").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    // Rows 4-7 (after the 4-row bordered canvas) are the histogram's four
+    // channel rows, each starting with its channel label.
+    let labels: String = (0..4).map(|row| buffer.get(0, 4 + row).symbol()).collect();
+    assert_eq!(labels, "RGBL");
+}
+
+#[test]
+fn renders_a_pixel_inspector_below_the_canvas_when_toggled_on() {
+    let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+    let mut app = sample_editor();
+    app.execute("inspect").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    // Row 4 (after the 4-row bordered canvas) is the inspector's top row;
+    // its middle row/column is the cursor's own cell, which starts at
+    // (0, 0) - the red pixel set up by `sample_editor`.
+    let center_cell: String = (16..23).map(|col| buffer.get(col, 6).symbol()).collect();
+    assert_eq!(center_cell, "#ff0000");
+}
+
+#[test]
+fn renders_the_message_log_below_the_canvas_when_toggled_on() {
+    let mut terminal = Terminal::new(TestBackend::new(40, 15)).unwrap();
+    let mut app = sample_editor();
+    app.execute("histogram").unwrap();
+    app.execute("messages").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    // Row 8 (after the 4-row canvas and the 4-row histogram) is the
+    // message log's first line: ":histogram" ran ok.
+    let line: String = (0..12).map(|x| buffer.get(x, 8).symbol()).collect();
+    assert_eq!(line, ":histogram  ");
+}
+
+#[test]
+fn invalid_command_keeps_the_line_and_highlights_the_bad_token() {
+    let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+    let mut app = sample_editor();
+    app.mode = tie::app::Mode::Command;
+    app.command_line = "fill 300 0 0 255".to_string();
+    assert!(app.execute(&app.command_line.clone()).is_err());
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let status: String = (0..40).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert!(status.starts_with(":fill 300 0 0 255"));
+    // "300" starts right after ":fill " (6 characters).
+    assert_eq!(buffer.get(6, 4).fg, Color::Red);
+}
+
+#[test]
+fn command_line_edit_cursor_tracks_its_position() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+    let mut app = sample_editor();
+    app.mode = tie::app::Mode::Command;
+    for c in "write".chars() {
+        app.command_insert(c);
+    }
+    app.command_move_left();
+    app.command_move_left();
+
+    terminal.render_app(&app).unwrap();
+
+    // ":write" - cursor at char index 3, plus 1 for the leading ':'.
+    assert_eq!(terminal.get_cursor().unwrap(), (4, 4));
+}
+
+#[test]
+fn canvas_title_shows_the_file_name_dimensions_and_dirty_flag() {
+    use std::path::PathBuf;
+
+    let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+    let mut image = Image::new(3, 2);
+    image.set_pixel(0, 0, [255, 0, 0, 255]).unwrap();
+    let mut app = Editor::new(image, Some(PathBuf::from("sprite.png")));
+
+    terminal.render_app(&app).unwrap();
+    let buffer = terminal.backend().buffer();
+    let title: String = (0..30).map(|x| buffer.get(x, 0).symbol()).collect();
+    assert!(title.trim_end().starts_with("┌Canvas — sprite.png 3×2"));
+    assert!(!title.contains("[+]"));
+
+    app.set_pixel(1, 0, [0, 255, 0, 255]).unwrap();
+    terminal.render_app(&app).unwrap();
+    let buffer = terminal.backend().buffer();
+    let title: String = (0..30).map(|x| buffer.get(x, 0).symbol()).collect();
+    assert!(title.contains("[+]"));
+}
+
+#[test]
+fn renders_an_overwrite_confirmation_prompt_when_one_is_pending() {
+    let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+    let mut app = sample_editor();
+    app.pending_overwrite = Some("sprite.png".into());
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let status: String = (0..40).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert!(status.starts_with("sprite.png"));
+    assert!(status.contains("overwrite"));
+    assert_eq!(buffer.get(0, 4).fg, Color::Red);
+}
+
+#[test]
+fn renders_a_revert_confirmation_prompt_when_one_is_pending() {
+    let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+    let mut app = sample_editor();
+    app.pending_revert = true;
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let status: String = (0..40).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert!(status.contains("discard"));
+    assert_eq!(buffer.get(0, 4).fg, Color::Red);
+}
+
+#[test]
+fn renders_selection_highlight_as_underline() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+    let mut app = sample_editor();
+    let mut selection = Selection::empty(app.image().width(), app.image().height());
+    selection.set(1, 1, true);
+    app.selection = Some(selection);
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let selected = buffer.get(1 + 1, 1 + 1); // +1 for the border on each axis
+    assert_eq!(selected.fg, app.theme.selection_highlight);
+    assert!(selected.modifier.contains(Modifier::UNDERLINED));
+
+    let unselected = buffer.get(1, 1);
+    assert_ne!(unselected.fg, Color::Yellow);
+}
+
+#[test]
+fn canvas_border_reflects_pen_and_selection_state() {
+    let mut terminal = Terminal::new(TestBackend::new(5, 5)).unwrap();
+    let mut app = sample_editor();
+
+    terminal.render_app(&app).unwrap();
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer.get(0, 0).fg, app.theme.border);
+
+    let mut selection = Selection::empty(app.image().width(), app.image().height());
+    selection.set(0, 0, true);
+    app.selection = Some(selection);
+    terminal.render_app(&app).unwrap();
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer.get(0, 0).fg, Color::Yellow);
+
+    app.toggle_pen();
+    terminal.render_app(&app).unwrap();
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer.get(0, 0).fg, Color::Green);
+}
+
+#[test]
+fn canvas_cursor_glyph_follows_the_active_tool() {
+    let mut terminal = Terminal::new(TestBackend::new(5, 5)).unwrap();
+    let mut app = sample_editor();
+    app.execute("tool eyedropper").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer.get(1, 1).symbol(), "o");
+}
+
+#[test]
+fn renders_a_guide_line_tinted_across_the_column() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+    let mut app = sample_editor();
+    app.execute("guide x 1").unwrap();
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let guided = buffer.get(1 + 1, 1); // +1 for the border on each axis
+    assert_eq!(guided.fg, app.theme.guide);
+
+    let unguided = buffer.get(1, 1);
+    assert_ne!(unguided.fg, app.theme.guide);
+}
+
+#[test]
+fn status_hint_switches_to_select_bindings_when_a_selection_is_active() {
+    let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+    let mut app = sample_editor();
+    let selection = Selection::empty(app.image().width(), app.image().height());
+    app.selection = Some(selection);
+
+    terminal.render_app(&app).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let status: String = (0..30).map(|x| buffer.get(x, 4).symbol()).collect();
+    assert!(status.trim_end().contains("grab"));
+}